@@ -1,7 +1,45 @@
 import test from 'ava'
 
-import { Encoder, Frame, DecodeOptions, ColorOutput } from '../index'
+import {
+  Encoder,
+  Decoder,
+  Frame,
+  DecodeOptions,
+  ColorOutput,
+  FrameBufType,
+  ColorSpace,
+  DistanceMetric,
+  PaletteOrder,
+  rgbToColorSpace,
+  colorSpaceToRgb,
+  colorDistance,
+  nearestColor,
+  hexToRgba,
+  hexToRgb,
+  rgbaToHex,
+  rgbToHex,
+  indexedToHex,
+  indexedToRgba,
+  rgbaToRgb,
+  rgbToRgba,
+  bgraToRgba,
+  premultiplyAlpha,
+  unpremultiplyAlpha,
+  extractPalette,
+  compactPalette,
+  compare,
+  compareFrames,
+  dhash,
+  phash,
+  hashDistance,
+  fingerprintSimilarity,
+  createDecodeStream,
+  createEncodeStream,
+} from '../stream'
+import type { Progress } from '../stream'
 import { readFileSync, writeFileSync } from 'node:fs'
+import { Readable } from 'node:stream'
+import { pipeline } from 'node:stream/promises'
 import { createCanvas } from '@napi-rs/canvas'
 
 test('encoder with canvas', (t) => {
@@ -95,3 +133,565 @@ test('encoder with indexed pixels', (t) => {
   writeFileSync('./__test__/encoderoutput2.gif', buffer)
   t.assert('mrawww')
 })
+
+test('remux raw frames without re-encoding', (t) => {
+  let options = new DecodeOptions()
+  options.skipFrameDecoding(true)
+
+  let input = options.readInfo(readFileSync('./__test__/encoderoutput2.gif'))
+  let gif = new Encoder(input.width, input.height, input.globalPalette)
+  gif.setRepeat(-1)
+
+  let f
+  let count = 0
+  while ((f = input.readNextFrame())) {
+    gif.addRawFrame(f)
+    count++
+  }
+
+  let output = new Decoder(gif.getBuffer())
+  let frames = 0
+  while (output.readNextFrame()) frames++
+  t.is(frames, count)
+})
+
+test('remux raw frames between gifs with different global palettes', (t) => {
+  const solid = (palette: number[]) => {
+    const encoder = new Encoder(2, 2, new Uint8Array(palette))
+    encoder.addFrame(Frame.fromIndexedPixels(2, 2, new Uint8Array(4)))
+    return encoder.getBuffer()
+  }
+  const red = solid([255, 0, 0, 0, 0, 0])
+  const blue = solid([0, 0, 255, 0, 0, 0])
+
+  const options = new DecodeOptions()
+  options.skipFrameDecoding(true)
+  const gif = new Encoder(2, 2, new Decoder(red).globalPalette)
+  for (const input of [red, blue]) {
+    gif.addRawFrame(options.readInfo(input).readNextFrame()!)
+  }
+
+  const output = new Decoder(gif.getBuffer())
+  const colors = [output.readNextFrame()!, output.readNextFrame()!].map((f) => [
+    ...f.convertTo(FrameBufType.Rgba, output.globalPalette).buffer.subarray(0, 3),
+  ])
+  t.deepEqual(colors, [
+    [255, 0, 0],
+    [0, 0, 255],
+  ])
+})
+
+test('lossy encoding produces smaller output', (t) => {
+  const input = new Decoder(readFileSync('./__test__/encoderinput.gif'))
+  const frames = []
+  let f
+  while ((f = input.readNextFrame())) frames.push(f)
+
+  const encode = (lossy: number) => {
+    let gif = new Encoder(input.width, input.height, input.globalPalette)
+    gif.setLossy(lossy)
+    frames.forEach((f) => gif.addFrame(f))
+    return gif.getBuffer()
+  }
+
+  const lossless = encode(0)
+  const lossy = encode(80)
+  t.true(lossy.length <= lossless.length)
+
+  const output = new Decoder(lossy)
+  let count = 0
+  while (output.readNextFrame()) count++
+  t.is(count, frames.length)
+})
+
+test('encoder with parallel rgba frames', (t) => {
+  const frames = [0xff0000, 0x00ff00, 0x0000ff].map((color) => {
+    const buffer = new Uint8Array(64 * 64 * 4)
+    for (let i = 0; i < buffer.length; i += 4) {
+      buffer[i] = color >> 16
+      buffer[i + 1] = (color >> 8) & 0xff
+      buffer[i + 2] = color & 0xff
+      buffer[i + 3] = 255
+    }
+    return buffer
+  })
+
+  let gif = new Encoder(64, 64)
+  gif.addRgbaFrames(frames, [10, 20, 30], 10, 2)
+
+  const output = new Decoder(gif.getBuffer())
+  const delays = []
+  let f
+  while ((f = output.readNextFrame())) delays.push(f.delay)
+  t.deepEqual(delays, [10, 20, 30])
+})
+
+test('export frames to png and apng', (t) => {
+  const PNG_SIGNATURE = Buffer.from([0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a])
+  const input = readFileSync('./__test__/encoderinput.gif')
+
+  const gif = new Decoder(input)
+  const png = gif.readNextFrame()!.toPng(gif.globalPalette)
+  t.deepEqual(png.subarray(0, 8), PNG_SIGNATURE)
+
+  const apng = new Decoder(input).toApng()
+  t.deepEqual(apng.subarray(0, 8), PNG_SIGNATURE)
+  t.true(apng.includes('acTL'))
+})
+
+test('import png and apng into the encoder', (t) => {
+  const input = new Decoder(readFileSync('./__test__/encoderinput.gif'))
+  const apng = input.toApng()
+
+  const frames = Frame.fromApng(apng)
+  const gif = new Encoder(input.width, input.height)
+  frames.forEach((f) => gif.addFrame(f))
+
+  const output = new Decoder(gif.getBuffer())
+  let count = 0
+  while (output.readNextFrame()) count++
+  t.is(count, frames.length)
+
+  const first = Frame.fromPng(apng)
+  t.is(first.width, input.width)
+})
+
+test('apng frames using the source blend operation clear the canvas', (t) => {
+  // A red 4x4 frame, then a transparent 2x2 frame at 1,1 that replaces the pixels below it
+  const frames = Frame.fromApng(readFileSync('./__test__/source-blend.png'))
+  const gif = new Encoder(4, 4)
+  frames.forEach((f) => gif.addFrame(f))
+
+  const output = new Decoder(gif.getBuffer())
+  output.readNextCompositedFrame()
+  const canvas = output.readNextCompositedFrame()!.buffer
+  t.deepEqual([...canvas.subarray(0, 4)], [255, 0, 0, 255])
+  t.is(canvas[(1 * 4 + 1) * 4 + 3], 0)
+  t.is(canvas[(2 * 4 + 2) * 4 + 3], 0)
+  t.is(canvas[(3 * 4 + 3) * 4 + 3], 255)
+})
+
+test('sprite sheet round trip', (t) => {
+  const input = new Decoder(readFileSync('./__test__/encoderinput.gif'))
+  const sheet = input.toSpriteSheet({ columns: 2, padding: 1 })
+  t.is(sheet.frames.length, 3)
+  t.is(sheet.width, input.width * 2 + 3)
+  t.is(JSON.parse(sheet.atlas).frames.length, 3)
+
+  const frames = Frame.fromSpriteSheet(sheet.buffer, sheet.frames, sheet.width)
+  const gif = new Encoder(sheet.frameWidth, sheet.frameHeight)
+  frames.forEach((f) => gif.addFrame(f))
+
+  const output = new Decoder(gif.getBuffer())
+  let count = 0
+  while (output.readNextCompositedFrame()) count++
+  t.is(count, 3)
+
+  // A half transparent red pixel next to an opaque blue one, imported with the same alpha handling as `Frame.fromRgba`
+  const rgba = Uint8Array.from([255, 0, 0, 100, 0, 0, 255, 255])
+  const placement = [{ x: 0, y: 0, width: 2, height: 1, left: 0, top: 0, delay: 0 }]
+  const alphaOf = (frame: Frame) => [...frame.convertTo(FrameBufType.Rgba).buffer].filter((_, i) => i % 4 === 3)
+  t.deepEqual(alphaOf(Frame.fromSpriteSheet(rgba, placement, 2)[0]), [255, 255])
+  t.deepEqual(alphaOf(Frame.fromSpriteSheet(rgba, placement, 2, null, { threshold: 128 })[0]), [0, 255])
+})
+
+test('decoder iterators', async (t) => {
+  const input = readFileSync('./__test__/encoderinput.gif')
+
+  t.is([...new Decoder(input)].length, 3)
+
+  const composited = [...new Decoder(input).frames(true)]
+  t.is(composited.length, 3)
+  t.true(composited.every((f) => f.width === composited[0].width && f.bufType === FrameBufType.Rgba))
+
+  const delays = []
+  for await (const frame of new Decoder(input)) delays.push(frame.delay)
+  t.is(delays.length, 3)
+
+  const gif = new Decoder(input)
+  t.truthy(await gif.readNextFrameAsync())
+  t.is([...gif].length, 2)
+})
+
+test('decode and encode streams', async (t) => {
+  const input = readFileSync('./__test__/encoderinput.gif')
+  const gif = new Decoder(input)
+
+  const delays: number[] = []
+  const chunks: Buffer[] = []
+  await pipeline(
+    Readable.from([input.subarray(0, 100), input.subarray(100)]),
+    createDecodeStream({ highWaterMark: 1 }),
+    async function* (frames: AsyncIterable<Frame>) {
+      for await (const frame of frames) {
+        delays.push(frame.delay)
+        yield frame
+      }
+    },
+    createEncodeStream(gif.width, gif.height, { palette: gif.globalPalette!, repeat: -1 }),
+    async (bytes: AsyncIterable<Buffer>) => {
+      for await (const chunk of bytes) chunks.push(chunk)
+    },
+  )
+
+  t.is(delays.length, 3)
+  t.true(chunks.length > 1)
+  t.deepEqual([...new Decoder(Buffer.concat(chunks))].map((f) => f.delay), delays)
+
+  await t.throwsAsync(
+    pipeline(Readable.from([Buffer.from('not a gif')]), createDecodeStream(), async (frames: AsyncIterable<Frame>) => {
+      for await (const _ of frames);
+    }),
+  )
+})
+
+test('decode stream emits frames before the input ends', async (t) => {
+  const input = readFileSync('./__test__/encoderinput.gif')
+  const stream = createDecodeStream()
+  let written = 0
+  const writtenAtFrame: number[] = []
+  stream.on('data', () => writtenAtFrame.push(written))
+  const ended = new Promise((resolve) => stream.on('end', resolve))
+
+  for (let i = 0; i < input.length; i += 500) {
+    await new Promise((resolve) => stream.write(input.subarray(i, i + 500), resolve))
+    written = Math.min(i + 500, input.length)
+  }
+  stream.end()
+  await ended
+
+  t.is(writtenAtFrame.length, 3)
+  t.true(writtenAtFrame[0] < input.length)
+  t.true(writtenAtFrame[1] < input.length)
+})
+
+test('async tasks report progress and can be aborted', async (t) => {
+  const input = readFileSync('./__test__/encoderinput.gif')
+
+  const progress: Progress[] = []
+  const apng = await new Decoder(input).toApngAsync({ onProgress: (p) => progress.push(p) })
+  await new Promise((resolve) => setImmediate(resolve))
+  t.deepEqual(apng, new Decoder(input).toApng())
+  t.is(progress.length, 3)
+  t.is(progress[2].bytes, input.length)
+
+  const frames = [0, 85, 170, 255].map((v) => new Uint8Array(32 * 32 * 4).fill(v))
+  const gif = new Encoder(32, 32)
+  const done: number[] = []
+  await gif.addRgbaFramesAsync(frames, null, 10, null, { onProgress: (p) => done.push(p.frames) })
+  await new Promise((resolve) => setImmediate(resolve))
+  t.deepEqual(done.sort(), [1, 2, 3, 4])
+  t.is([...new Decoder(gif.getBuffer())].length, 4)
+
+  t.deepEqual(await new Decoder(input).extractPaletteAsync(3), new Decoder(input).extractPalette(3))
+  t.deepEqual(await new Decoder(input).statsAsync(), new Decoder(input).stats())
+  t.deepEqual(await new Decoder(input).fingerprintAsync(), new Decoder(input).fingerprint())
+
+  const controller = new AbortController()
+  controller.abort()
+  const error = await t.throwsAsync(new Decoder(input).toSpriteSheetAsync(null, { signal: controller.signal }))
+  t.is((error as Error & { code: string }).code, 'Cancelled')
+  await t.throwsAsync(new Decoder(input).fingerprintAsync({ signal: controller.signal }), { code: 'Cancelled' })
+})
+
+test('frame buffers are views into the frame', (t) => {
+  const gif = new Decoder(readFileSync('./__test__/encoderinput.gif'))
+  const frame = gif.readNextFrame()!
+  t.is(frame.buffer, frame.buffer)
+
+  frame.buffer[0] = 1
+  const encoder = new Encoder(gif.width, gif.height, gif.globalPalette)
+  encoder.addFrame(frame)
+  const output = encoder.finish()
+  t.is(new Decoder(output).readNextFrame()!.buffer[0], 1)
+  t.is(encoder.getBuffer().length, 1)
+})
+
+test('decoder input is copied before reading off the main thread', async (t) => {
+  const input = readFileSync('./__test__/encoderinput.gif')
+  const data = new Uint8Array(input)
+  const gif = new Decoder(data)
+  await gif.readNextFrameAsync()
+  data.fill(0)
+
+  const expected = new Decoder(input)
+  expected.readNextFrame()
+  t.deepEqual(gif.readNextFrame()!.buffer, expected.readNextFrame()!.buffer)
+})
+
+test('frame conversion and color outputs', (t) => {
+  const input = readFileSync('./__test__/encoderinput.gif')
+  const gif = new Decoder(input)
+  const frame = gif.readNextFrame()!
+  const pixels = frame.width * frame.height
+
+  const rgba = frame.convertTo(FrameBufType.Rgba, gif.globalPalette)
+  t.is(rgba.bufType, FrameBufType.Rgba)
+  t.is(rgba.buffer.length, pixels * 4)
+  t.is(frame.convertTo(FrameBufType.Rgb, gif.globalPalette).buffer.length, pixels * 3)
+
+  const hex = rgba.convertTo(FrameBufType.Hex)
+  t.is(hex.buffer.length, pixels * 9)
+  t.deepEqual(hex.convertTo(FrameBufType.IndexedPixels).convertTo(FrameBufType.Rgba).buffer, rgba.buffer)
+
+  for (const [output, bufType, size] of [
+    [ColorOutput.Rgb, FrameBufType.Rgb, 3],
+    [ColorOutput.Hex, FrameBufType.Hex, 9],
+  ] as const) {
+    const options = new DecodeOptions()
+    options.setColorOutput(output)
+    const f = options.readInfo(input).readNextFrame()!
+    t.is(f.bufType, bufType)
+    t.is(f.buffer.length, f.width * f.height * size)
+
+    const encoder = new Encoder(gif.width, gif.height)
+    encoder.addFrame(f)
+    t.is([...new Decoder(encoder.finish())].length, 1)
+  }
+})
+
+test('color space conversions and distances', (t) => {
+  const pixels = Uint8Array.from([255, 0, 0, 0, 255, 0, 12, 34, 56, 200, 150, 100])
+  for (const space of [ColorSpace.Hsl, ColorSpace.Hsv, ColorSpace.Lab, ColorSpace.Lch, ColorSpace.Oklab]) {
+    t.deepEqual([...colorSpaceToRgb(rgbToColorSpace(pixels, space), space)], [...pixels])
+  }
+
+  const [l, a, b] = rgbToColorSpace(pixels.subarray(0, 3), ColorSpace.Lab)
+  t.is(Math.round(l), 53)
+  t.is(Math.round(a), 80)
+  t.is(Math.round(b), 67)
+
+  const rgba = Uint8Array.from([10, 20, 30, 128])
+  t.is(rgbToColorSpace(rgba, ColorSpace.Oklab, true).length, 4)
+  t.deepEqual([...colorSpaceToRgb(rgbToColorSpace(rgba, ColorSpace.Oklab, true), ColorSpace.Oklab, true)], [...rgba])
+
+  const black = Uint8Array.from([0, 0, 0])
+  const white = Uint8Array.from([255, 255, 255])
+  t.is(Math.round(colorDistance(black, white, DistanceMetric.Cie76)[0]), 100)
+  t.is(Math.round(colorDistance(black, white, DistanceMetric.Ciede2000)[0]), 100)
+  t.is(Math.round(colorDistance(black, white, DistanceMetric.Oklab)[0]), 1)
+  t.is(colorDistance(white, white, DistanceMetric.Ciede2000)[0], 0)
+  // The CIEDE2000 reference pairs of Sharma et al. are given in Lab, so they are checked in src/color.rs.
+
+  t.is(nearestColor(Uint8Array.from([0, 0, 0, 255, 0, 0, 255, 255, 255]), Uint8Array.from([200, 30, 20]), DistanceMetric.Ciede2000), 1)
+  t.is(nearestColor(new Uint8Array(), black, DistanceMetric.Oklab), null)
+})
+
+test('css color parsing', (t) => {
+  t.deepEqual(
+    [...hexToRgba(['#f00', 'Red', 'transparent', 'rgb(0 128 255 / 50%)', 'rgba(0, 0, 255, 0.5)', 'hsl(120deg 100% 50%)', 'hwb(0 0% 0%)'])],
+    [255, 0, 0, 255, 255, 0, 0, 255, 0, 0, 0, 0, 0, 128, 255, 128, 0, 0, 255, 128, 0, 255, 0, 255, 255, 0, 0, 255],
+  )
+  t.deepEqual([...hexToRgba(['oklch(62.8% 0.2577 29.23)'])], [255, 0, 0, 255])
+  t.deepEqual([...hexToRgb(['rebeccapurple', '#abc'])], [102, 51, 153, 170, 187, 204])
+
+  t.throws(() => hexToRgba(['#ffgg00']), { message: 'Invalid hex character `g` at index 3 in #ffgg00' })
+  t.throws(() => hexToRgba(['notacolor']), { message: 'Unknown color name: notacolor' })
+  t.throws(() => hexToRgba(['rgb(1, 2)']))
+  t.throws(() => hexToRgb(['#abcd']))
+})
+
+test('hex utilities return one string per color', (t) => {
+  const rgba = Uint8Array.from([255, 0, 0, 255, 0, 255, 0, 128, 17, 34, 51, 0])
+  t.deepEqual(rgbaToHex(rgba), ['#FF0000', '#00FF0080', '#11223300'])
+  t.deepEqual(rgbaToHex(rgba, true, true), ['#F00F', '#00FF0080', '#1230'])
+  t.deepEqual(rgbaToHex(rgba, null, null, { lowercase: true }), ['#ff0000', '#00ff0080', '#11223300'])
+  t.deepEqual(rgbaToHex(rgba, null, null, { css: true }), ['rgb(255, 0, 0)', 'rgba(0, 255, 0, 0.502)', 'rgba(17, 34, 51, 0)'])
+  t.deepEqual([...hexToRgba(rgbaToHex(rgba, null, null, { css: true }))], [...rgba])
+
+  t.deepEqual(rgbToHex(Uint8Array.from([255, 0, 0, 171, 205, 239]), true, { lowercase: true }), ['#f00', '#abcdef'])
+  t.deepEqual(indexedToHex(Uint8Array.from([0, 1, 5]), Uint8Array.from([255, 0, 0, 0, 0, 0]), 1), ['#FF0000', '#00000000', '#000000'])
+})
+
+test('pixel format conversions', (t) => {
+  // 37 pixels, so the SIMD loops leave a remainder
+  const rgba = Uint8Array.from({ length: 37 * 4 }, (_, i) => (i * 89 + 7) % 256)
+  const rgb = rgba.filter((_, i) => i % 4 !== 3)
+
+  t.deepEqual([...rgbaToRgb(rgba)], [...rgb])
+  t.deepEqual([...rgbToRgba(rgb)], [...rgba.map((v, i) => (i % 4 === 3 ? 255 : v))])
+  t.deepEqual([...bgraToRgba(rgba)], [...rgba.map((_, i) => rgba[i % 4 === 0 ? i + 2 : i % 4 === 2 ? i - 2 : i])])
+
+  const premultiplied = premultiplyAlpha(rgba)
+  t.deepEqual([...premultiplied], [...rgba.map((v, i) => (i % 4 === 3 ? v : Math.round((v * rgba[i - (i % 4) + 3]) / 255)))])
+  t.deepEqual([...unpremultiplyAlpha(Uint8Array.from([128, 64, 0, 128, 9, 9, 9, 0]))], [255, 128, 0, 128, 9, 9, 9, 0])
+
+  const indices = Uint8Array.from({ length: 37 }, (_, i) => i % 3)
+  const palette = Uint8Array.from([255, 0, 0, 0, 255, 0])
+  t.deepEqual([...indexedToRgba(indices, palette, 1).subarray(0, 12)], [255, 0, 0, 255, 0, 255, 0, 0, 0, 0, 0, 255])
+})
+
+test('frames from bgra, grayscale and padded pixels', (t) => {
+  // Two rows of two pixels, the first padded to 10 bytes
+  const bgra = Uint8Array.from([0, 0, 255, 255, 255, 0, 0, 255, 0, 0, 0, 255, 0, 255, 0, 0, 0, 0])
+  const frame = Frame.fromPixels(2, 2, bgra, { format: 'bgra', stride: 10 })
+  t.is(frame.bufType, FrameBufType.IndexedPixels)
+  t.deepEqual([...frame.convertTo(FrameBufType.Rgba).buffer], [255, 0, 0, 255, 0, 0, 255, 255, 0, 255, 0, 255, 0, 0, 0, 0])
+
+  const gray = Frame.fromPixels(2, 1, Uint8Array.from([0, 255]), { format: 'gray' })
+  t.deepEqual([...gray.convertTo(FrameBufType.Rgba).buffer], [0, 0, 0, 255, 255, 255, 255, 255])
+  const argb = Frame.fromPixels(1, 1, Uint8Array.from([255, 10, 20, 30]), { format: 'argb' })
+  t.deepEqual([...argb.convertTo(FrameBufType.Rgba).buffer], [10, 20, 30, 255])
+
+  t.throws(() => Frame.fromPixels(2, 2, bgra, { format: 'bgra', stride: 4 }), { message: /Stride/ })
+  t.throws(() => Frame.fromPixels(2, 2, bgra), { message: /Buffer size mismatch/ })
+})
+
+test('alpha threshold, matting and a reserved transparent index', (t) => {
+  const rgba = Uint8Array.from([255, 0, 0, 255, 0, 0, 255, 100, 0, 255, 0, 0, 10, 10, 10, 200])
+  const toRgba = (frame: Frame) => [...frame.convertTo(FrameBufType.Rgba).buffer]
+
+  t.deepEqual(toRgba(Frame.fromRgba(2, 2, rgba.slice(), null, { threshold: 128 })), [255, 0, 0, 255, 0, 0, 0, 0, 0, 0, 0, 0, 10, 10, 10, 255])
+  t.deepEqual(toRgba(Frame.fromRgba(2, 2, rgba.slice(), null, { threshold: 50, matte: 'white' })), [255, 0, 0, 255, 155, 155, 255, 255, 0, 0, 0, 0, 63, 63, 63, 255])
+
+  const reserved = Frame.fromRgba(2, 2, rgba.slice(), null, { transparentIndex: 5 })
+  t.is(reserved.transparent, 5)
+  t.deepEqual([...reserved.buffer].map((i) => i === 5), [false, false, true, false])
+
+  // Half transparent pixels are dithered to roughly half of them transparent
+  const soft = new Uint8Array(16 * 16 * 4).map((_, i) => (i % 4 === 3 ? 128 : 200))
+  const dithered = Frame.fromRgba(16, 16, soft, null, { dither: true })
+  t.is([...dithered.buffer].filter((i) => i === dithered.transparent).length, 128)
+})
+
+test('palette extraction', (t) => {
+  const pixels = (rgba: number[], n: number) => Array.from({ length: n }, () => rgba).flat()
+  const rgba = Uint8Array.from([...pixels([255, 0, 0, 255], 60), ...pixels([0, 0, 255, 255], 30), ...pixels([255, 255, 255, 255], 10), ...pixels([9, 9, 9, 0], 50)])
+
+  t.deepEqual(
+    extractPalette(rgba, 5).map((c) => [c.color, c.count, c.percentage]),
+    [['#FF0000', 60, 60], ['#0000FF', 30, 30], ['#FFFFFF', 10, 10]],
+  )
+  t.deepEqual(extractPalette(rgba, 5, { ignoreWhite: true }).map((c) => c.color), ['#FF0000', '#0000FF'])
+  t.deepEqual(extractPalette(rgba, 2).map((c) => c.color), ['#FF0000', '#4040FF'])
+  t.deepEqual(extractPalette(rgba, 1)[0].rgb, [179, 26, 102])
+  t.throws(() => extractPalette(rgba, 0), { message: 'Color count needs to be in the range 1-256' })
+
+  const decoder = new Decoder(readFileSync('./__test__/encoderinput.gif'))
+  const colors = decoder.extractPalette(3)
+  t.is(colors.length, 3)
+  t.is(Math.round(colors.reduce((sum, c) => sum + c.percentage, 0)), 100)
+})
+
+test('frame and animation statistics', (t) => {
+  const frame = Frame.fromIndexedPixels(3, 2, Uint8Array.from([2, 0, 0, 2, 1, 2]), null, 2)
+  frame.setPalette(Uint8Array.from([255, 0, 0, 0, 255, 0, 0, 0, 255]))
+  frame.left = 2
+  frame.top = 1
+
+  const stats = frame.stats()
+  t.is(stats.pixels, 6)
+  t.is(stats.distinctColors, 3)
+  t.deepEqual(stats.mean, [85, 42.5, 127.5, 127.5])
+  t.is(stats.variance[3], 16256.25)
+  t.is(stats.transparentFraction, 0.5)
+  t.deepEqual(stats.bounds, { left: 3, top: 1, width: 2, height: 2 })
+  t.deepEqual(stats.paletteUsage?.slice(0, 4), [2, 1, 3, 0])
+
+  const decoder = new Decoder(readFileSync('./__test__/encoderinput.gif'))
+  const { width, height } = decoder
+  const all = decoder.stats()
+  t.true(all.pixels >= width * height)
+  t.is(all.paletteUsage?.reduce((sum, n) => sum + n, 0), all.pixels)
+  t.true(all.bounds!.left + all.bounds!.width <= width && all.bounds!.top + all.bounds!.height <= height)
+})
+
+test('palette compaction', (t) => {
+  // Red twice, an unused gray, white and a transparent black next to an opaque one.
+  const palette = Uint8Array.from([0, 0, 0, 255, 0, 0, 9, 9, 9, 255, 0, 0, 255, 255, 255, 0, 0, 255, 0, 0, 0])
+  const pixels = Uint8Array.from([1, 3, 3, 4, 6, 1, 4, 4, 4])
+
+  const compacted = compactPalette(pixels, palette, 6)
+  t.deepEqual([...compacted.pixels], [0, 0, 0, 1, 2, 0, 1, 1, 1])
+  t.deepEqual([...compacted.palette], [255, 0, 0, 255, 255, 255, 0, 0, 0, 0, 0, 0])
+  t.is(compacted.transparent, 2)
+
+  const sorted = compactPalette(pixels, palette, 6, PaletteOrder.Luminance)
+  t.deepEqual([...sorted.pixels], [1, 1, 1, 2, 0, 1, 2, 2, 2])
+  t.is(sorted.transparent, 0)
+  t.is(compactPalette(pixels, palette).transparent, undefined)
+  t.throws(() => compactPalette(Uint8Array.from([9]), palette), { message: 'Pixel index 9 is outside of the palette with 7 colors' })
+  t.throws(() => Frame.fromRgba(1, 1, Uint8Array.from([0, 0, 0, 255])).convertTo(FrameBufType.Rgba).compactPalette(), {
+    message: 'Only frames holding indexed pixels can be compacted',
+  })
+
+  const encode = (compact: boolean) => {
+    const decoder = new Decoder(readFileSync('./__test__/encoderinput.gif'))
+    const encoder = new Encoder(decoder.width, decoder.height)
+    encoder.setCompactPalettes(compact, PaletteOrder.Frequency)
+    let frame
+    while ((frame = decoder.readNextFrame())) {
+      frame.setPalette(decoder.globalPalette)
+      encoder.addFrame(frame)
+    }
+    return encoder.getBuffer()
+  }
+  const compactedGif = encode(true)
+  t.true(compactedGif.length < encode(false).length)
+  t.true(new Decoder(compactedGif).readNextFrame()!.palette!.length < 768)
+})
+
+test('comparing GIFs and frames', (t) => {
+  const encode = (frames: [number[], number][]) => {
+    const encoder = new Encoder(4, 4, Uint8Array.from([0, 0, 0, 255, 255, 255, 255, 0, 0]))
+    for (const [pixels, delay] of frames) {
+      const frame = Frame.fromIndexedPixels(4, 4, Uint8Array.from(pixels))
+      frame.delay = delay
+      encoder.addFrame(frame)
+    }
+    return encoder.getBuffer()
+  }
+  const white = Array(16).fill(1)
+  const dotted = white.map((p, i) => (i === 5 ? 2 : p))
+  const a = encode([[white, 10], [white, 10]])
+  const b = encode([[white, 10], [dotted, 20], [white, 5]])
+
+  const same = compare(a, a)
+  t.true(same.identical)
+  t.is(same.minPsnr, Infinity)
+  t.is(same.minSsim, 1)
+
+  const report = compare(a, b, { diffImages: true })
+  t.false(report.identical)
+  t.deepEqual([report.frameCountA, report.frameCountB, report.durationA, report.durationB], [2, 3, 20, 35])
+  t.is(report.frames[0].changedPixels, 0)
+  const [, second] = report.frames
+  t.deepEqual([second.changedPixels, second.maxDifference, second.delayA, second.delayB], [1, 255, 10, 20])
+  t.true(second.psnr < 20 && second.ssim < 1)
+  t.deepEqual([...second.diff!.subarray(20, 24)], [255, 0, 0, 255])
+  t.throws(() => compare(a, readFileSync('./__test__/encoderinput.gif')), { message: 'GIFs differ in size: 4x4 and 512x512' })
+
+  const frame = compareFrames(Uint8Array.from([0, 0, 0, 255]), Uint8Array.from([10, 0, 0, 255]), 1, 1, { threshold: 10 })
+  t.deepEqual([frame.maxDifference, frame.changedPixels, frame.delayA], [10, 0, undefined])
+})
+
+test('perceptual hashes and fingerprints', (t) => {
+  // A textured animation rendered at any size, so that a smaller copy looks the same.
+  const render = (size: number, seed: number) => {
+    const encoder = new Encoder(size, size)
+    for (let f = 0; f < 3; f++) {
+      const rgba = new Uint8Array(size * size * 4)
+      for (let i = 0; i < size * size; i++) {
+        const [x, y] = [((i % size) * 256) / size, (Math.floor(i / size) * 256) / size]
+        rgba.set([128 + 127 * Math.sin(x / (17 + seed) + f) * Math.cos(y / (23 - seed)), (x + y * seed) % 256, 128, 255], i * 4)
+      }
+      const frame = Frame.fromRgba(size, size, rgba)
+      frame.delay = 10
+      encoder.addFrame(frame)
+    }
+    return new Decoder(encoder.getBuffer()).fingerprint()
+  }
+
+  const original = render(128, 1)
+  t.is(original.frames.length, 3)
+  t.is(original.duration, 30)
+  t.regex(original.frames[0].dhash, /^[0-9a-f]{16}$/)
+  t.is(fingerprintSimilarity(original, original), 1)
+  t.true(fingerprintSimilarity(original, render(64, 1)) > 0.85)
+  t.true(fingerprintSimilarity(original, render(128, 5)) < 0.7)
+
+  const rgba = Uint8Array.from({ length: 16 * 16 * 4 }, (_, i) => (i % 4 === 3 ? 255 : (i * 7) % 256))
+  t.is(hashDistance(dhash(rgba, 16, 16), dhash(rgba, 16, 16)), 0)
+  t.true(hashDistance(phash(rgba, 16, 16), phash(rgba.map((v, i) => (i % 4 === 3 ? v : 255 - v)), 16, 16)) > 48)
+  t.throws(() => hashDistance('xyz', phash(rgba, 16, 16)), { message: 'Invalid hash `xyz`: expected 16 hex digits' })
+})
@@ -0,0 +1,64 @@
+import test from 'ava'
+
+import { existsSync, readFileSync } from 'node:fs'
+import { createRequire } from 'node:module'
+import * as native from '../index'
+
+// Built by `yarn build:wasm`; the tests are skipped when the package is missing.
+const WASM_PATH = '../pkg/gifsx.js'
+const wasmTest = existsSync(new URL(WASM_PATH, import.meta.url)) ? test : test.skip
+const wasm = () => createRequire(import.meta.url)(WASM_PATH)
+
+const gradient = (width: number, height: number) => {
+  const buffer = new Uint8Array(width * height * 4)
+  for (let i = 0; i < buffer.length; i += 4) {
+    buffer[i] = (i / 4) % width
+    buffer[i + 1] = Math.floor(i / 4 / width)
+    buffer[i + 2] = 128
+    buffer[i + 3] = 255
+  }
+  return buffer
+}
+
+wasmTest('wasm encoder matches the native encoder', (t) => {
+  const { Encoder, Frame } = wasm()
+  const pixels = gradient(64, 64)
+
+  const encode = (E: any, F: any) => {
+    const gif = new E(64, 64)
+    gif.setRepeat(-1)
+    gif.addFrame(F.fromRgba(64, 64, pixels.slice(), 10))
+    return Buffer.from(gif.getBuffer())
+  }
+
+  t.deepEqual(encode(Encoder, Frame), encode(native.Encoder, native.Frame))
+})
+
+wasmTest('wasm decoder reads frames and composites', (t) => {
+  const { Decoder, DecodeOptions, ColorOutput } = wasm()
+  const input = readFileSync('./__test__/encoderinput.gif')
+
+  const options = new DecodeOptions()
+  options.setColorOutput(ColorOutput.Rgba)
+  const gif = options.readInfo(input)
+  const reference = new native.Decoder(input)
+  t.is(gif.width, reference.width)
+  t.is(gif.height, reference.height)
+
+  let frames = 0
+  while (gif.readNextFrame()) frames++
+  t.is(frames, 3)
+
+  const composited = new Decoder(input).readNextCompositedFrame()
+  t.is(composited.buffer.length, reference.width * reference.height * 4)
+})
+
+wasmTest('wasm util functions and neuquant', (t) => {
+  const { hexToRgba, rgbaToHex, NeuQuant } = wasm()
+  const rgba = hexToRgba(['#FF0000', '#00FF0080'])
+  t.deepEqual(Array.from(rgba), [255, 0, 0, 255, 0, 255, 0, 128])
+  t.deepEqual(rgbaToHex(rgba), ['#FF0000', '#00FF0080'])
+
+  const nq = new NeuQuant(10, 16, gradient(16, 16))
+  t.is(nq.colorMapRgb().length, 16 * 3)
+})
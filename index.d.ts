@@ -14,7 +14,24 @@ export const enum ColorOutput {
    * Indexed pixel mode, where each pixel references a color in a palette.
    * Useful for memory-efficient representations with limited color ranges.
    */
-  IndexedPixels = 1
+  IndexedPixels = 1,
+  /** RGB color mode, like `Rgba` without the alpha component. */
+  Rgb = 2,
+  /** Hex color mode, where each pixel is written as an ASCII `#RRGGBBAA` string. */
+  Hex = 3
+}
+/** The representation of a frame's buffer. */
+export const enum FrameBufType {
+  /** Four bytes per pixel: red, green, blue and alpha. */
+  Rgba = 0,
+  /** Three bytes per pixel: red, green and blue. */
+  Rgb = 1,
+  /** One palette index per pixel. */
+  IndexedPixels = 2,
+  /** Nine bytes per pixel: an ASCII `#RRGGBBAA` color. */
+  Hex = 3,
+  /** LZW-compressed image data, as produced by a decoder that skips frame decoding. */
+  Lzw = 4
 }
 /** Disposal method, describing how the next frame should be drawn over the current one. */
 export const enum DisposalMethod {
@@ -27,35 +44,392 @@ export const enum DisposalMethod {
   /** Restore the canvas to the previous frame's state. */
   Previous = 3
 }
+/** A color space for `rgbToColorSpace` and `colorSpaceToRgb`. */
+export const enum ColorSpace {
+  /** Hue in degrees (0-360), saturation and lightness (0-1). */
+  Hsl = 0,
+  /** Hue in degrees (0-360), saturation and value (0-1). */
+  Hsv = 1,
+  /** CIE L*a*b* under the D65 white point. Lightness is 0-100. */
+  Lab = 2,
+  /** Cylindrical CIE L*a*b*: lightness (0-100), chroma and hue in degrees (0-360). */
+  Lch = 3,
+  /** OKLab. Lightness is 0-1. */
+  Oklab = 4
+}
+/** A way to measure how different two colors look. */
+export const enum DistanceMetric {
+  /** Euclidean distance in CIE L*a*b* (ΔE*76). Fast, but overstates differences between saturated colors. */
+  Cie76 = 0,
+  /** The CIEDE2000 color difference (ΔE*00). The most accurate, and the slowest. */
+  Ciede2000 = 1,
+  /** Euclidean distance in OKLab. Close to CIEDE2000 at the cost of CIE76. */
+  Oklab = 2
+}
+/** How `compactPalette` orders the colors it keeps. */
+export const enum PaletteOrder {
+  /** Keep the colors in their original order. */
+  Original = 0,
+  /** Darkest first. */
+  Luminance = 1,
+  /** Most used first. Runs of low indices tend to compress better. */
+  Frequency = 2
+}
+/**
+ * The layout of the pixels passed to `Frame.fromPixels`.
+ * - `rgba`: red, green, blue and straight alpha.
+ * - `bgra`: blue, green, red and alpha, as produced by most screen capture APIs.
+ * - `argb`: alpha, red, green and blue.
+ * - `rgba_premultiplied`: RGBA with the color channels multiplied by alpha, as held by canvas surfaces.
+ * - `gray`: one luminance byte per pixel.
+ * - `gray_alpha`: luminance and alpha.
+ */
+export type InputFormat = 'rgba' | 'bgra' | 'argb' | 'rgba_premultiplied' | 'gray' | 'gray_alpha'
+/** How semi-transparent pixels are handled when quantizing RGBA pixels. GIF pixels are either fully opaque or fully transparent. */
+export interface AlphaOptions {
+  /** Pixels with an alpha below this become transparent, all others opaque. The default is `1`, only making fully transparent pixels transparent. */
+  threshold?: number
+  /** Replace the threshold with an ordered dither pattern, so that soft edges fade out instead of being cut off. The default is `false`. */
+  dither?: boolean
+  /** Blend semi-transparent pixels that stay opaque over this hex or other CSS color instead of dropping their alpha. */
+  matte?: string
+  /** Reserve this palette index for transparent pixels. Opaque pixels never use it, even if the frame has no transparent pixels. */
+  transparentIndex?: number
+}
+/** Options for `Frame.fromPixels`. */
+export interface PixelsOptions {
+  /** Defaults to `"rgba"`. */
+  format?: InputFormat
+  /**
+   * The number of bytes from the start of one row to the next, for buffers with padding after each row.
+   * Defaults to the length of a row.
+   */
+  stride?: number
+}
+/** Options for the text written by `rgbaToHex`, `rgbToHex` and `indexedToHex`. */
+export interface HexOptions {
+  /** Write hex digits in lowercase. The default is `false`. */
+  lowercase?: boolean
+  /** Write CSS `rgb()`/`rgba()` functions instead of hex colors, with alpha in the range 0-1. `allowShort` is ignored. The default is `false`. */
+  css?: boolean
+}
 /**
  * Convert RGBA to hex color. (e.g. `rgbaToHex([255, 0, 0, 255, 255, 128, 0, 128])` -> `["#FF0000", "#FF800080"]`)
  * @param rgba - An array of RGBA values.
  * @param alwaysIncludeAlpha - Whether to always include the alpha channel in the output.
  * @param allowShort - Whether to allow short hex output.
+ * @param options - Lowercase or CSS `rgb()`/`rgba()` output.
  * @returns An array of hex color strings. (e.g. `"#FF0000"`)
  */
-export declare function rgbaToHex(rgba: Uint8Array, alwaysIncludeAlpha?: boolean | undefined | null, allowShort?: boolean | undefined | null): Array<string>
+export declare function rgbaToHex(rgba: Uint8Array, alwaysIncludeAlpha?: boolean | undefined | null, allowShort?: boolean | undefined | null, options?: HexOptions | undefined | null): Array<string>
 /**
  * Convert RGB to hex color. (e.g. `rgbToHex([255, 0, 0, 255, 128, 0])` -> `["#FF0000", "#FF8000"]`)
  * @param rgb - An array of RGB values.
  * @param allowShort - Whether to allow short hex output.
+ * @param options - Lowercase or CSS `rgb()` output.
  * @returns An array of hex color strings. (e.g. `"#FF0000"`)
  */
-export declare function rgbToHex(rgb: Uint8Array, allowShort?: boolean | undefined | null): Array<string>
+export declare function rgbToHex(rgb: Uint8Array, allowShort?: boolean | undefined | null, options?: HexOptions | undefined | null): Array<string>
 /**
- * Convert hex color to RGBA. (e.g. `hexToRgba(["#FF0000FF", "#800080FF"])` -> `[255, 0, 0, 255, 128, 0, 128, 255]`)
- * @param hex - An array of hex color strings.
+ * Convert hex or other CSS colors to RGBA. (e.g. `hexToRgba(["#FF0000FF", "rgb(128 0 128 / 50%)"])` -> `[255, 0, 0, 255, 128, 0, 128, 128]`)
+ * @param hex - An array of color strings: hex, `rgb()`/`rgba()`, `hsl()`/`hsla()`, `hwb()`, `oklch()`, named colors or `transparent`.
  * @returns A flattened array of RGBA values.
+ *
+ * ### Notes:
+ * - Hex colors with characters other than hex digits are rejected, naming the character and its index.
  */
 export declare function hexToRgba(hex: Array<string>): Uint8Array
 /**
- * Convert hex color to RGB. (e.g. `hexToRgb(["#FF0000", "#FF8000"])` -> `[255, 0, 0, 255, 128, 0]`)
- * @param hex - An array of hex color strings.
+ * Convert hex or other CSS colors to RGB. (e.g. `hexToRgb(["#FF0000", "orange"])` -> `[255, 0, 0, 255, 165, 0]`)
+ * @param hex - An array of color strings, see `hexToRgba`. Hex colors can not have alpha, and the alpha of other colors is dropped.
  * @returns A flattened array of RGB values.
  */
 export declare function hexToRgb(hex: Array<string>): Uint8Array
+/** Options for building a sprite sheet. */
+export interface SpriteSheetOptions {
+  /** Number of columns of the grid. Defaults to a roughly square grid. Ignored when `packed` is set. */
+  columns?: number
+  /** Space in pixels between frames and around the edge of the sheet. The default is `0`. */
+  padding?: number
+  /** Background color of the sheet as a hex or other CSS color. The default is transparent. */
+  background?: string
+  /** Trim transparent borders from every frame and pack them in rows instead of a uniform grid. */
+  packed?: boolean
+  /** Encode the sheet as a PNG instead of raw RGBA. */
+  png?: boolean
+}
+/** A frame's placement in a sprite sheet. */
+export interface SpriteFrame {
+  /** Horizontal position of the frame in the sheet. */
+  x: number
+  /** Vertical position of the frame in the sheet. */
+  y: number
+  /** Width of the frame in the sheet. */
+  width: number
+  /** Height of the frame in the sheet. */
+  height: number
+  /** Horizontal offset of the frame within the animation, non-zero for trimmed frames. */
+  left: number
+  /** Vertical offset of the frame within the animation, non-zero for trimmed frames. */
+  top: number
+  /** The delay for this frame in units of 10ms. */
+  delay: number
+}
+/** A sprite sheet built from the frames of an animation. */
+export interface SpriteSheet {
+  /** Width of the sheet. */
+  width: number
+  /** Height of the sheet. */
+  height: number
+  /** Width of the animation the frames were taken from. */
+  frameWidth: number
+  /** Height of the animation the frames were taken from. */
+  frameHeight: number
+  /** The sheet as raw RGBA, or as a PNG if requested. */
+  buffer: Buffer
+  /** Placement of every frame in the sheet. */
+  frames: Array<SpriteFrame>
+  /** JSON atlas in the common `frames`/`meta` layout, with durations in milliseconds. */
+  atlas: string
+}
+/** How far a long-running operation has got. */
+export interface Progress {
+  /** Frames processed so far. */
+  frames: number
+  /** The number of frames to process, when known up front. */
+  totalFrames?: number
+  /** Input bytes consumed so far, for operations reading a GIF. */
+  bytes?: number
+  /** The size of the input in bytes, for operations reading a GIF. */
+  totalBytes?: number
+}
+/** Options for operations running off the main thread. */
+export interface TaskOptions {
+  /** Stops the operation before its next frame once aborted. The promise then rejects with a `Cancelled` error. */
+  signal?: AbortSignal
+  /** Called as frames are processed. */
+  onProgress?: (progress: Progress) => void
+}
+/**
+ * Creates the placements of `count` frames laid out in a uniform grid.
+ * @param count - The number of frames.
+ * @param frameWidth - Width of every frame.
+ * @param frameHeight - Height of every frame.
+ * @param columns - Number of columns. Defaults to a roughly square grid.
+ * @param padding - Space in pixels between frames and around the edge of the sheet.
+ * @param delay - The delay for every frame in units of 10ms.
+ */
+export declare function spriteGrid(count: number, frameWidth: number, frameHeight: number, columns?: number | undefined | null, padding?: number | undefined | null, delay?: number | undefined | null): Array<SpriteFrame>
 export declare function indexedToRgba(pixels: Uint8Array, palette: Uint8Array, transparent?: number | undefined | null): Uint8Array
-export declare function indexedToHex(pixels: Uint8Array, palette: Uint8Array, transparent?: number | undefined | null, alwaysIncludeAlpha?: boolean | undefined | null, allowShort?: boolean | undefined | null): Array<string>
+export declare function indexedToHex(pixels: Uint8Array, palette: Uint8Array, transparent?: number | undefined | null, alwaysIncludeAlpha?: boolean | undefined | null, allowShort?: boolean | undefined | null, options?: HexOptions | undefined | null): Array<string>
+/**
+ * Converts RGB or RGBA pixels to another color space, three floats per pixel.
+ * @param alpha - Whether the pixels are RGBA. The alpha channel is kept as a fourth float in the range 0-1.
+ */
+export declare function rgbToColorSpace(pixels: Uint8Array, space: ColorSpace, alpha?: boolean | undefined | null): Float32Array
+/**
+ * Converts colors from another color space back to RGB or RGBA pixels, clamping colors outside the sRGB gamut.
+ * @param alpha - Whether the colors have a fourth alpha float in the range 0-1, producing RGBA pixels.
+ */
+export declare function colorSpaceToRgb(values: Float32Array, space: ColorSpace, alpha?: boolean | undefined | null): Buffer
+/**
+ * Measures how different each pair of pixels in two RGB or RGBA buffers of the same length looks.
+ * @param alpha - Whether the pixels are RGBA. The alpha channel is ignored.
+ * @returns One distance per pixel.
+ */
+export declare function colorDistance(a: Uint8Array, b: Uint8Array, metric: DistanceMetric, alpha?: boolean | undefined | null): Float32Array
+/** Returns the index of the palette color that looks closest to an RGB color, or `null` for an empty palette. */
+export declare function nearestColor(palette: Uint8Array, color: Uint8Array, metric: DistanceMetric): number | null
+/** Which pixels `extractPalette` leaves out. */
+export interface PaletteOptions {
+  /** Skip pixels with an alpha below 128. The default is `true`. */
+  ignoreTransparent?: boolean
+  /** Skip near-white pixels. The default is `false`. */
+  ignoreWhite?: boolean
+  /** Skip near-black pixels. The default is `false`. */
+  ignoreBlack?: boolean
+  /** How close to white or black every channel of a pixel needs to be to be skipped. The default is `16`. */
+  tolerance?: number
+  /** The quantization speed in the range 1-30, used when there are more distinct colors than requested. The default is `10`. */
+  speed?: number
+}
+/** A color found by `extractPalette`. */
+export interface PaletteColor {
+  /** The color as `#RRGGBB`. */
+  color: string
+  /** The color as red, green and blue. */
+  rgb: Array<number>
+  /** The number of pixels closest to this color. */
+  count: number
+  /** The share of the counted pixels closest to this color, in the range 0-100. */
+  percentage: number
+}
+/** A rectangle on the GIF's canvas. */
+export interface Bounds {
+  left: number
+  top: number
+  width: number
+  height: number
+}
+/** Pixel statistics of a frame or a whole GIF, see `<Frame>.stats` and `<Decoder>.stats`. */
+export interface ImageStats {
+  /** The number of pixels counted. */
+  pixels: number
+  /** The number of distinct RGBA colors. */
+  distinctColors: number
+  /** The mean of the red, green, blue and alpha channels, in the range 0-255. */
+  mean: Array<number>
+  /** The variance of the red, green, blue and alpha channels. */
+  variance: Array<number>
+  /** The share of fully transparent pixels, in the range 0-1. */
+  transparentFraction: number
+  /** The smallest rectangle on the canvas holding every pixel that is not fully transparent. Missing if all pixels are transparent. */
+  bounds?: Bounds
+  /** How many pixels use each of the 256 palette indices. Only set for indexed frames. */
+  paletteUsage?: Array<number>
+}
+/**
+ * Finds up to `count` colors that best represent RGBA pixels, most common first.
+ * See `<Frame>.extractPalette` and `<Decoder>.extractPalette` to use frames or a whole GIF.
+ *
+ * ### Notes:
+ * - Images with at most `count` distinct colors are counted exactly, others are quantized with NeuQuant.
+ * - `count` needs to be in the range 1-256.
+ */
+export declare function extractPalette(rgba: Uint8Array, count: number, options?: PaletteOptions | undefined | null): Array<PaletteColor>
+/** Indexed pixels with a palette rewritten by `compactPalette`. */
+export interface CompactedPalette {
+  /** The pixels, pointing into the new palette. */
+  pixels: Buffer
+  /** The new palette, padded to a power of two. */
+  palette: Buffer
+  /** The new transparent index. Missing if no pixel is transparent. */
+  transparent?: number
+}
+/**
+ * Drops unused palette entries and merges duplicate colors of indexed pixels, remapping the pixels and the transparent index.
+ * See `<Frame>.compactPalette` to compact a frame's palette.
+ *
+ * ### Notes:
+ * - The palette is padded with black to the smallest power of two that GIF palettes can hold.
+ * - The transparent entry is kept apart from opaque entries of the same color, and dropped if no pixel uses it.
+ * @param order - How to order the colors that are kept. Defaults to `PaletteOrder.Original`.
+ */
+export declare function compactPalette(pixels: Uint8Array, palette: Uint8Array, transparent?: number | undefined | null, order?: PaletteOrder | undefined | null): CompactedPalette
+/** Options for `compare` and `compareFrames`. */
+export interface CompareOptions {
+  /** The largest channel difference that still counts a pixel as unchanged. The default is `0`. */
+  threshold?: number
+  /** Render a diff image for every frame pair, showing unchanged pixels faded and changed pixels in red. The default is `false`. */
+  diffImages?: boolean
+}
+/** How much two frames differ. */
+export interface FrameComparison {
+  /** Peak signal-to-noise ratio over all RGBA channels, in dB. `Infinity` for identical frames. */
+  psnr: number
+  /** Mean structural similarity of the luminance, where 1 means identical. */
+  ssim: number
+  /** The largest difference of any channel of any pixel. */
+  maxDifference: number
+  /** The number of pixels with a channel difference above the threshold. */
+  changedPixels: number
+  /** The delay of the frame from the first GIF in units of 10ms. Only set by `compare`. */
+  delayA?: number
+  /** The delay of the frame from the second GIF in units of 10ms. Only set by `compare`. */
+  delayB?: number
+  /** The diff image as opaque RGBA, if requested with `diffImages`. */
+  diff?: Buffer
+}
+/** How much two GIFs differ, see `compare`. */
+export interface CompareReport {
+  /** One comparison per pair of composited frames, up to the length of the shorter GIF. */
+  frames: Array<FrameComparison>
+  /** The number of frames of the first GIF. */
+  frameCountA: number
+  /** The number of frames of the second GIF. */
+  frameCountB: number
+  /** The summed delays of the first GIF in units of 10ms. */
+  durationA: number
+  /** The summed delays of the second GIF in units of 10ms. */
+  durationB: number
+  /** The lowest PSNR of any frame pair. */
+  minPsnr: number
+  /** The lowest SSIM of any frame pair. */
+  minSsim: number
+  /** The largest channel difference of any frame pair. */
+  maxDifference: number
+  /** Both GIFs have the same number of frames with the same delays and pixels. */
+  identical: boolean
+}
+/**
+ * Decodes two GIFs of the same size and compares their frames after compositing, pair by pair.
+ * Useful for regression tests of rendering pipelines.
+ *
+ * ### Notes:
+ * - Frames are compared as rendered, so GIFs encoded differently can still be identical.
+ * - Timing differences show up in the delays of each pair and the total durations.
+ */
+export declare function compare(a: Uint8Array, b: Uint8Array, options?: CompareOptions | undefined | null): CompareReport
+/** Compares two RGBA images of the same size, see `compare`. */
+export declare function compareFrames(a: Uint8Array, b: Uint8Array, width: number, height: number, options?: CompareOptions | undefined | null): FrameComparison
+/** The hashes of a composited frame, see `<Decoder>.fingerprint`. */
+export interface FrameHash {
+  /** The difference hash as 16 hex digits, see `dhash`. */
+  dhash: string
+  /** The perceptual hash as 16 hex digits, see `phash`. */
+  phash: string
+  /** How long the frame is shown in units of 10ms. */
+  delay: number
+}
+/** The hashes of the composited frames of a GIF along with their timing, see `<Decoder>.fingerprint`. */
+export interface Fingerprint {
+  /** One entry per frame. Consecutive frames with the same hashes are merged into one, adding up their delays. */
+  frames: Array<FrameHash>
+  /** The summed delays of all frames in units of 10ms. */
+  duration: number
+}
+/**
+ * The difference hash of RGBA pixels as 16 hex digits.
+ * Each bit tells whether a cell of a 9 by 8 grayscale grid is brighter than its right neighbour.
+ *
+ * ### Notes:
+ * - Fast, and robust to resizing and re-encoding. See `hashDistance` to compare hashes.
+ * - The size of `rgba` should match the expected size based on `width`, `height`.
+ */
+export declare function dhash(rgba: Uint8Array, width: number, height: number): string
+/**
+ * The perceptual hash of RGBA pixels as 16 hex digits.
+ * Each bit tells whether one of the 64 lowest frequencies of the DCT of a 32 by 32 grayscale version is above their median.
+ *
+ * ### Notes:
+ * - Slower than `dhash`, but also robust to changes in brightness and contrast. See `hashDistance` to compare hashes.
+ * - The size of `rgba` should match the expected size based on `width`, `height`.
+ */
+export declare function phash(rgba: Uint8Array, width: number, height: number): string
+/**
+ * The number of bits two hashes from `dhash` or `phash` differ in, from 0 for identical images to 64.
+ * Images with a distance of up to about 10 usually look alike.
+ */
+export declare function hashDistance(a: string, b: string): number
+/**
+ * How alike two GIFs are based on their fingerprints, in the range 0-1 where 1 means they look the same throughout.
+ *
+ * ### Notes:
+ * - Both animations are stretched to the same length and compared over time, so every frame counts as much as it is shown.
+ * - Speeding a GIF up or down keeps it similar, reordering its frames does not.
+ * - Unrelated GIFs score around 0.5; re-encoded or resized copies usually score above 0.9.
+ */
+export declare function fingerprintSimilarity(a: Fingerprint, b: Fingerprint): number
+/** Drops the alpha channel of RGBA pixels. */
+export declare function rgbaToRgb(rgba: Uint8Array): Buffer
+/** Adds an opaque alpha channel to RGB pixels. */
+export declare function rgbToRgba(rgb: Uint8Array): Buffer
+/** Swaps the red and blue channels of BGRA pixels. The same call converts RGBA pixels to BGRA. */
+export declare function bgraToRgba(bgra: Uint8Array): Buffer
+/** Multiplies the color channels of RGBA pixels by their alpha, as expected by canvas and GPU APIs. */
+export declare function premultiplyAlpha(rgba: Uint8Array): Buffer
+/** Reverses `premultiplyAlpha`. Fully transparent pixels are left unchanged. */
+export declare function unpremultiplyAlpha(rgba: Uint8Array): Buffer
 /** A GIF frame. */
 export declare class Frame {
   /** The delay for this frame in units of 10ms (e.g., a value of `10` equals 100ms). */
@@ -76,21 +450,74 @@ export declare class Frame {
   height: number
   /** True if the image is interlaced. */
   interlaced: boolean
-  /** The frame's palette. */
+  /** Lossy LZW compression level for this frame, overriding the encoder's level. See `<Encoder>.setLossy`. */
+  lossy?: number
+  /**
+   * The frame's palette.
+   *
+   * ### Notes:
+   * - Unlike `<Frame>.buffer`, this is a copy of at most 768 bytes. Writing to it does not change the frame; use `<Frame>.setPalette`.
+   */
   get palette(): Buffer | null
   /** Sets the frame's palette. */
   setPalette(val?: Uint8Array | undefined | null): void
-  /** The buffer of this frame. */
+  /**
+   * The frame's buffer.
+   *
+   * ### Notes:
+   * - The buffer is a view into the frame's data, not a copy. Writing to it changes the frame.
+   */
   get buffer(): Buffer
   setBuffer(val: Uint8Array): void
+  /**
+   * Encodes the frame as a PNG.
+   * Indexed frames are written as an indexed PNG, keeping the palette and the transparent index.
+   * @param globalPalette - The palette to use if the frame has no palette of its own. (e.g. `<Decoder>.globalPalette`)
+   */
+  toPng(globalPalette?: Uint8Array | undefined | null): Buffer
+  /**
+   * Finds up to `count` colors that best represent the frame, most common first. See `extractPalette`.
+   * @param globalPalette - The palette to use if the frame has no palette of its own. (e.g. `<Decoder>.globalPalette`)
+   */
+  extractPalette(count: number, options?: PaletteOptions | undefined | null, globalPalette?: Uint8Array | undefined | null): Array<PaletteColor>
+  /**
+   * Computes the frame's pixel statistics: distinct colors, channel means and variances, transparency and the bounds of its visible pixels.
+   * Indexed frames also count how often each palette index is used.
+   * @param globalPalette - The palette to use if the frame has no palette of its own. (e.g. `<Decoder>.globalPalette`)
+   */
+  stats(globalPalette?: Uint8Array | undefined | null): ImageStats
+  /**
+   * Returns a copy of the frame with unused palette entries dropped and duplicate colors merged, see `compactPalette`.
+   * Frames without a palette of their own get the compacted `globalPalette` as their own.
+   *
+   * ### Notes:
+   * - Only frames holding indexed pixels can be compacted.
+   * @param order - How to order the colors that are kept. Defaults to `PaletteOrder.Original`.
+   * @param globalPalette - The palette to use if the frame has no palette of its own. (e.g. `<Decoder>.globalPalette`)
+   */
+  compactPalette(order?: PaletteOrder | undefined | null, globalPalette?: Uint8Array | undefined | null): Frame
+  /**
+   * Returns a copy of the frame with its buffer converted to another representation.
+   * Indexed pixels are resolved against the frame's palette and transparent index, or `globalPalette` if the frame has none.
+   *
+   * ### Notes:
+   * - Converting to indexed pixels keeps the exact colors of frames with up to 256 colors, and quantizes the others.
+   * - Speed needs to be in the range 1-30. Higher is faster, lower CPU usage but worse quality.
+   * - Frames holding LZW data can not be converted.
+   * @param bufType - The representation to convert to.
+   * @param globalPalette - The palette to use if the frame has no palette of its own. (e.g. `<Decoder>.globalPalette`)
+   * @param speed - The quantization speed, used when converting to indexed pixels.
+   */
+  convertTo(bufType: FrameBufType, globalPalette?: Uint8Array | undefined | null, speed?: number | undefined | null): Frame
   /**
    * Creates a frame from RGBA pixel data.
    *
    * ### Notes:
    * - Speed needs to be in the range 1-30. Higher is faster, lower CPU usage but worse quality.
    * - The size of `buffer` should match the expected size based on `width`, `height`.
+   * - Only fully transparent pixels become transparent unless set otherwise with `alpha`.
    */
-  static fromRgba(width: number, height: number, buffer: Uint8Array, speed?: number | undefined | null): Frame
+  static fromRgba(width: number, height: number, buffer: Uint8Array, speed?: number | undefined | null, alpha?: AlphaOptions | undefined | null): Frame
   /**
    * Creates a frame from RGB pixel data.
    *
@@ -100,14 +527,53 @@ export declare class Frame {
    */
   static fromRgb(width: number, height: number, buffer: Uint8Array, speed?: number | undefined | null): Frame
   /**
-   * Creates a frame from hex pixel data.
+   * Creates a frame from BGRA, ARGB, premultiplied RGBA or grayscale pixel data, converting it to RGBA first.
    *
    * ### Notes:
    * - Speed needs to be in the range 1-30. Higher is faster, lower CPU usage but worse quality.
-   * - The size of `buffer` should match the expected size based on `width`, `height`.
-   * - The color output will be in Rgba.
+   * - Rows may be padded up to `stride` bytes. The padding after the last row can be left out.
+   * - Only fully transparent pixels become transparent unless set otherwise with `alpha`.
+   */
+  static fromPixels(width: number, height: number, buffer: Uint8Array, options?: PixelsOptions | undefined | null, speed?: number | undefined | null, alpha?: AlphaOptions | undefined | null): Frame
+  /**
+   * Creates a frame from hex colors, one per pixel. (e.g. `["#FF0000", "#00FF0080"]`)
+   *
+   * ### Notes:
+   * - Speed needs to be in the range 1-30. Higher is faster, lower CPU usage but worse quality.
+   * - The length of `buffer` should match the number of pixels based on `width`, `height`.
+   * - Only fully transparent pixels become transparent unless set otherwise with `alpha`.
+   */
+  static fromHex(width: number, height: number, buffer: Array<string>, speed?: number | undefined | null, alpha?: AlphaOptions | undefined | null): Frame
+  /**
+   * Creates a frame from a PNG image.
+   *
+   * ### Notes:
+   * - Speed needs to be in the range 1-30. Higher is faster, lower CPU usage but worse quality.
+   * - Only fully transparent pixels become transparent unless set otherwise with `alpha`.
+   * - For an APNG, only the first frame is used. See `Frame.fromApng`.
+   */
+  static fromPng(buffer: Uint8Array, speed?: number | undefined | null, alpha?: AlphaOptions | undefined | null): Frame
+  /**
+   * Creates frames from every frame of an APNG, keeping their offsets, delays and disposal methods.
+   *
+   * ### Notes:
+   * - Speed needs to be in the range 1-30. Higher is faster, lower CPU usage but worse quality.
+   * - Only fully transparent pixels become transparent unless set otherwise with `alpha`.
+   * - APNGs whose frames clear pixels with the `source` blend operation are composited into full-size frames, as GIF frames are always drawn over the canvas.
+   */
+  static fromApng(buffer: Uint8Array, speed?: number | undefined | null, alpha?: AlphaOptions | undefined | null): Array<Frame>
+  /**
+   * Creates frames by cutting them out of a sprite sheet.
+   * @param buffer - The sheet as raw RGBA or as a PNG.
+   * @param frames - Placement of every frame in the sheet. (e.g. `spriteGrid(...)` or `<SpriteSheet>.frames`)
+   * @param sheetWidth - Width of the sheet, required for raw RGBA.
+   *
+   * ### Notes:
+   * - Speed needs to be in the range 1-30. Higher is faster, lower CPU usage but worse quality.
+   * - Only fully transparent pixels become transparent unless set otherwise with `alpha`.
+   * - Frames are positioned at their `left` and `top` offsets and cleared to the background once displayed.
    */
-  static fromHex(width: number, height: number, buffer: Array<string>, speed?: number | undefined | null): Frame
+  static fromSpriteSheet(buffer: Uint8Array, frames: Array<SpriteFrame>, sheetWidth?: number | undefined | null, speed?: number | undefined | null, alpha?: AlphaOptions | undefined | null): Array<Frame>
   /**
    * Creates a frame from indexed pixel data.
    *
@@ -127,20 +593,84 @@ export declare class Encoder {
    * Add a frame to the gif.
    *
    * ### Notes:
-   * - The size of `buffer` should match the expected size based on `width`, `height`.
+   * - The frame must fit within the gif's `width` and `height`, taking `left` and `top` into account.
+   * - Frames holding RGB, RGBA or hex pixels are quantized first, see `<Frame>.convertTo`.
    */
   addFrame(frame: Frame): void
+  /**
+   * Add a frame whose buffer is already LZW-compressed to the gif, without re-encoding it.
+   *
+   * ### Notes:
+   * - The frame is usually read from a decoder with `<DecodeOptions>.skipFrameDecoding` enabled.
+   * - A frame without a palette of its own keeps decoding against the global palette of the GIF it was read from, which becomes its own palette unless it matches the encoder's.
+   * - The frame must fit within the gif's `width` and `height`, taking `left` and `top` into account.
+   */
+  addRawFrame(frame: Frame): void
   /** The global color palette. */
   get palette(): Uint8Array | null
+  /**
+   * Add multiple frames from RGBA pixel data to the gif.
+   * The frames are quantized and compressed in parallel, then written in order.
+   *
+   * ### Notes:
+   * - Speed needs to be in the range 1-30. Higher is faster, lower CPU usage but worse quality.
+   * - The size of every buffer should match the expected size based on `width`, `height`.
+   * - `delays`, if provided, needs one entry per frame.
+   * - `threads` defaults to the number of CPU cores.
+   * - The buffers are quantized in place, so their pixels change.
+   */
+  addRgbaFrames(frames: Array<Uint8Array>, delays?: Array<number> | undefined | null, speed?: number | undefined | null, threads?: number | undefined | null): void
+  /**
+   * Like `<Encoder>.addRgbaFrames`, but quantizes and writes the frames off the main thread.
+   * Other calls on the encoder wait until the frames are written.
+   * The frames are copied first, so the buffers are left untouched and can be reused right away.
+   * @param options - An `AbortSignal` to cancel the task and a progress callback, called as each frame is quantized.
+   */
+  addRgbaFramesAsync(frames: Array<Uint8Array>, delays?: Array<number> | undefined | null, speed?: number | undefined | null, threads?: number | undefined | null, options?: TaskOptions | undefined | null): Promise<void>
+  /**
+   * Sets the lossy LZW compression level used for frames without their own `lossy` level.
+   * Higher values allow pixels to be replaced by more distant palette colours, producing smaller files at the cost of quality.
+   *
+   * ### Notes:
+   * - The level needs to be in the range 0-200. `0` disables lossy compression; 20-80 is a reasonable range.
+   */
+  setLossy(value: number): void
+  /**
+   * Compacts the palette of every frame with a palette of its own added with `addFrame`, see `<Frame>.compactPalette`.
+   * Decoded frames often carry 256-color palettes of which only a few entries are used, which costs space in every frame.
+   * @param order - How to order the colors that are kept. Defaults to `PaletteOrder.Original`.
+   */
+  setCompactPalettes(value: boolean, order?: PaletteOrder | undefined | null): void
   /** Sets the repeat count for the gif. If the value is -1, the gif will repeat infinitely; otherwise, the gif will repeat a `value` number of times. */
   setRepeat(value: number): void
   /** Returns the gif buffer. */
   getBuffer(): Uint8Array
+  /**
+   * Returns the bytes written since the encoder was created or since the last call, and removes them from the encoder.
+   *
+   * ### Notes:
+   * - `<Encoder>.getBuffer` only returns the bytes written after the last call, followed by the trailer.
+   */
+  takeBuffer(): Buffer
+  /**
+   * Returns the gif buffer like `<Encoder>.getBuffer`, but hands it over without copying it.
+   *
+   * ### Notes:
+   * - The encoder is left empty, like after `<Encoder>.takeBuffer`. Frames added afterwards are not part of the returned gif.
+   */
+  finish(): Buffer
 }
-/** The GIF Decoder. */
+/**
+ * The GIF Decoder.
+ * Iterating over the decoder with `for...of` reads the remaining frames, like `<Decoder>.readNextFrame`.
+ * `for await...of` reads them off the main thread, see `<Decoder>.readNextFrameAsync`.
+ */
 export declare class Decoder {
+  [Symbol.iterator](): Iterator<Frame, void, void>
   /**
    * Create a new decoder.
+   * The buffer is borrowed rather than copied, so it should not be modified while the decoder is in use.
+   * It is copied once the decoder is first used off the main thread, e.g. by `<Decoder>.readNextFrameAsync`.
    * @param buffer - The GIF buffer to decode.
    * @param options - The options to use for decoding.
    */
@@ -150,8 +680,76 @@ export declare class Decoder {
   /**
    * Reads the next frame from the GIF.
    * Do not call `<Decoder>.nextFrameInfo` beforehand. Deinterlaces the result.
+   * If frame decoding is skipped, the frame's buffer holds the compressed LZW data instead.
    */
   readNextFrame(): Frame | null
+  /**
+   * Reads the next frame off the main thread.
+   * @param composited - Whether to return the composited canvas, like `<Decoder>.readNextCompositedFrame`.
+   */
+  readNextFrameAsync(composited?: boolean | undefined | null): Promise<Frame | null>
+  /**
+   * Returns an iterator over the remaining frames, for use with `for...of`.
+   * @param composited - Whether to yield the composited canvas, like `<Decoder>.readNextCompositedFrame`.
+   */
+  frames(composited?: boolean | undefined | null): DecoderFrames
+  /**
+   * Reads the remaining frames and encodes them as an APNG.
+   * Frame offsets, delays and disposal methods are carried over, and the loop count maps to the number of plays.
+   */
+  toApng(): Buffer
+  /**
+   * Reads the remaining frames and finds up to `count` colors that best represent all of them together, most common first.
+   * See `extractPalette`.
+   */
+  extractPalette(count: number, options?: PaletteOptions | undefined | null): Array<PaletteColor>
+  /**
+   * Like `<Decoder>.extractPalette`, but reads the frames and counts their colors off the main thread.
+   * @param taskOptions - An `AbortSignal` to cancel the task and a progress callback, called after each frame is read.
+   */
+  extractPaletteAsync(count: number, options?: PaletteOptions | undefined | null, taskOptions?: TaskOptions | undefined | null): Promise<Array<PaletteColor>>
+  /**
+   * Reads the remaining frames and computes their pixel statistics together, see `<Frame>.stats`.
+   * Bounds cover the visible pixels of all frames, and palette usage is counted for indexed frames.
+   */
+  stats(): ImageStats
+  /**
+   * Like `<Decoder>.stats`, but reads the frames and counts their pixels off the main thread.
+   * @param options - An `AbortSignal` to cancel the task and a progress callback, called after each frame is read.
+   */
+  statsAsync(options?: TaskOptions | undefined | null): Promise<ImageStats>
+  /**
+   * Reads the remaining frames, composites them and hashes each one along with its delay, see `dhash` and `phash`.
+   * Compare fingerprints with `fingerprintSimilarity` to find copies of a GIF that were re-encoded or resized.
+   */
+  fingerprint(): Fingerprint
+  /**
+   * Like `<Decoder>.fingerprint`, but reads, composites and hashes the frames off the main thread.
+   * @param options - An `AbortSignal` to cancel the task and a progress callback, called after each frame is read.
+   */
+  fingerprintAsync(options?: TaskOptions | undefined | null): Promise<Fingerprint>
+  /**
+   * Like `<Decoder>.toApng`, but reads and encodes the frames off the main thread.
+   * @param options - An `AbortSignal` to cancel the task and a progress callback, called after each frame is read.
+   */
+  toApngAsync(options?: TaskOptions | undefined | null): Promise<Buffer>
+  /**
+   * Reads the next frame and draws it onto the canvas left by the previous frames, applying their disposal methods.
+   * Returns the whole canvas as an RGBA frame of the GIF's size, keeping the frame's delay.
+   * Only frames read with this method are drawn onto the canvas.
+   */
+  readNextCompositedFrame(): Frame | null
+  /**
+   * Reads the remaining frames, composites them and lays them out as a sprite sheet.
+   * @param options - The layout, background and output format of the sheet.
+   */
+  toSpriteSheet(options?: SpriteSheetOptions | undefined | null): SpriteSheet
+  /**
+   * Like `<Decoder>.toSpriteSheet`, but composites the frames and builds the sheet off the main thread.
+   * @param options - The layout, background and output format of the sheet.
+   * @param taskOptions - An `AbortSignal` to cancel the task and a progress callback, called after each frame is read.
+   */
+  toSpriteSheetAsync(options?: SpriteSheetOptions | undefined | null, taskOptions?: TaskOptions | undefined | null): Promise<SpriteSheet>
   /** Output buffer size. */
   get bufferSize(): number
   /** Line length of the current frame. */
@@ -172,6 +770,29 @@ export declare class Decoder {
   /** Number of loop repetitions. */
   get loops(): number
 }
+/** Iterator over the remaining frames of a decoder. See `<Decoder>.frames`. */
+export declare class DecoderFrames {
+  [Symbol.iterator](): Iterator<Frame, void, void>
+}
+/**
+ * A GIF that arrives in chunks, read by a decoder as soon as its header is complete. Used by `createDecodeStream`.
+ * Only the bytes the decoder has not read yet are kept.
+ */
+export declare class DecoderFeed {
+  /**
+   * Create a new feed.
+   * @param options - The options to use for decoding.
+   */
+  constructor(options?: DecodeOptions | undefined | null)
+  /** Appends the next chunk of the GIF. */
+  push(chunk: Uint8Array): void
+  /** Marks the GIF as complete, so that the decoder can be created and read to the end even if it is truncated. */
+  end(): void
+  /** The number of frames that have completely arrived. Reading more frames before `<DecoderFeed>.end` fails. */
+  get frames(): number
+  /** Returns the decoder once the header has arrived, always the same one. Returns `null` before. */
+  decoder(): Decoder | null
+}
 /** Options for opening a GIF decoder. `<DecodeOptions>.readInfo` will create a decoder with these options. */
 export declare class DecodeOptions {
   /** Create new decode options. */
@@ -199,6 +820,7 @@ export declare class DecodeOptions {
    * @param value - Whether to skip frame decoding.
    * The default is `false`.
    * When turned on, LZW decoding is skipped. `<Decoder>.readNextFrame` will return compressed LZW bytes in frame’s data. `<Decoder>.nextFrameInfo` will return the metadata of the next frame as usual. This is useful to count frames without incurring the overhead of decoding.
+   * Frames read this way can be written back as-is with `<Encoder>.addRawFrame`.
    */
   skipFrameDecoding(value: boolean): void
   /**
@@ -220,6 +842,8 @@ export declare class DecodeOptions {
   /**
    * Reads the logical screen descriptor including the global color palette
    * Returns a Decoder. All decoder configuration has to be done beforehand.
+   * The buffer is borrowed rather than copied, so it should not be modified while the decoder is in use.
+   * It is copied once the decoder is first used off the main thread, e.g. by `<Decoder>.readNextFrameAsync`.
    * @param buffer - The GIF buffer to decode.
    */
   readInfo(buffer: Uint8Array): Decoder
@@ -310,13 +310,19 @@ if (!nativeBinding) {
   throw new Error(`Failed to load native binding`)
 }
 
-const { ColorOutput, DisposalMethod, Frame, Encoder, Decoder, DecodeOptions, rgbaToHex, rgbToHex, hexToRgba, hexToRgb, indexedToRgba, indexedToHex, NeuQuant } = nativeBinding
+const { ColorOutput, FrameBufType, DisposalMethod, ColorSpace, DistanceMetric, PaletteOrder, Frame, Encoder, Decoder, DecoderFrames, DecoderFeed, DecodeOptions, rgbaToHex, rgbToHex, hexToRgba, hexToRgb, indexedToRgba, indexedToHex, NeuQuant, spriteGrid, rgbToColorSpace, colorSpaceToRgb, colorDistance, nearestColor, rgbaToRgb, rgbToRgba, bgraToRgba, premultiplyAlpha, unpremultiplyAlpha, extractPalette, compactPalette, compare, compareFrames, dhash, phash, hashDistance, fingerprintSimilarity } = nativeBinding
 
 module.exports.ColorOutput = ColorOutput
+module.exports.FrameBufType = FrameBufType
 module.exports.DisposalMethod = DisposalMethod
+module.exports.ColorSpace = ColorSpace
+module.exports.DistanceMetric = DistanceMetric
+module.exports.PaletteOrder = PaletteOrder
 module.exports.Frame = Frame
 module.exports.Encoder = Encoder
 module.exports.Decoder = Decoder
+module.exports.DecoderFrames = DecoderFrames
+module.exports.DecoderFeed = DecoderFeed
 module.exports.DecodeOptions = DecodeOptions
 module.exports.rgbaToHex = rgbaToHex
 module.exports.rgbToHex = rgbToHex
@@ -325,3 +331,21 @@ module.exports.hexToRgb = hexToRgb
 module.exports.indexedToRgba = indexedToRgba
 module.exports.indexedToHex = indexedToHex
 module.exports.NeuQuant = NeuQuant
+module.exports.spriteGrid = spriteGrid
+module.exports.rgbToColorSpace = rgbToColorSpace
+module.exports.colorSpaceToRgb = colorSpaceToRgb
+module.exports.colorDistance = colorDistance
+module.exports.nearestColor = nearestColor
+module.exports.rgbaToRgb = rgbaToRgb
+module.exports.rgbToRgba = rgbToRgba
+module.exports.bgraToRgba = bgraToRgba
+module.exports.premultiplyAlpha = premultiplyAlpha
+module.exports.unpremultiplyAlpha = unpremultiplyAlpha
+module.exports.extractPalette = extractPalette
+module.exports.compactPalette = compactPalette
+module.exports.compare = compare
+module.exports.compareFrames = compareFrames
+module.exports.dhash = dhash
+module.exports.phash = phash
+module.exports.hashDistance = hashDistance
+module.exports.fingerprintSimilarity = fingerprintSimilarity
@@ -0,0 +1,335 @@
+use crate::hex::{hex_pixels_to_rgba, indexed_to_rgba};
+use crate::{pixels, Error, PixelFormat, Result};
+
+fn png_error(e: png::EncodingError) -> Error {
+  Error::Encoding(format!("Failed to encode a PNG: {}", e))
+}
+
+fn png_decoding_error(e: png::DecodingError) -> Error {
+  Error::Decoding(format!("Failed to decode a PNG: {}", e))
+}
+
+pub(crate) fn frame_palette<'p>(
+  frame: &'p gif::Frame,
+  global_palette: Option<&'p [u8]>,
+) -> Result<&'p [u8]> {
+  frame
+    .palette
+    .as_deref()
+    .or(global_palette)
+    .ok_or_else(|| Error::invalid("Frame has no palette"))
+}
+
+/// Returns the frame's pixels as RGBA, resolving indexed pixels against the frame's or the global palette.
+pub fn frame_to_rgba(
+  frame: &gif::Frame,
+  format: PixelFormat,
+  global_palette: Option<&[u8]>,
+) -> Result<Vec<u8>> {
+  match format {
+    PixelFormat::Rgba => Ok(frame.buffer.to_vec()),
+    PixelFormat::Rgb => Ok(pixels::rgb_to_rgba(&frame.buffer)),
+    PixelFormat::Indexed => Ok(indexed_to_rgba(
+      &frame.buffer,
+      frame_palette(frame, global_palette)?,
+      frame.transparent,
+    )),
+    PixelFormat::Hex => hex_pixels_to_rgba(&frame.buffer),
+    PixelFormat::Lzw => Err(Error::invalid(
+      "Frame holds LZW data, which can not be converted",
+    )),
+  }
+}
+
+/// Encodes a frame as a PNG. Indexed frames keep their palette, with the transparent index stored in `tRNS`.
+pub fn encode_png(
+  frame: &gif::Frame,
+  format: PixelFormat,
+  global_palette: Option<&[u8]>,
+) -> Result<Vec<u8>> {
+  let mut out = Vec::new();
+  let mut encoder = png::Encoder::new(&mut out, frame.width as u32, frame.height as u32);
+  encoder.set_depth(png::BitDepth::Eight);
+
+  let data = match format {
+    PixelFormat::Indexed => {
+      let palette = frame_palette(frame, global_palette)?;
+      let len = palette.len() / 3;
+      if frame.buffer.iter().any(|&i| i as usize >= len) {
+        return Err(Error::invalid(
+          "Frame references colors outside of its palette",
+        ));
+      }
+
+      encoder.set_color(png::ColorType::Indexed);
+      encoder.set_palette(palette[..len * 3].to_vec());
+      if let Some(t) = frame.transparent.filter(|&t| (t as usize) < len) {
+        let mut trns = vec![255; t as usize + 1];
+        trns[t as usize] = 0;
+        encoder.set_trns(trns);
+      }
+      frame.buffer.to_vec()
+    }
+    PixelFormat::Rgb => {
+      encoder.set_color(png::ColorType::Rgb);
+      frame.buffer.to_vec()
+    }
+    _ => {
+      encoder.set_color(png::ColorType::Rgba);
+      frame_to_rgba(frame, format, global_palette)?
+    }
+  };
+
+  let mut writer = encoder.write_header().map_err(png_error)?;
+  writer.write_image_data(&data).map_err(png_error)?;
+  writer.finish().map_err(png_error)?;
+  Ok(out)
+}
+
+/// Encodes RGBA frames as an APNG.
+/// Frame offsets, delays and disposal methods are mapped to `fcTL`, and the repeat count to `acTL`.
+pub fn encode_apng(
+  width: u16,
+  height: u16,
+  repeat: gif::Repeat,
+  frames: &[(gif::Frame, Vec<u8>)],
+) -> Result<Vec<u8>> {
+  if frames.is_empty() {
+    return Err(Error::invalid("An APNG needs at least one frame"));
+  }
+
+  for (frame, _) in frames {
+    if frame.left as u32 + frame.width as u32 > width as u32
+      || frame.top as u32 + frame.height as u32 > height as u32
+    {
+      return Err(Error::invalid(format!(
+        "Frame {}x{} at {},{} does not fit within {}x{}",
+        frame.width, frame.height, frame.left, frame.top, width, height
+      )));
+    }
+  }
+
+  let mut out = Vec::new();
+  let mut encoder = png::Encoder::new(&mut out, width as u32, height as u32);
+  encoder.set_color(png::ColorType::Rgba);
+  encoder.set_depth(png::BitDepth::Eight);
+  encoder
+    .set_animated(
+      frames.len() as u32,
+      match repeat {
+        gif::Repeat::Infinite => 0,
+        gif::Repeat::Finite(n) => n as u32 + 1,
+      },
+    )
+    .map_err(png_error)?;
+
+  let mut writer = encoder.write_header().map_err(png_error)?;
+  for (i, (frame, rgba)) in frames.iter().enumerate() {
+    writer
+      .set_frame_delay(frame.delay, 100)
+      .map_err(png_error)?;
+    writer
+      .set_dispose_op(match frame.dispose {
+        gif::DisposalMethod::Any | gif::DisposalMethod::Keep => png::DisposeOp::None,
+        gif::DisposalMethod::Background => png::DisposeOp::Background,
+        gif::DisposalMethod::Previous => png::DisposeOp::Previous,
+      })
+      .map_err(png_error)?;
+    writer.set_blend_op(png::BlendOp::Over).map_err(png_error)?;
+    writer.reset_frame_position().map_err(png_error)?;
+
+    // The first frame is the default image and has to cover the whole canvas.
+    if i == 0 && (frame.width != width || frame.height != height) {
+      let mut canvas = vec![0; width as usize * height as usize * 4];
+      let row = frame.width as usize * 4;
+      for (y, src) in rgba.chunks_exact(row).enumerate() {
+        let start = ((frame.top as usize + y) * width as usize + frame.left as usize) * 4;
+        canvas[start..start + row].copy_from_slice(src);
+      }
+      writer.reset_frame_dimension().map_err(png_error)?;
+      writer.write_image_data(&canvas).map_err(png_error)?;
+      continue;
+    }
+
+    writer
+      .set_frame_dimension(frame.width as u32, frame.height as u32)
+      .map_err(png_error)?;
+    writer
+      .set_frame_position(frame.left as u32, frame.top as u32)
+      .map_err(png_error)?;
+    writer.write_image_data(rgba).map_err(png_error)?;
+  }
+  writer.finish().map_err(png_error)?;
+  Ok(out)
+}
+
+/// A frame decoded from a PNG or APNG, with its pixels as RGBA.
+pub struct PngFrame {
+  pub width: u16,
+  pub height: u16,
+  pub left: u16,
+  pub top: u16,
+  pub delay: u16,
+  pub dispose: gif::DisposalMethod,
+  pub rgba: Vec<u8>,
+}
+
+pub(crate) fn to_u16(value: u32) -> Result<u16> {
+  u16::try_from(value).map_err(|_| {
+    Error::invalid(format!(
+      "Dimension {} exceeds the GIF limit of 65535",
+      value
+    ))
+  })
+}
+
+fn output_to_rgba(data: &[u8], color_type: png::ColorType) -> Vec<u8> {
+  match color_type {
+    png::ColorType::Grayscale => data.iter().flat_map(|&v| [v, v, v, 255]).collect(),
+    png::ColorType::GrayscaleAlpha => data
+      .chunks_exact(2)
+      .flat_map(|p| [p[0], p[0], p[0], p[1]])
+      .collect(),
+    png::ColorType::Rgb => pixels::rgb_to_rgba(data),
+    _ => data.to_vec(),
+  }
+}
+
+/// Draws `src` over `dst` with straight alpha.
+fn blend_over(dst: &mut [u8], src: &[u8]) {
+  let (sa, da) = (src[3] as u32, dst[3] as u32);
+  let da = da * (255 - sa) / 255;
+  let a = sa + da;
+  if a == 0 {
+    dst.copy_from_slice(&[0, 0, 0, 0]);
+    return;
+  }
+  for c in 0..3 {
+    dst[c] = ((src[c] as u32 * sa + dst[c] as u32 * da + a / 2) / a) as u8;
+  }
+  dst[3] = a as u8;
+}
+
+/// Renders the frames of an APNG onto a canvas, following its blend and dispose operations,
+/// and returns one frame per canvas state that covers the whole canvas and is cleared once displayed.
+fn composite(frames: Vec<(PngFrame, png::BlendOp)>, width: u16, height: u16) -> Vec<PngFrame> {
+  let stride = width as usize * 4;
+  let mut canvas = vec![0; stride * height as usize];
+  let mut out = Vec::with_capacity(frames.len());
+  for (i, (frame, blend)) in frames.into_iter().enumerate() {
+    let row = frame.width as usize * 4;
+    let offset = |y: usize| (frame.top as usize + y) * stride + frame.left as usize * 4;
+    let previous = (frame.dispose == gif::DisposalMethod::Previous).then(|| {
+      (0..frame.height as usize)
+        .flat_map(|y| canvas[offset(y)..offset(y) + row].to_vec())
+        .collect::<Vec<u8>>()
+    });
+
+    for (y, src) in frame.rgba.chunks_exact(row).enumerate() {
+      let dst = &mut canvas[offset(y)..offset(y) + row];
+      match blend {
+        png::BlendOp::Source => dst.copy_from_slice(src),
+        png::BlendOp::Over => dst
+          .chunks_exact_mut(4)
+          .zip(src.chunks_exact(4))
+          .for_each(|(d, s)| blend_over(d, s)),
+      }
+    }
+
+    out.push(PngFrame {
+      width,
+      height,
+      left: 0,
+      top: 0,
+      delay: frame.delay,
+      dispose: gif::DisposalMethod::Background,
+      rgba: canvas.clone(),
+    });
+
+    // A first frame disposed to the previous canvas is cleared instead, as there is none.
+    let restore = match frame.dispose {
+      gif::DisposalMethod::Background => Some(vec![0; frame.rgba.len()]),
+      gif::DisposalMethod::Previous if i == 0 => Some(vec![0; frame.rgba.len()]),
+      _ => previous,
+    };
+    if let Some(restore) = restore {
+      for (y, src) in restore.chunks_exact(row).enumerate() {
+        canvas[offset(y)..offset(y) + row].copy_from_slice(src);
+      }
+    }
+  }
+  out
+}
+
+/// Decodes every frame of a PNG or APNG. A plain PNG yields a single frame.
+/// A default image that is not part of the animation is skipped.
+///
+/// GIF frames are always drawn over the canvas. If any APNG frame replaces semi-transparent or transparent
+/// pixels with the `source` blend operation, the animation is composited instead, see [`composite`].
+pub fn decode_png(buffer: &[u8]) -> Result<Vec<PngFrame>> {
+  let mut decoder = png::Decoder::new(buffer);
+  decoder.set_transformations(png::Transformations::normalize_to_color8());
+  let mut reader = decoder.read_info().map_err(png_decoding_error)?;
+
+  let animation = reader.info().animation_control;
+  let skip_default = animation.is_some() && reader.info().frame_control.is_none();
+  let num_frames = animation.map_or(1, |a| a.num_frames) as usize + skip_default as usize;
+  let (width, height) = (to_u16(reader.info().width)?, to_u16(reader.info().height)?);
+
+  let mut buf = vec![0; reader.output_buffer_size()];
+  let mut frames = Vec::with_capacity(num_frames);
+  let mut needs_compositing = false;
+  for i in 0..num_frames {
+    let output = reader.next_frame(&mut buf).map_err(png_decoding_error)?;
+    if i == 0 && skip_default {
+      continue;
+    }
+
+    let data = &buf[..output.line_size * output.height as usize];
+    let mut frame = PngFrame {
+      width: to_u16(output.width)?,
+      height: to_u16(output.height)?,
+      left: 0,
+      top: 0,
+      delay: 0,
+      dispose: gif::DisposalMethod::Keep,
+      rgba: output_to_rgba(data, output.color_type),
+    };
+
+    let mut blend = png::BlendOp::Over;
+    if let Some(fctl) = reader.info().frame_control {
+      let den = if fctl.delay_den == 0 {
+        100
+      } else {
+        fctl.delay_den as u32
+      };
+      frame.left = to_u16(fctl.x_offset)?;
+      frame.top = to_u16(fctl.y_offset)?;
+      frame.delay = ((fctl.delay_num as u32 * 100 + den / 2) / den).min(u16::MAX as u32) as u16;
+      frame.dispose = match fctl.dispose_op {
+        png::DisposeOp::None => gif::DisposalMethod::Keep,
+        png::DisposeOp::Background => gif::DisposalMethod::Background,
+        png::DisposeOp::Previous => gif::DisposalMethod::Previous,
+      };
+      blend = fctl.blend_op;
+    }
+    if frame.left as u32 + frame.width as u32 > width as u32
+      || frame.top as u32 + frame.height as u32 > height as u32
+    {
+      return Err(Error::invalid(format!(
+        "Frame {}x{} at {},{} does not fit within {}x{}",
+        frame.width, frame.height, frame.left, frame.top, width, height
+      )));
+    }
+    // Over and source only differ where the frame is not fully opaque.
+    needs_compositing |= blend == png::BlendOp::Source
+      && i > skip_default as usize
+      && frame.rgba.chunks_exact(4).any(|p| p[3] < 255);
+    frames.push((frame, blend));
+  }
+
+  if needs_compositing {
+    return Ok(composite(frames, width, height));
+  }
+  Ok(frames.into_iter().map(|(frame, _)| frame).collect())
+}
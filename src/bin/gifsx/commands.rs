@@ -0,0 +1,641 @@
+use crate::Command;
+use gifsx::progress::Control;
+use gifsx::{apng, compositor::Compositor, hex, lzw, quantize, transform, PixelFormat};
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+type Decoder = gif::Decoder<BufReader<File>>;
+type Encoder = gif::Encoder<BufWriter<File>>;
+
+/// Composited RGBA frames of a GIF with their delays.
+struct Animation {
+  width: u16,
+  height: u16,
+  repeat: gif::Repeat,
+  frames: Vec<(u16, Vec<u8>)>,
+}
+
+pub fn run(command: Command) -> Result<()> {
+  match command {
+    Command::Info { inputs, json } => {
+      print!("{}", info(&expand(&inputs)?, json)?);
+      Ok(())
+    }
+    Command::Palette { inputs, json } => {
+      print!("{}", palette(&expand(&inputs)?, json)?);
+      Ok(())
+    }
+    Command::Frames { inputs, output } => {
+      fs::create_dir_all(&output)?;
+      for input in expand(&inputs)? {
+        frames(&input, &output)?;
+      }
+      Ok(())
+    }
+    Command::Optimize {
+      inputs,
+      output,
+      lossy,
+    } => {
+      lzw::check_lossy(lossy)?;
+      each(&inputs, &output, |input, output| {
+        optimize(input, output, lossy)?;
+        println!(
+          "{}: {} -> {} bytes",
+          input.display(),
+          fs::metadata(input)?.len(),
+          fs::metadata(output)?.len()
+        );
+        Ok(())
+      })
+    }
+    Command::Resize {
+      inputs,
+      output,
+      width,
+      height,
+      speed,
+    } => each(&inputs, &output, |input, output| {
+      let mut animation = composite(input)?;
+      let (w, h) = (animation.width as u32, animation.height as u32);
+      let (new_width, new_height) = match (width, height) {
+        (Some(width), Some(height)) => (width as u32, height as u32),
+        (Some(width), None) => (width as u32, scale(h, width as u32, w)),
+        (None, Some(height)) => (scale(w, height as u32, h), height as u32),
+        (None, None) => return Err("Pass `--width`, `--height` or both".into()),
+      };
+      let too_large = |_| {
+        format!(
+          "{}x{} exceeds the GIF limit of 65535",
+          new_width, new_height
+        )
+      };
+      let gif_width = u16::try_from(new_width).map_err(too_large)?;
+      let gif_height = u16::try_from(new_height).map_err(too_large)?;
+
+      for (_, rgba) in &mut animation.frames {
+        *rgba = transform::resize(rgba, w, h, new_width, new_height)?;
+      }
+      animation.width = gif_width;
+      animation.height = gif_height;
+      write_animation(output, animation, speed)
+    }),
+    Command::Crop {
+      inputs,
+      output,
+      x,
+      y,
+      width,
+      height,
+      speed,
+    } => each(&inputs, &output, |input, output| {
+      let mut animation = composite(input)?;
+      let (w, h) = (animation.width as u32, animation.height as u32);
+      for (_, rgba) in &mut animation.frames {
+        *rgba = transform::crop(rgba, w, h, x as u32, y as u32, width as u32, height as u32)?;
+      }
+      animation.width = width;
+      animation.height = height;
+      write_animation(output, animation, speed)
+    }),
+    Command::Speed {
+      inputs,
+      output,
+      factor,
+    } => {
+      if !(factor > 0.0 && factor.is_finite()) {
+        return Err("Factor needs to be greater than 0".into());
+      }
+      each(&inputs, &output, |input, output| {
+        remux(input, output, None, |delay| {
+          if delay == 0 {
+            0
+          } else {
+            (delay as f64 / factor).round().clamp(1.0, u16::MAX as f64) as u16
+          }
+        })
+      })
+    }
+    Command::Reverse {
+      inputs,
+      output,
+      speed,
+    } => each(&inputs, &output, |input, output| {
+      let mut animation = composite(input)?;
+      animation.frames.reverse();
+      write_animation(output, animation, speed)
+    }),
+    Command::Loop {
+      inputs,
+      output,
+      count,
+    } => {
+      let repeat = match count {
+        -1 => gif::Repeat::Infinite,
+        0..=65535 => gif::Repeat::Finite(count as u16),
+        _ => return Err("Count needs to be -1 or in the range 0-65535".into()),
+      };
+      each(&inputs, &output, |input, output| {
+        remux(input, output, Some(repeat), |delay| delay)
+      })
+    }
+    Command::Concat { inputs, output } => concat(&expand(&inputs)?, &output),
+  }
+}
+
+/// Expands glob patterns into the matching files, keeping the order of the patterns.
+fn expand(patterns: &[String]) -> Result<Vec<PathBuf>> {
+  let mut paths = Vec::new();
+  for pattern in patterns {
+    let before = paths.len();
+    for entry in glob::glob(pattern)? {
+      let path = entry?;
+      if path.is_file() {
+        paths.push(path);
+      }
+    }
+    if paths.len() == before {
+      return Err(format!("No files match `{}`", pattern).into());
+    }
+  }
+  Ok(paths)
+}
+
+/// Runs `f` for every input with its output path. Several inputs are written into the `output` directory.
+fn each(
+  patterns: &[String],
+  output: &Path,
+  mut f: impl FnMut(&Path, &Path) -> Result<()>,
+) -> Result<()> {
+  let inputs = expand(patterns)?;
+  let into_dir = inputs.len() > 1 || output.is_dir();
+  if into_dir {
+    fs::create_dir_all(output)?;
+  }
+
+  for input in &inputs {
+    let target = if into_dir {
+      output.join(input.file_name().ok_or("Input has no file name")?)
+    } else {
+      output.to_path_buf()
+    };
+    f(input, &target).map_err(|e| format!("{}: {}", input.display(), e))?;
+  }
+  Ok(())
+}
+
+fn scale(size: u32, new_other: u32, other: u32) -> u32 {
+  ((size as f64 * new_other as f64 / other as f64).round() as u32).max(1)
+}
+
+fn open(path: &Path, color_output: gif::ColorOutput, skip_frame_decoding: bool) -> Result<Decoder> {
+  let mut options = gif::DecodeOptions::new();
+  options.set_color_output(color_output);
+  options.skip_frame_decoding(skip_frame_decoding);
+  Ok(options.read_info(BufReader::new(File::open(path)?))?)
+}
+
+fn create(path: &Path, width: u16, height: u16, palette: &[u8]) -> Result<Encoder> {
+  Ok(gif::Encoder::new(
+    BufWriter::new(File::create(path)?),
+    width,
+    height,
+    palette,
+  )?)
+}
+
+fn composite(path: &Path) -> Result<Animation> {
+  let mut decoder = open(path, gif::ColorOutput::RGBA, false)?;
+  let (width, height) = (decoder.width(), decoder.height());
+  let mut compositor = Compositor::new(width, height);
+
+  let mut frames = Vec::new();
+  while let Some(frame) = decoder.read_next_frame()? {
+    let canvas = compositor.draw(frame, &frame.buffer);
+    frames.push((frame.delay, canvas.to_vec()));
+  }
+
+  Ok(Animation {
+    width,
+    height,
+    repeat: decoder.repeat(),
+    frames,
+  })
+}
+
+/// Quantizes the frames in parallel and writes them as full-size frames that each replace the previous one.
+fn write_animation(path: &Path, mut animation: Animation, speed: i32) -> Result<()> {
+  for (_, rgba) in &mut animation.frames {
+    quantize::apply_alpha(rgba, animation.width, &quantize::AlphaOptions::default());
+  }
+
+  let encoded = quantize::quantize_rgba_batch(
+    animation.width,
+    animation.height,
+    animation
+      .frames
+      .iter_mut()
+      .map(|(_, rgba)| rgba.as_mut_slice())
+      .collect(),
+    speed,
+    None,
+    0,
+    None,
+    Control::default(),
+  )?;
+
+  let mut encoder = create(path, animation.width, animation.height, &[])?;
+  encoder.set_repeat(animation.repeat)?;
+  for (mut frame, (delay, _)) in encoded.into_iter().zip(&animation.frames) {
+    frame.delay = *delay;
+    frame.dispose = gif::DisposalMethod::Background;
+    encoder.write_lzw_pre_encoded_frame(&frame)?;
+  }
+  Ok(())
+}
+
+/// Copies the LZW data of every frame without decoding it, changing the repeat count and delays.
+fn remux(
+  input: &Path,
+  output: &Path,
+  repeat: Option<gif::Repeat>,
+  delay: impl Fn(u16) -> u16,
+) -> Result<()> {
+  let mut decoder = open(input, gif::ColorOutput::Indexed, true)?;
+  let mut encoder = create(
+    output,
+    decoder.width(),
+    decoder.height(),
+    decoder.global_palette().unwrap_or(&[]),
+  )?;
+  encoder.set_repeat(repeat.unwrap_or(decoder.repeat()))?;
+
+  while let Some(frame) = decoder.read_next_frame()? {
+    let mut frame = frame.clone();
+    frame.delay = delay(frame.delay);
+    encoder.write_lzw_pre_encoded_frame(&frame)?;
+  }
+  Ok(())
+}
+
+fn optimize(input: &Path, output: &Path, lossy: u32) -> Result<()> {
+  let mut decoder = open(input, gif::ColorOutput::Indexed, false)?;
+  let palette = decoder.global_palette().map(|p| p.to_vec());
+  let mut encoder = create(
+    output,
+    decoder.width(),
+    decoder.height(),
+    palette.as_deref().unwrap_or(&[]),
+  )?;
+  encoder.set_repeat(decoder.repeat())?;
+
+  while let Some(frame) = decoder.read_next_frame()? {
+    let mut frame = frame.clone();
+    lzw::compress_frame(&mut frame, palette.as_deref(), lossy);
+    encoder.write_lzw_pre_encoded_frame(&frame)?;
+  }
+  Ok(())
+}
+
+/// Grows `frame` to cover the whole canvas with transparent pixels around it, so that its background disposal
+/// clears everything drawn before. Returns `false` if the frame has no transparent index and no index to spare.
+fn cover_canvas(frame: &mut gif::Frame, width: u16, height: u16) -> bool {
+  if (frame.left, frame.top, frame.width, frame.height) == (0, 0, width, height) {
+    return true;
+  }
+
+  let transparent = match frame.transparent {
+    Some(index) => index,
+    None => {
+      let palette = frame.palette.get_or_insert_with(Vec::new);
+      if palette.len() < 256 * 3 {
+        palette.extend([0; 3]);
+        (palette.len() / 3 - 1) as u8
+      } else {
+        let mut used = [false; 256];
+        frame.buffer.iter().for_each(|&i| used[i as usize] = true);
+        let Some(index) = used.iter().position(|&u| !u) else {
+          return false;
+        };
+        index as u8
+      }
+    }
+  };
+
+  let (left, top) = (frame.left as usize, frame.top as usize);
+  let mut buffer = vec![transparent; width as usize * height as usize];
+  for (y, row) in frame.buffer.chunks_exact(frame.width as usize).enumerate() {
+    // Frames may reach past the logical screen, so they are clipped to the canvas
+    if let Some(target) = buffer.chunks_exact_mut(width as usize).nth(top + y) {
+      let end = (left + row.len()).min(target.len());
+      if left < end {
+        target[left..end].copy_from_slice(&row[..end - left]);
+      }
+    }
+  }
+
+  frame.buffer = buffer.into();
+  frame.transparent = Some(transparent);
+  (frame.left, frame.top, frame.width, frame.height) = (0, 0, width, height);
+  true
+}
+
+fn concat(inputs: &[PathBuf], output: &Path) -> Result<()> {
+  let mut decoders = inputs
+    .iter()
+    .map(|input| open(input, gif::ColorOutput::Indexed, false))
+    .collect::<Result<Vec<_>>>()?;
+  let width = decoders.iter().map(|d| d.width()).max().unwrap_or(0);
+  let height = decoders.iter().map(|d| d.height()).max().unwrap_or(0);
+
+  let mut encoder = create(output, width, height, &[])?;
+  if let Some(first) = decoders.first() {
+    encoder.set_repeat(first.repeat())?;
+  }
+
+  let count = decoders.len();
+  for (i, decoder) in decoders.iter_mut().enumerate() {
+    let palette = decoder.global_palette().map(|p| p.to_vec());
+    let mut frames = Vec::new();
+    while let Some(frame) = decoder.read_next_frame()? {
+      let mut frame = frame.clone();
+      // Every input brings its own global palette, which is stored as each frame's local palette.
+      if frame.palette.is_none() {
+        frame.palette = palette.clone();
+      }
+      frames.push(frame);
+    }
+
+    // GIFs can only draw over the canvas, and disposal only clears a frame's own area. The last frame of each
+    // input but the last is grown to the whole canvas and cleared, so that every input starts on an empty canvas.
+    let mut clear = None;
+    if let (Some(last), false) = (frames.last_mut(), i + 1 == count) {
+      last.dispose = gif::DisposalMethod::Background;
+      if !cover_canvas(last, width, height) {
+        // Only frames using all 256 colors without transparency get here. A transparent frame is shown
+        // between inputs instead, which browsers hold for about 100 ms.
+        last.dispose = gif::DisposalMethod::Keep;
+        clear = Some(gif::Frame {
+          width,
+          height,
+          dispose: gif::DisposalMethod::Background,
+          transparent: Some(0),
+          palette: Some(vec![0; 6]),
+          buffer: vec![0; width as usize * height as usize].into(),
+          ..gif::Frame::default()
+        });
+      }
+    }
+    for frame in frames.iter().chain(&clear) {
+      encoder.write_frame(frame)?;
+    }
+  }
+  Ok(())
+}
+
+fn frames(input: &Path, output: &Path) -> Result<()> {
+  let animation = composite(input)?;
+  let stem = input
+    .file_stem()
+    .ok_or("Input has no file name")?
+    .to_string_lossy();
+
+  for (i, (_, rgba)) in animation.frames.into_iter().enumerate() {
+    let frame = gif::Frame {
+      width: animation.width,
+      height: animation.height,
+      buffer: rgba.into(),
+      ..gif::Frame::default()
+    };
+    let png = apng::encode_png(&frame, PixelFormat::Rgba, None)?;
+    fs::write(output.join(format!("{}-{:03}.png", stem, i)), png)?;
+  }
+  Ok(())
+}
+
+fn json_string(s: &str) -> String {
+  let mut out = String::with_capacity(s.len() + 2);
+  out.push('"');
+  for c in s.chars() {
+    match c {
+      '"' => out.push_str("\\\""),
+      '\\' => out.push_str("\\\\"),
+      c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+      c => out.push(c),
+    }
+  }
+  out.push('"');
+  out
+}
+
+/// Joins the entries of [`info`] and [`palette`] into the printed output.
+fn lines(entries: Vec<String>, json: bool) -> String {
+  if json {
+    format!("[{}]\n", entries.join(","))
+  } else {
+    entries.into_iter().map(|e| e + "\n").collect()
+  }
+}
+
+/// Describes the size, frame count, loop count and duration of GIFs, one line per input or as a JSON array.
+fn info(inputs: &[PathBuf], json: bool) -> Result<String> {
+  let mut entries = Vec::new();
+  for input in inputs {
+    let mut decoder = open(input, gif::ColorOutput::Indexed, true)?;
+    let (mut frames, mut duration) = (0u32, 0u64);
+    while let Some(frame) = decoder.read_next_frame()? {
+      frames += 1;
+      duration += frame.delay as u64 * 10;
+    }
+
+    let loops = match decoder.repeat() {
+      gif::Repeat::Finite(n) => n as i32,
+      gif::Repeat::Infinite => -1,
+    };
+    let colors = decoder.global_palette().map_or(0, |p| p.len() / 3);
+    let size = fs::metadata(input)?.len();
+    let file = input.display().to_string();
+
+    if json {
+      entries.push(format!(
+        r#"{{"file":{},"width":{},"height":{},"frames":{},"duration":{},"loops":{},"colors":{},"size":{}}}"#,
+        json_string(&file),
+        decoder.width(),
+        decoder.height(),
+        frames,
+        duration,
+        loops,
+        colors,
+        size
+      ));
+    } else {
+      entries.push(format!(
+        "{}: {}x{}, {} frames, {} ms, {}, {} colors, {} bytes",
+        file,
+        decoder.width(),
+        decoder.height(),
+        frames,
+        duration,
+        if loops < 0 {
+          "loops forever".to_string()
+        } else {
+          format!("{} loops", loops)
+        },
+        colors,
+        size
+      ));
+    }
+  }
+  Ok(lines(entries, json))
+}
+
+/// Lists the global palette of GIFs as hex colors, one line per input or as a JSON array.
+fn palette(inputs: &[PathBuf], json: bool) -> Result<String> {
+  let mut entries = Vec::new();
+  for input in inputs {
+    let decoder = open(input, gif::ColorOutput::Indexed, true)?;
+    let colors = hex::rgb_to_strings(
+      decoder.global_palette().unwrap_or(&[]),
+      hex::TextFormat::default(),
+    )?;
+    let file = input.display().to_string();
+
+    if json {
+      entries.push(format!(
+        r#"{{"file":{},"colors":[{}]}}"#,
+        json_string(&file),
+        colors
+          .iter()
+          .map(|c| json_string(c))
+          .collect::<Vec<_>>()
+          .join(",")
+      ));
+    } else {
+      entries.push(format!("{}: {}", file, colors.join(" ")));
+    }
+  }
+  Ok(lines(entries, json))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn input() -> String {
+    concat!(env!("CARGO_MANIFEST_DIR"), "/__test__/encoderinput.gif").to_string()
+  }
+
+  /// An empty directory for the outputs of one test.
+  fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("gifsx-{}-{}", std::process::id(), name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+  }
+
+  /// Writes a 4x4 GIF with single-colored frames of the given sizes at the top left corner.
+  fn write_gif(path: &Path, frames: &[(u16, u16)], color: [u8; 3]) {
+    let mut encoder = create(path, 4, 4, &[]).unwrap();
+    for &(width, height) in frames {
+      let frame = gif::Frame {
+        width,
+        height,
+        palette: Some([color, [0, 0, 0]].concat()),
+        buffer: vec![0; width as usize * height as usize].into(),
+        ..gif::Frame::default()
+      };
+      encoder.write_frame(&frame).unwrap();
+    }
+  }
+
+  fn delays(path: &Path) -> Vec<u16> {
+    let mut decoder = open(path, gif::ColorOutput::Indexed, true).unwrap();
+    let mut delays = Vec::new();
+    while let Some(frame) = decoder.read_next_frame().unwrap() {
+      delays.push(frame.delay);
+    }
+    delays
+  }
+
+  #[test]
+  fn info_as_json() {
+    let json = info(&[PathBuf::from(input())], true).unwrap();
+    assert!(json.starts_with(r#"[{"file":""#));
+    assert!(json.contains(
+      r#""width":512,"height":512,"frames":3,"duration":3000,"loops":-1,"colors":256,"size":9996}]"#
+    ));
+    assert!(run(Command::Info {
+      inputs: vec![input()],
+      json: true
+    })
+    .is_ok());
+  }
+
+  #[test]
+  fn speed_and_loop_keep_frames() {
+    let dir = temp_dir("speed");
+    let (fast, looped) = (dir.join("fast.gif"), dir.join("looped.gif"));
+
+    run(Command::Speed {
+      inputs: vec![input()],
+      output: fast.clone(),
+      factor: 2.0,
+    })
+    .unwrap();
+    assert_eq!(delays(&fast), [50, 50, 50]);
+
+    run(Command::Loop {
+      inputs: vec![fast.to_string_lossy().into_owned()],
+      output: looped.clone(),
+      count: 3,
+    })
+    .unwrap();
+    assert_eq!(delays(&looped), [50, 50, 50]);
+    let decoder = open(&looped, gif::ColorOutput::Indexed, true).unwrap();
+    assert_eq!(decoder.repeat(), gif::Repeat::Finite(3));
+
+    assert!(run(Command::Loop {
+      inputs: vec![input()],
+      output: looped,
+      count: 65536,
+    })
+    .is_err());
+    fs::remove_dir_all(dir).unwrap();
+  }
+
+  #[test]
+  fn concat_starts_every_input_on_an_empty_canvas() {
+    let dir = temp_dir("concat");
+    let (red, blue, output) = (
+      dir.join("red.gif"),
+      dir.join("blue.gif"),
+      dir.join("out.gif"),
+    );
+    // The last red frame only covers one pixel, so clearing it would leave the rest of the canvas red
+    write_gif(&red, &[(4, 4), (1, 1)], [255, 0, 0]);
+    write_gif(&blue, &[(2, 2)], [0, 0, 255]);
+
+    run(Command::Concat {
+      inputs: [&red, &blue]
+        .map(|p| p.to_string_lossy().into_owned())
+        .to_vec(),
+      output: output.clone(),
+    })
+    .unwrap();
+
+    // No extra frame is shown between the inputs
+    assert_eq!(delays(&output).len(), 3);
+    let animation = composite(&output).unwrap();
+    let [first, .., last] = animation.frames.as_slice() else {
+      panic!("expected several frames");
+    };
+    assert_eq!(first.1[..4], [255, 0, 0, 255]);
+    assert_eq!(last.1[..4], [0, 0, 255, 255]);
+    // Outside of the blue frame, the red input has been cleared
+    assert_eq!(last.1[(3 * 4 + 3) * 4 + 3], 0);
+    fs::remove_dir_all(dir).unwrap();
+  }
+}
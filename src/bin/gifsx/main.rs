@@ -0,0 +1,135 @@
+//! `gifsx` command-line tool for batch GIF operations.
+//!
+//! Inputs accept glob patterns, e.g. `gifsx optimize "assets/*.gif" -o out/`.
+//! With several inputs, `--output` names a directory and every result keeps its input's file name.
+
+mod commands;
+
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+#[derive(Parser)]
+#[command(name = "gifsx", version, about = "Batch operations on GIF files")]
+struct Cli {
+  #[command(subcommand)]
+  command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+  /// Print the size, frame count, loop count and duration of GIFs.
+  Info {
+    /// Input files or glob patterns.
+    #[arg(required = true)]
+    inputs: Vec<String>,
+    /// Print the result as JSON.
+    #[arg(long)]
+    json: bool,
+  },
+  /// Write every composited frame as a PNG named `<input>-<index>.png`.
+  Frames {
+    #[arg(required = true)]
+    inputs: Vec<String>,
+    /// Directory for the PNG files.
+    #[arg(short, long)]
+    output: PathBuf,
+  },
+  /// Recompress the frames, optionally with lossy LZW compression.
+  Optimize {
+    #[arg(required = true)]
+    inputs: Vec<String>,
+    #[arg(short, long)]
+    output: PathBuf,
+    /// Lossy compression level in the range 0-200. `0` recompresses losslessly.
+    #[arg(long, default_value_t = 0)]
+    lossy: u32,
+  },
+  /// Scale GIFs. With only one of `--width` and `--height`, the aspect ratio is kept.
+  Resize {
+    #[arg(required = true)]
+    inputs: Vec<String>,
+    #[arg(short, long)]
+    output: PathBuf,
+    #[arg(long)]
+    width: Option<u16>,
+    #[arg(long)]
+    height: Option<u16>,
+    /// Quantization speed in the range 1-30.
+    #[arg(long, default_value_t = 10)]
+    speed: i32,
+  },
+  /// Cut an area out of every frame.
+  Crop {
+    #[arg(required = true)]
+    inputs: Vec<String>,
+    #[arg(short, long)]
+    output: PathBuf,
+    #[arg(long, default_value_t = 0)]
+    x: u16,
+    #[arg(long, default_value_t = 0)]
+    y: u16,
+    #[arg(long)]
+    width: u16,
+    #[arg(long)]
+    height: u16,
+    /// Quantization speed in the range 1-30.
+    #[arg(long, default_value_t = 10)]
+    speed: i32,
+  },
+  /// Change the playback speed. Frames are copied without re-encoding.
+  Speed {
+    #[arg(required = true)]
+    inputs: Vec<String>,
+    #[arg(short, long)]
+    output: PathBuf,
+    /// Speed multiplier, e.g. `2` plays twice as fast and `0.5` half as fast.
+    #[arg(long)]
+    factor: f64,
+  },
+  /// Play GIFs backwards.
+  Reverse {
+    #[arg(required = true)]
+    inputs: Vec<String>,
+    #[arg(short, long)]
+    output: PathBuf,
+    /// Quantization speed in the range 1-30.
+    #[arg(long, default_value_t = 10)]
+    speed: i32,
+  },
+  /// Set the loop count. Frames are copied without re-encoding.
+  Loop {
+    #[arg(required = true)]
+    inputs: Vec<String>,
+    #[arg(short, long)]
+    output: PathBuf,
+    /// Number of repetitions. `-1` loops forever.
+    #[arg(long, allow_negative_numbers = true)]
+    count: i32,
+  },
+  /// Join GIFs into a single GIF, in the given order.
+  Concat {
+    #[arg(required = true)]
+    inputs: Vec<String>,
+    #[arg(short, long)]
+    output: PathBuf,
+  },
+  /// Print the global palette of GIFs as hex colors.
+  Palette {
+    #[arg(required = true)]
+    inputs: Vec<String>,
+    /// Print the result as JSON.
+    #[arg(long)]
+    json: bool,
+  },
+}
+
+fn main() -> ExitCode {
+  match commands::run(Cli::parse().command) {
+    Ok(()) => ExitCode::SUCCESS,
+    Err(e) => {
+      eprintln!("error: {}", e);
+      ExitCode::FAILURE
+    }
+  }
+}
@@ -0,0 +1,469 @@
+// The conversion matrices are kept as published, beyond what an f32 can hold.
+#![allow(clippy::excessive_precision)]
+
+use crate::{Error, Result};
+
+/// A color space that RGB colors can be converted to, with its components as floats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorSpace {
+  /// Hue in degrees (0-360), saturation and lightness (0-1).
+  Hsl,
+  /// Hue in degrees (0-360), saturation and value (0-1).
+  Hsv,
+  /// CIE L\*a\*b\* under the D65 white point. Lightness is 0-100.
+  Lab,
+  /// Cylindrical CIE L\*a\*b\*: lightness (0-100), chroma and hue in degrees (0-360).
+  Lch,
+  /// OKLab. Lightness is 0-1.
+  Oklab,
+}
+
+/// A way to measure how different two colors look.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DistanceMetric {
+  /// Euclidean distance in CIE L\*a\*b\* (ΔE\*76).
+  Cie76,
+  /// The CIEDE2000 color difference (ΔE\*00).
+  Ciede2000,
+  /// Euclidean distance in OKLab.
+  Oklab,
+}
+
+fn to_linear(c: u8) -> f32 {
+  let c = c as f32 / 255.0;
+  if c <= 0.04045 {
+    c / 12.92
+  } else {
+    ((c + 0.055) / 1.055).powf(2.4)
+  }
+}
+
+fn from_linear(c: f32) -> u8 {
+  let c = if c <= 0.0031308 {
+    c * 12.92
+  } else {
+    1.055 * c.powf(1.0 / 2.4) - 0.055
+  };
+  to_u8(c)
+}
+
+fn to_u8(c: f32) -> u8 {
+  (c * 255.0).round().clamp(0.0, 255.0) as u8
+}
+
+// D65 reference white.
+const WHITE: [f32; 3] = [0.95047, 1.0, 1.08883];
+
+fn lab_f(t: f32) -> f32 {
+  if t > 216.0 / 24389.0 {
+    t.cbrt()
+  } else {
+    (24389.0 / 27.0 * t + 16.0) / 116.0
+  }
+}
+
+fn lab_f_inv(t: f32) -> f32 {
+  if t * t * t > 216.0 / 24389.0 {
+    t * t * t
+  } else {
+    (116.0 * t - 16.0) * 27.0 / 24389.0
+  }
+}
+
+fn hue(h: f32) -> f32 {
+  let h = h.rem_euclid(360.0);
+  if h >= 360.0 {
+    0.0
+  } else {
+    h
+  }
+}
+
+/// Converts an sRGB color to CIE L\*a\*b\*.
+pub fn rgb_to_lab([r, g, b]: [u8; 3]) -> [f32; 3] {
+  let (r, g, b) = (to_linear(r), to_linear(g), to_linear(b));
+  let x = (0.4124564 * r + 0.3575761 * g + 0.1804375 * b) / WHITE[0];
+  let y = (0.2126729 * r + 0.7151522 * g + 0.0721750 * b) / WHITE[1];
+  let z = (0.0193339 * r + 0.119192 * g + 0.9503041 * b) / WHITE[2];
+  let (fx, fy, fz) = (lab_f(x), lab_f(y), lab_f(z));
+  [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+}
+
+/// Converts a CIE L\*a\*b\* color to sRGB, clamping colors outside the gamut.
+pub fn lab_to_rgb([l, a, b]: [f32; 3]) -> [u8; 3] {
+  let fy = (l + 16.0) / 116.0;
+  let x = lab_f_inv(fy + a / 500.0) * WHITE[0];
+  let y = lab_f_inv(fy) * WHITE[1];
+  let z = lab_f_inv(fy - b / 200.0) * WHITE[2];
+  [
+    from_linear(3.2404542 * x - 1.5371385 * y - 0.4985314 * z),
+    from_linear(-0.969266 * x + 1.8760108 * y + 0.041556 * z),
+    from_linear(0.0556434 * x - 0.2040259 * y + 1.0572252 * z),
+  ]
+}
+
+/// Converts an sRGB color to OKLab.
+pub fn rgb_to_oklab([r, g, b]: [u8; 3]) -> [f32; 3] {
+  let (r, g, b) = (to_linear(r), to_linear(g), to_linear(b));
+  let l = (0.4122214708 * r + 0.5363325363 * g + 0.0514459929 * b).cbrt();
+  let m = (0.2119034982 * r + 0.6806995451 * g + 0.1073969566 * b).cbrt();
+  let s = (0.0883024619 * r + 0.2817188376 * g + 0.6299787005 * b).cbrt();
+  [
+    0.2104542553 * l + 0.793617785 * m - 0.0040720468 * s,
+    1.9779984951 * l - 2.428592205 * m + 0.4505937099 * s,
+    0.0259040371 * l + 0.7827717662 * m - 0.808675766 * s,
+  ]
+}
+
+/// Converts an OKLab color to sRGB, clamping colors outside the gamut.
+pub fn oklab_to_rgb([l, a, b]: [f32; 3]) -> [u8; 3] {
+  let l_ = (l + 0.3963377774 * a + 0.2158037573 * b).powi(3);
+  let m_ = (l - 0.1055613458 * a - 0.0638541728 * b).powi(3);
+  let s_ = (l - 0.0894841775 * a - 1.291485548 * b).powi(3);
+  [
+    from_linear(4.0767416621 * l_ - 3.3077115913 * m_ + 0.2309699292 * s_),
+    from_linear(-1.2684380046 * l_ + 2.6097574011 * m_ - 0.3413193965 * s_),
+    from_linear(-0.0041960863 * l_ - 0.7034186147 * m_ + 1.707614701 * s_),
+  ]
+}
+
+fn rgb_to_hsx([r, g, b]: [u8; 3], lightness: bool) -> [f32; 3] {
+  let (r, g, b) = (r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0);
+  let max = r.max(g).max(b);
+  let min = r.min(g).min(b);
+  let d = max - min;
+
+  let h = if d == 0.0 {
+    0.0
+  } else if max == r {
+    60.0 * ((g - b) / d)
+  } else if max == g {
+    60.0 * ((b - r) / d + 2.0)
+  } else {
+    60.0 * ((r - g) / d + 4.0)
+  };
+
+  if lightness {
+    let l = (max + min) / 2.0;
+    let s = if d == 0.0 {
+      0.0
+    } else {
+      d / (1.0 - (2.0 * l - 1.0).abs())
+    };
+    [hue(h), s, l]
+  } else {
+    let s = if max == 0.0 { 0.0 } else { d / max };
+    [hue(h), s, max]
+  }
+}
+
+fn hue_to_rgb(h: f32, c: f32, m: f32) -> [u8; 3] {
+  let h = hue(h) / 60.0;
+  let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+  let (r, g, b) = match h as u32 {
+    0 => (c, x, 0.0),
+    1 => (x, c, 0.0),
+    2 => (0.0, c, x),
+    3 => (0.0, x, c),
+    4 => (x, 0.0, c),
+    _ => (c, 0.0, x),
+  };
+  [to_u8(r + m), to_u8(g + m), to_u8(b + m)]
+}
+
+/// Converts an HSL color to sRGB.
+pub fn hsl_to_rgb([h, s, l]: [f32; 3]) -> [u8; 3] {
+  let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+  hue_to_rgb(h, c, l - c / 2.0)
+}
+
+/// Converts an HSV color to sRGB.
+pub fn hsv_to_rgb([h, s, v]: [f32; 3]) -> [u8; 3] {
+  let c = v * s;
+  hue_to_rgb(h, c, v - c)
+}
+
+fn lab_to_lch([l, a, b]: [f32; 3]) -> [f32; 3] {
+  [l, a.hypot(b), hue(b.atan2(a).to_degrees())]
+}
+
+fn lch_to_lab([l, c, h]: [f32; 3]) -> [f32; 3] {
+  let (sin, cos) = h.to_radians().sin_cos();
+  [l, c * cos, c * sin]
+}
+
+/// Converts an sRGB color to `space`.
+pub fn rgb_to_space(rgb: [u8; 3], space: ColorSpace) -> [f32; 3] {
+  match space {
+    ColorSpace::Hsl => rgb_to_hsx(rgb, true),
+    ColorSpace::Hsv => rgb_to_hsx(rgb, false),
+    ColorSpace::Lab => rgb_to_lab(rgb),
+    ColorSpace::Lch => lab_to_lch(rgb_to_lab(rgb)),
+    ColorSpace::Oklab => rgb_to_oklab(rgb),
+  }
+}
+
+/// Converts a color in `space` to sRGB, clamping colors outside the gamut.
+pub fn space_to_rgb(color: [f32; 3], space: ColorSpace) -> [u8; 3] {
+  match space {
+    ColorSpace::Hsl => hsl_to_rgb(color),
+    ColorSpace::Hsv => hsv_to_rgb(color),
+    ColorSpace::Lab => lab_to_rgb(color),
+    ColorSpace::Lch => lab_to_rgb(lch_to_lab(color)),
+    ColorSpace::Oklab => oklab_to_rgb(color),
+  }
+}
+
+fn channels(alpha: bool) -> usize {
+  if alpha {
+    4
+  } else {
+    3
+  }
+}
+
+/// Converts RGB or RGBA pixels to `space`, three floats per pixel.
+/// With `alpha`, the input is RGBA and the alpha channel is kept as a fourth float in the range 0-1.
+pub fn pixels_to_space(pixels: &[u8], space: ColorSpace, alpha: bool) -> Result<Vec<f32>> {
+  let n = channels(alpha);
+  if !pixels.len().is_multiple_of(n) {
+    return Err(Error::invalid(format!(
+      "Pixel length must be a multiple of {}",
+      n
+    )));
+  }
+
+  let mut out = Vec::with_capacity(pixels.len());
+  for p in pixels.chunks_exact(n) {
+    out.extend_from_slice(&rgb_to_space([p[0], p[1], p[2]], space));
+    if alpha {
+      out.push(p[3] as f32 / 255.0);
+    }
+  }
+  Ok(out)
+}
+
+/// Converts colors in `space` back to RGB or RGBA pixels, the inverse of [`pixels_to_space`].
+pub fn space_to_pixels(values: &[f32], space: ColorSpace, alpha: bool) -> Result<Vec<u8>> {
+  let n = channels(alpha);
+  if !values.len().is_multiple_of(n) {
+    return Err(Error::invalid(format!(
+      "Value length must be a multiple of {}",
+      n
+    )));
+  }
+
+  let mut out = Vec::with_capacity(values.len());
+  for v in values.chunks_exact(n) {
+    out.extend_from_slice(&space_to_rgb([v[0], v[1], v[2]], space));
+    if alpha {
+      out.push(to_u8(v[3]));
+    }
+  }
+  Ok(out)
+}
+
+/// The CIEDE2000 difference between two CIE L\*a\*b\* colors.
+pub fn ciede2000([l1, a1, b1]: [f32; 3], [l2, a2, b2]: [f32; 3]) -> f32 {
+  let c_mean = (a1.hypot(b1) + a2.hypot(b2)) / 2.0;
+  let c7 = c_mean.powi(7);
+  let g = 0.5 * (1.0 - (c7 / (c7 + 25f32.powi(7))).sqrt());
+  let (a1, a2) = (a1 * (1.0 + g), a2 * (1.0 + g));
+  let (c1, c2) = (a1.hypot(b1), a2.hypot(b2));
+  let h = |a: f32, b: f32| {
+    if a == 0.0 && b == 0.0 {
+      0.0
+    } else {
+      b.atan2(a).to_degrees().rem_euclid(360.0)
+    }
+  };
+  let (h1, h2) = (h(a1, b1), h(a2, b2));
+
+  let dl = l2 - l1;
+  let dc = c2 - c1;
+  let dh = if c1 * c2 == 0.0 {
+    0.0
+  } else if (h2 - h1).abs() <= 180.0 {
+    h2 - h1
+  } else if h2 <= h1 {
+    h2 - h1 + 360.0
+  } else {
+    h2 - h1 - 360.0
+  };
+  let dh = 2.0 * (c1 * c2).sqrt() * (dh / 2.0).to_radians().sin();
+
+  let l_mean = (l1 + l2) / 2.0;
+  let c_mean = (c1 + c2) / 2.0;
+  let h_mean = if c1 * c2 == 0.0 {
+    h1 + h2
+  } else if (h1 - h2).abs() <= 180.0 {
+    (h1 + h2) / 2.0
+  } else if h1 + h2 < 360.0 {
+    (h1 + h2 + 360.0) / 2.0
+  } else {
+    (h1 + h2 - 360.0) / 2.0
+  };
+
+  let t = 1.0 - 0.17 * (h_mean - 30.0).to_radians().cos()
+    + 0.24 * (2.0 * h_mean).to_radians().cos()
+    + 0.32 * (3.0 * h_mean + 6.0).to_radians().cos()
+    - 0.20 * (4.0 * h_mean - 63.0).to_radians().cos();
+  let l50 = (l_mean - 50.0).powi(2);
+  let sl = 1.0 + 0.015 * l50 / (20.0 + l50).sqrt();
+  let sc = 1.0 + 0.045 * c_mean;
+  let sh = 1.0 + 0.015 * c_mean * t;
+  let c7 = c_mean.powi(7);
+  let rt = -2.0
+    * (c7 / (c7 + 25f32.powi(7))).sqrt()
+    * (60.0 * (-((h_mean - 275.0) / 25.0).powi(2)).exp())
+      .to_radians()
+      .sin();
+
+  let (l, c, h) = (dl / sl, dc / sc, dh / sh);
+  (l * l + c * c + h * h + rt * c * h).max(0.0).sqrt()
+}
+
+fn euclidean(a: [f32; 3], b: [f32; 3]) -> f32 {
+  ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)).sqrt()
+}
+
+/// How different two sRGB colors look under `metric`.
+pub fn distance(a: [u8; 3], b: [u8; 3], metric: DistanceMetric) -> f32 {
+  match metric {
+    DistanceMetric::Cie76 => euclidean(rgb_to_lab(a), rgb_to_lab(b)),
+    DistanceMetric::Ciede2000 => ciede2000(rgb_to_lab(a), rgb_to_lab(b)),
+    DistanceMetric::Oklab => euclidean(rgb_to_oklab(a), rgb_to_oklab(b)),
+  }
+}
+
+/// The distance between each pair of pixels in two RGB or RGBA buffers of the same length. Alpha is ignored.
+pub fn distances(a: &[u8], b: &[u8], metric: DistanceMetric, alpha: bool) -> Result<Vec<f32>> {
+  let n = channels(alpha);
+  if a.len() != b.len() || !a.len().is_multiple_of(n) {
+    return Err(Error::invalid(format!(
+      "Both buffers must have the same length, a multiple of {}",
+      n
+    )));
+  }
+
+  Ok(
+    a.chunks_exact(n)
+      .zip(b.chunks_exact(n))
+      .map(|(a, b)| distance([a[0], a[1], a[2]], [b[0], b[1], b[2]], metric))
+      .collect(),
+  )
+}
+
+/// Returns the index of the palette color closest to `color` under `metric`, or `None` for an empty palette.
+pub fn nearest(palette: &[u8], color: [u8; 3], metric: DistanceMetric) -> Option<usize> {
+  palette
+    .chunks_exact(3)
+    .map(|c| distance([c[0], c[1], c[2]], color, metric))
+    .enumerate()
+    .min_by(|(_, a), (_, b)| a.total_cmp(b))
+    .map(|(i, _)| i)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// The test data of Sharma, Wu and Dalal, "The CIEDE2000 Color-Difference Formula: Implementation Notes,
+  /// Supplementary Test Data, and Mathematical Observations" (2005), Table 1.
+  /// Pairs 9-15 have hues about 180° apart, where the mean hue and hue difference wrap around.
+  const SHARMA: [([f32; 3], [f32; 3], f32); 34] = [
+    ([50.0, 2.6772, -79.7751], [50.0, 0.0, -82.7485], 2.0425),
+    ([50.0, 3.1571, -77.2803], [50.0, 0.0, -82.7485], 2.8615),
+    ([50.0, 2.8361, -74.0200], [50.0, 0.0, -82.7485], 3.4412),
+    ([50.0, -1.3802, -84.2814], [50.0, 0.0, -82.7485], 1.0000),
+    ([50.0, -1.1848, -84.8006], [50.0, 0.0, -82.7485], 1.0000),
+    ([50.0, -0.9009, -85.5211], [50.0, 0.0, -82.7485], 1.0000),
+    ([50.0, 0.0, 0.0], [50.0, -1.0, 2.0], 2.3669),
+    ([50.0, -1.0, 2.0], [50.0, 0.0, 0.0], 2.3669),
+    ([50.0, 2.4900, -0.0010], [50.0, -2.4900, 0.0009], 7.1792),
+    ([50.0, 2.4900, -0.0010], [50.0, -2.4900, 0.0010], 7.1792),
+    ([50.0, 2.4900, -0.0010], [50.0, -2.4900, 0.0011], 7.2195),
+    ([50.0, 2.4900, -0.0010], [50.0, -2.4900, 0.0012], 7.2195),
+    ([50.0, -0.0010, 2.4900], [50.0, 0.0009, -2.4900], 4.8045),
+    ([50.0, -0.0010, 2.4900], [50.0, 0.0010, -2.4900], 4.8045),
+    ([50.0, -0.0010, 2.4900], [50.0, 0.0011, -2.4900], 4.7461),
+    ([50.0, 2.5, 0.0], [50.0, 0.0, -2.5], 4.3065),
+    ([50.0, 2.5, 0.0], [73.0, 25.0, -18.0], 27.1492),
+    ([50.0, 2.5, 0.0], [61.0, -5.0, 29.0], 22.8977),
+    ([50.0, 2.5, 0.0], [56.0, -27.0, -3.0], 31.9030),
+    ([50.0, 2.5, 0.0], [58.0, 24.0, 15.0], 19.4535),
+    ([50.0, 2.5, 0.0], [50.0, 3.1736, 0.5854], 1.0000),
+    ([50.0, 2.5, 0.0], [50.0, 3.2972, 0.0], 1.0000),
+    ([50.0, 2.5, 0.0], [50.0, 1.8634, 0.5757], 1.0000),
+    ([50.0, 2.5, 0.0], [50.0, 3.2592, 0.3350], 1.0000),
+    (
+      [60.2574, -34.0099, 36.2677],
+      [60.4626, -34.1751, 39.4387],
+      1.2644,
+    ),
+    (
+      [63.0109, -31.0961, -5.8663],
+      [62.8187, -29.7946, -4.0864],
+      1.2630,
+    ),
+    (
+      [61.2901, 3.7196, -5.3901],
+      [61.4292, 2.2480, -4.9620],
+      1.8731,
+    ),
+    (
+      [35.0831, -44.1164, 3.7933],
+      [35.0232, -40.0716, 1.5901],
+      1.8645,
+    ),
+    (
+      [22.7233, 20.0904, -46.6940],
+      [23.0331, 14.9730, -42.5619],
+      2.0373,
+    ),
+    (
+      [36.4612, 47.8580, 18.3852],
+      [36.2715, 50.5065, 21.2231],
+      1.4146,
+    ),
+    (
+      [90.8027, -2.0831, 1.4410],
+      [91.1528, -1.6435, 0.0447],
+      1.4441,
+    ),
+    (
+      [90.9257, -0.5406, -0.9208],
+      [88.6381, -0.8985, -0.7239],
+      1.5381,
+    ),
+    (
+      [6.7747, -0.2908, -2.4247],
+      [5.8714, -0.0985, -2.2286],
+      0.6377,
+    ),
+    (
+      [2.0776, 0.0795, -1.1350],
+      [0.9033, -0.0636, -0.5514],
+      0.9082,
+    ),
+  ];
+
+  #[test]
+  fn ciede2000_matches_reference_data() {
+    for (i, &(a, b, expected)) in SHARMA.iter().enumerate() {
+      let d = ciede2000(a, b);
+      assert!(
+        (d - expected).abs() < 1e-4,
+        "pair {}: expected {}, got {}",
+        i + 1,
+        expected,
+        d
+      );
+      assert!(
+        (ciede2000(b, a) - d).abs() < 1e-5,
+        "pair {} is not symmetric",
+        i + 1
+      );
+    }
+  }
+}
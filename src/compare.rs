@@ -0,0 +1,250 @@
+//! Perceptual comparison of frames and whole animations, for regression tests of rendering pipelines.
+
+use crate::compositor::Compositor;
+use crate::{Error, Result};
+
+/// Side length of the windows SSIM is computed over.
+const WINDOW: usize = 8;
+/// Distance between neighbouring SSIM windows.
+const STEP: usize = 4;
+const C1: f64 = (0.01 * 255.0) * (0.01 * 255.0);
+const C2: f64 = (0.03 * 255.0) * (0.03 * 255.0);
+
+/// How [`compare_frames`] and [`compare_gifs`] compare pixels.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CompareOptions {
+  /// The largest channel difference that still counts a pixel as unchanged. Defaults to `0`.
+  pub threshold: u8,
+  /// Render a diff image for every frame pair, see [`FrameComparison::diff`].
+  pub diff_images: bool,
+}
+
+/// How much two frames differ.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameComparison {
+  /// Peak signal-to-noise ratio over all RGBA channels, in dB. Infinite for identical frames.
+  pub psnr: f64,
+  /// Mean structural similarity of the luminance, in the range -1 to 1 where 1 means identical.
+  pub ssim: f64,
+  /// The largest difference of any channel of any pixel.
+  pub max_difference: u8,
+  /// The number of pixels with a channel difference above the threshold.
+  pub changed_pixels: usize,
+  /// An opaque RGBA image showing unchanged pixels faded and changed pixels in red, if requested.
+  pub diff: Option<Vec<u8>>,
+}
+
+/// How much two animations differ, frame by frame after compositing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+  /// One comparison per pair of frames, up to the length of the shorter animation.
+  pub frames: Vec<FrameComparison>,
+  /// The delays of each pair of frames, in units of 10 ms.
+  pub delays: Vec<[u16; 2]>,
+  /// The number of frames of each animation.
+  pub frame_counts: [usize; 2],
+  /// The summed delays of each animation, in units of 10 ms.
+  pub durations: [u32; 2],
+  /// The lowest PSNR of any frame pair. Infinite if all compared frames are identical.
+  pub min_psnr: f64,
+  /// The lowest SSIM of any frame pair.
+  pub min_ssim: f64,
+  /// The largest channel difference of any frame pair.
+  pub max_difference: u8,
+  /// Both animations have the same frames with the same delays, down to every pixel.
+  pub identical: bool,
+}
+
+/// Luminance of a pixel, blended over white so that transparent and opaque pixels can be told apart.
+pub(crate) fn pixel_luma(p: &[u8]) -> f64 {
+  let l = (299.0 * p[0] as f64 + 587.0 * p[1] as f64 + 114.0 * p[2] as f64) / 1000.0;
+  255.0 - (255.0 - l) * p[3] as f64 / 255.0
+}
+
+fn luma(rgba: &[u8]) -> Vec<f64> {
+  rgba.chunks_exact(4).map(pixel_luma).collect()
+}
+
+/// Start offsets of the windows along one axis, always including one that ends at the edge.
+fn window_starts(len: usize, size: usize) -> impl Iterator<Item = usize> {
+  let last = len - size;
+  (0..last).step_by(STEP).chain(std::iter::once(last))
+}
+
+fn ssim(a: &[f64], b: &[f64], width: usize, height: usize) -> f64 {
+  let (w, h) = (WINDOW.min(width), WINDOW.min(height));
+  let n = (w * h) as f64;
+  let (mut total, mut windows) = (0.0, 0);
+
+  for y0 in window_starts(height, h) {
+    for x0 in window_starts(width, w) {
+      let (mut sa, mut sb, mut saa, mut sbb, mut sab) = (0.0, 0.0, 0.0, 0.0, 0.0);
+      for y in y0..y0 + h {
+        for i in y * width + x0..y * width + x0 + w {
+          let (va, vb) = (a[i], b[i]);
+          sa += va;
+          sb += vb;
+          saa += va * va;
+          sbb += vb * vb;
+          sab += va * vb;
+        }
+      }
+
+      let (ma, mb) = (sa / n, sb / n);
+      let var_a = saa / n - ma * ma;
+      let var_b = sbb / n - mb * mb;
+      let cov = sab / n - ma * mb;
+      total += ((2.0 * ma * mb + C1) * (2.0 * cov + C2))
+        / ((ma * ma + mb * mb + C1) * (var_a + var_b + C2));
+      windows += 1;
+    }
+  }
+  total / windows as f64
+}
+
+/// Compares two RGBA images of the same size.
+pub fn compare_frames(
+  a: &[u8],
+  b: &[u8],
+  width: u16,
+  height: u16,
+  options: &CompareOptions,
+) -> Result<FrameComparison> {
+  let (width, height) = (width as usize, height as usize);
+  let size = width * height * 4;
+  if size == 0 {
+    return Err(Error::invalid("Width and height need to be at least 1"));
+  }
+  if a.len() != size || b.len() != size {
+    return Err(Error::invalid(format!(
+      "Buffer size mismatch: expected {} bytes, got {} and {}",
+      size,
+      a.len(),
+      b.len()
+    )));
+  }
+
+  let (mut squares, mut max_difference, mut changed_pixels) = (0u64, 0u8, 0);
+  let mut diff = options.diff_images.then(|| Vec::with_capacity(size));
+  for (pa, pb) in a.chunks_exact(4).zip(b.chunks_exact(4)) {
+    let mut pixel_max = 0;
+    for (&ca, &cb) in pa.iter().zip(pb) {
+      let d = ca.abs_diff(cb);
+      squares += d as u64 * d as u64;
+      pixel_max = pixel_max.max(d);
+    }
+    max_difference = max_difference.max(pixel_max);
+
+    let changed = pixel_max > options.threshold;
+    changed_pixels += changed as usize;
+    if let Some(diff) = &mut diff {
+      if changed {
+        diff.extend_from_slice(&[255, 0, 0, 255]);
+      } else {
+        let v = 255 - (255 - pixel_luma(pa).round() as u8) / 4;
+        diff.extend_from_slice(&[v, v, v, 255]);
+      }
+    }
+  }
+
+  let mse = squares as f64 / size as f64;
+  let psnr = if mse == 0.0 {
+    f64::INFINITY
+  } else {
+    10.0 * (255.0 * 255.0 / mse).log10()
+  };
+
+  Ok(FrameComparison {
+    psnr,
+    ssim: ssim(&luma(a), &luma(b), width, height),
+    max_difference,
+    changed_pixels,
+    diff,
+  })
+}
+
+fn decoding_error(e: gif::DecodingError) -> Error {
+  Error::Decoding(format!("Failed to decode a GIF: {}", e))
+}
+
+/// Decodes a GIF and composites its frames one at a time.
+struct Frames<'a> {
+  decoder: gif::Decoder<&'a [u8]>,
+  compositor: Compositor,
+}
+
+impl Frames<'_> {
+  fn open(gif: &[u8]) -> Result<Frames<'_>> {
+    let mut options = gif::DecodeOptions::new();
+    options.set_color_output(gif::ColorOutput::RGBA);
+    let decoder = options.read_info(gif).map_err(decoding_error)?;
+    let compositor = Compositor::new(decoder.width(), decoder.height());
+    Ok(Frames {
+      decoder,
+      compositor,
+    })
+  }
+
+  /// Returns the next frame's delay and the canvas after drawing it.
+  fn next(&mut self) -> Result<Option<(u16, &[u8])>> {
+    match self.decoder.read_next_frame().map_err(decoding_error)? {
+      Some(frame) => Ok(Some((
+        frame.delay,
+        self.compositor.draw(frame, &frame.buffer),
+      ))),
+      None => Ok(None),
+    }
+  }
+}
+
+/// Decodes two GIFs of the same size and compares their composited frames pair by pair, see [`compare_frames`].
+pub fn compare_gifs(a: &[u8], b: &[u8], options: &CompareOptions) -> Result<Comparison> {
+  let mut frames = [Frames::open(a)?, Frames::open(b)?];
+  let sizes = frames
+    .each_ref()
+    .map(|f| (f.decoder.width(), f.decoder.height()));
+  if sizes[0] != sizes[1] {
+    return Err(Error::invalid(format!(
+      "GIFs differ in size: {}x{} and {}x{}",
+      sizes[0].0, sizes[0].1, sizes[1].0, sizes[1].1
+    )));
+  }
+  let (width, height) = sizes[0];
+
+  let mut comparison = Comparison {
+    frames: Vec::new(),
+    delays: Vec::new(),
+    frame_counts: [0; 2],
+    durations: [0; 2],
+    min_psnr: f64::INFINITY,
+    min_ssim: 1.0,
+    max_difference: 0,
+    identical: true,
+  };
+
+  let [fa, fb] = &mut frames;
+  loop {
+    let (next_a, next_b) = (fa.next()?, fb.next()?);
+    for (i, next) in [&next_a, &next_b].into_iter().enumerate() {
+      if let Some((delay, _)) = next {
+        comparison.frame_counts[i] += 1;
+        comparison.durations[i] += *delay as u32;
+      }
+    }
+
+    match (next_a, next_b) {
+      (Some((delay_a, canvas_a)), Some((delay_b, canvas_b))) => {
+        let frame = compare_frames(canvas_a, canvas_b, width, height, options)?;
+        comparison.min_psnr = comparison.min_psnr.min(frame.psnr);
+        comparison.min_ssim = comparison.min_ssim.min(frame.ssim);
+        comparison.max_difference = comparison.max_difference.max(frame.max_difference);
+        comparison.identical &= frame.max_difference == 0 && delay_a == delay_b;
+        comparison.frames.push(frame);
+        comparison.delays.push([delay_a, delay_b]);
+      }
+      (None, None) => break,
+      _ => comparison.identical = false,
+    }
+  }
+  Ok(comparison)
+}
@@ -0,0 +1,58 @@
+/// Renders frames onto an RGBA canvas, applying each frame's disposal method before the next one is drawn.
+pub struct Compositor {
+  width: u16,
+  height: u16,
+  canvas: Vec<u8>,
+  /// Disposal of the last drawn frame: its method, area, and the canvas to restore for `Previous`.
+  pending: Option<(gif::DisposalMethod, [usize; 4], Option<Vec<u8>>)>,
+}
+
+impl Compositor {
+  pub fn new(width: u16, height: u16) -> Self {
+    Self {
+      width,
+      height,
+      canvas: vec![0; width as usize * height as usize * 4],
+      pending: None,
+    }
+  }
+
+  /// Draws the frame's RGBA pixels at its offset and returns the resulting canvas.
+  /// Fully transparent pixels leave the canvas untouched; pixels outside of the canvas are clipped.
+  pub fn draw(&mut self, frame: &gif::Frame, rgba: &[u8]) -> &[u8] {
+    let width = self.width as usize;
+    match self.pending.take() {
+      Some((gif::DisposalMethod::Background, [left, top, right, bottom], _)) => {
+        for y in top..bottom {
+          self.canvas[(y * width + left) * 4..(y * width + right) * 4].fill(0);
+        }
+      }
+      Some((gif::DisposalMethod::Previous, _, Some(previous))) => self.canvas = previous,
+      _ => {}
+    }
+
+    let left = (frame.left as usize).min(width);
+    let top = (frame.top as usize).min(self.height as usize);
+    let right = (frame.left as usize + frame.width as usize).min(width);
+    let bottom = (frame.top as usize + frame.height as usize).min(self.height as usize);
+
+    let previous = (frame.dispose == gif::DisposalMethod::Previous).then(|| self.canvas.clone());
+    self.pending = Some((frame.dispose, [left, top, right, bottom], previous));
+
+    for y in top..bottom {
+      let src_row = (y - frame.top as usize) * frame.width as usize;
+      for x in left..right {
+        let src = (src_row + x - frame.left as usize) * 4;
+        let Some(pixel) = rgba.get(src..src + 4) else {
+          continue;
+        };
+        if pixel[3] != 0 {
+          let dst = (y * width + x) * 4;
+          self.canvas[dst..dst + 4].copy_from_slice(pixel);
+        }
+      }
+    }
+
+    &self.canvas
+  }
+}
@@ -0,0 +1,64 @@
+use crate::{apng, hex, pixels, quantize};
+use crate::{Error, PixelFormat, Result};
+use std::borrow::Cow;
+
+/// Converts RGBA pixels to RGB, RGBA or hex pixels. For indexed pixels, see [`convert_frame`].
+pub fn rgba_to(rgba: Vec<u8>, format: PixelFormat) -> Result<Vec<u8>> {
+  match format {
+    PixelFormat::Rgba => Ok(rgba),
+    PixelFormat::Rgb => Ok(pixels::rgba_to_rgb(&rgba)),
+    PixelFormat::Hex => hex::rgba_to_hex(&rgba, true, false),
+    PixelFormat::Indexed | PixelFormat::Lzw => Err(Error::invalid(
+      "RGBA pixels can only be converted to RGB, RGBA or hex",
+    )),
+  }
+}
+
+/// Converts a frame's pixels from `from` to `to`, keeping its offset, delay and disposal method.
+///
+/// Indexed pixels are resolved against the frame's palette, or `global_palette` if it has none.
+/// Converting to indexed pixels builds an exact palette for frames with up to 256 colors and quantizes
+/// the rest at `speed`; fully transparent pixels share the transparent index.
+/// Other formats carry no palette.
+pub fn convert_frame(
+  frame: &gif::Frame,
+  from: PixelFormat,
+  to: PixelFormat,
+  global_palette: Option<&[u8]>,
+  speed: i32,
+) -> Result<gif::Frame<'static>> {
+  if to == PixelFormat::Lzw {
+    return Err(Error::invalid("Frames can not be converted to LZW data"));
+  }
+
+  let mut converted = if from == to {
+    gif::Frame {
+      buffer: Cow::Owned(frame.buffer.to_vec()),
+      palette: frame.palette.clone(),
+      transparent: frame.transparent,
+      ..gif::Frame::default()
+    }
+  } else {
+    let mut rgba = apng::frame_to_rgba(frame, from, global_palette)?;
+    if to == PixelFormat::Indexed {
+      for p in rgba.chunks_exact_mut(4).filter(|p| p[3] == 0) {
+        p.copy_from_slice(&[0, 0, 0, 0]);
+      }
+      quantize::quantize_rgba(frame.width, frame.height, &mut rgba, speed)?
+    } else {
+      gif::Frame {
+        buffer: Cow::Owned(rgba_to(rgba, to)?),
+        ..gif::Frame::default()
+      }
+    }
+  };
+
+  converted.width = frame.width;
+  converted.height = frame.height;
+  converted.left = frame.left;
+  converted.top = frame.top;
+  converted.delay = frame.delay;
+  converted.dispose = frame.dispose;
+  converted.needs_user_input = frame.needs_user_input;
+  Ok(converted)
+}
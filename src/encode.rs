@@ -0,0 +1,91 @@
+//! Preparing frames for `gif::Encoder`: quantizing, compacting palettes, lossy LZW compression and palettes for LZW data.
+
+use crate::palette::PaletteOrder;
+use crate::{convert, lzw, palette, Error, PixelFormat, Result};
+use std::borrow::Cow;
+use std::io::Write;
+
+/// Turns a frame holding `format` pixels into the frame to write, borrowing it if there is nothing to change.
+///
+/// RGB, RGBA and hex frames are quantized first, see [`convert::convert_frame`]. With `compact`, a palette of the
+/// frame's own is compacted, see [`palette::compact_frame_palette`]. With a `lossy` level above `0`, the frame is
+/// LZW-compressed against its palette or `global_palette` and has to be written with
+/// `gif::Encoder::write_lzw_pre_encoded_frame`, otherwise with `gif::Encoder::write_frame`. See [`write_frame`].
+pub fn prepare_frame<'f>(
+  frame: &'f gif::Frame<'f>,
+  format: PixelFormat,
+  compact: Option<PaletteOrder>,
+  lossy: u32,
+  global_palette: Option<&[u8]>,
+) -> Result<Cow<'f, gif::Frame<'f>>> {
+  let mut frame = match format {
+    PixelFormat::Indexed => Cow::Borrowed(frame),
+    PixelFormat::Lzw => {
+      return Err(Error::invalid(
+        "Frame already holds LZW data and can be written as it is",
+      ))
+    }
+    _ => Cow::Owned(convert::convert_frame(
+      frame,
+      format,
+      PixelFormat::Indexed,
+      None,
+      15,
+    )?),
+  };
+
+  if let Some(order) = compact {
+    if frame.palette.is_some() {
+      palette::compact_frame_palette(frame.to_mut(), None, order)?;
+    }
+  }
+  if lossy > 0 {
+    lzw::compress_frame(frame.to_mut(), global_palette, lossy);
+  }
+  Ok(frame)
+}
+
+/// Prepares a frame with [`prepare_frame`] and writes it to `encoder`.
+pub fn write_frame<W: Write>(
+  encoder: &mut gif::Encoder<W>,
+  frame: &gif::Frame,
+  format: PixelFormat,
+  compact: Option<PaletteOrder>,
+  lossy: u32,
+  global_palette: Option<&[u8]>,
+) -> Result<()> {
+  let prepared = prepare_frame(frame, format, compact, lossy, global_palette)?;
+  let written = if lossy > 0 {
+    encoder.write_lzw_pre_encoded_frame(&prepared)
+  } else {
+    encoder.write_frame(&prepared)
+  };
+  written.map_err(|e| Error::Encoding(format!("Failed to write a frame: {}", e)))
+}
+
+/// Makes sure a frame holding LZW data decodes against the right palette once written, borrowing it if it does.
+///
+/// A frame without a palette of its own was decoded against the global palette of its GIF, `source_palette`. If the
+/// encoder's `global_palette` differs, the source palette becomes the frame's own. Frames whose source palette is
+/// unknown can only be written by an encoder with a global palette.
+pub fn prepare_raw_frame<'f>(
+  frame: &'f gif::Frame<'f>,
+  source_palette: Option<&[u8]>,
+  global_palette: Option<&[u8]>,
+) -> Result<Cow<'f, gif::Frame<'f>>> {
+  // An empty palette leaves the global color table out
+  let global_palette = global_palette.filter(|p| !p.is_empty());
+  match (&frame.palette, source_palette) {
+    (Some(_), _) => Ok(Cow::Borrowed(frame)),
+    (None, Some(source)) if global_palette == Some(source) => Ok(Cow::Borrowed(frame)),
+    (None, Some(source)) => Ok(Cow::Owned(gif::Frame {
+      palette: Some(source.to_vec()),
+      buffer: Cow::Borrowed(&frame.buffer),
+      ..*frame
+    })),
+    (None, None) if global_palette.is_some() => Ok(Cow::Borrowed(frame)),
+    (None, None) => Err(Error::invalid(
+      "Frame has no palette of its own and the encoder has no global palette",
+    )),
+  }
+}
@@ -0,0 +1,47 @@
+use std::fmt;
+
+/// Errors returned by the core functions of this crate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+  /// An argument is out of range or does not match the image.
+  InvalidArgument(String),
+  /// Writing an image failed.
+  Encoding(String),
+  /// Reading an image failed.
+  Decoding(String),
+  /// The operation was cancelled through a [`CancelToken`](crate::progress::CancelToken).
+  Aborted,
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+  pub(crate) fn invalid(message: impl Into<String>) -> Self {
+    Error::InvalidArgument(message.into())
+  }
+}
+
+impl fmt::Display for Error {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Error::InvalidArgument(message) | Error::Encoding(message) | Error::Decoding(message) => {
+        f.write_str(message)
+      }
+      Error::Aborted => f.write_str("The operation was aborted"),
+    }
+  }
+}
+
+impl std::error::Error for Error {}
+
+#[cfg(feature = "napi")]
+impl From<Error> for napi::Error {
+  fn from(e: Error) -> Self {
+    let status = match e {
+      Error::InvalidArgument(_) => napi::Status::InvalidArg,
+      Error::Aborted => napi::Status::Cancelled,
+      _ => napi::Status::GenericFailure,
+    };
+    napi::Error::new(status, e.to_string())
+  }
+}
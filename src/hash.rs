@@ -0,0 +1,225 @@
+//! Perceptual hashes of frames and fingerprints of whole animations, for finding re-uploads after re-encoding or resizing.
+
+use crate::compare::pixel_luma;
+use crate::{Error, Result};
+use std::f64::consts::PI;
+
+/// Side length of the image the DCT of [`phash`] is taken over.
+const DCT_SIZE: usize = 32;
+/// Side length of the low frequencies [`phash`] keeps.
+const LOW_FREQUENCIES: usize = 8;
+
+fn check_size(rgba: &[u8], width: u16, height: u16) -> Result<()> {
+  let size = width as usize * height as usize * 4;
+  if size == 0 {
+    return Err(Error::invalid("Width and height need to be at least 1"));
+  }
+  if rgba.len() != size {
+    return Err(Error::invalid(format!(
+      "Buffer size mismatch: expected {} bytes, got {}",
+      size,
+      rgba.len()
+    )));
+  }
+  Ok(())
+}
+
+/// Averages the luminance of the pixels over a `columns` by `rows` grid. Images smaller than the grid repeat pixels.
+fn shrink(rgba: &[u8], width: u16, height: u16, columns: usize, rows: usize) -> Vec<f64> {
+  let (width, height) = (width as usize, height as usize);
+  let mut cells = Vec::with_capacity(columns * rows);
+  for row in 0..rows {
+    let y0 = row * height / rows;
+    let y1 = ((row + 1) * height / rows).max(y0 + 1);
+    for column in 0..columns {
+      let x0 = column * width / columns;
+      let x1 = ((column + 1) * width / columns).max(x0 + 1);
+
+      let mut sum = 0.0;
+      for y in y0..y1 {
+        for p in rgba[(y * width + x0) * 4..(y * width + x1) * 4].chunks_exact(4) {
+          sum += pixel_luma(p);
+        }
+      }
+      cells.push(sum / ((y1 - y0) * (x1 - x0)) as f64);
+    }
+  }
+  cells
+}
+
+/// The difference hash of RGBA pixels: each bit tells whether a cell of a 9 by 8 grid is brighter than its right neighbour.
+pub fn dhash(rgba: &[u8], width: u16, height: u16) -> Result<u64> {
+  check_size(rgba, width, height)?;
+  let cells = shrink(rgba, width, height, 9, 8);
+
+  let mut hash = 0;
+  for row in cells.chunks_exact(9) {
+    for pair in row.windows(2) {
+      hash = hash << 1 | (pair[0] > pair[1]) as u64;
+    }
+  }
+  Ok(hash)
+}
+
+/// The perceptual hash of RGBA pixels: each bit tells whether one of the 64 lowest frequencies of the DCT
+/// of a 32 by 32 grayscale version is above the median of the 63 coefficients after the DC one, which only
+/// follows the overall brightness and would otherwise skew the median.
+pub fn phash(rgba: &[u8], width: u16, height: u16) -> Result<u64> {
+  check_size(rgba, width, height)?;
+  let cells = shrink(rgba, width, height, DCT_SIZE, DCT_SIZE);
+
+  let cosines: Vec<f64> = (0..LOW_FREQUENCIES * DCT_SIZE)
+    .map(|i| {
+      let (u, x) = (i / DCT_SIZE, i % DCT_SIZE);
+      ((2 * x + 1) as f64 * u as f64 * PI / (2 * DCT_SIZE) as f64).cos()
+    })
+    .collect();
+  let cosine = |u: usize, x: usize| cosines[u * DCT_SIZE + x];
+
+  // The DCT is separable: transform the rows, then the columns of the low frequencies.
+  let mut rows = vec![0.0; DCT_SIZE * LOW_FREQUENCIES];
+  for y in 0..DCT_SIZE {
+    for u in 0..LOW_FREQUENCIES {
+      rows[y * LOW_FREQUENCIES + u] = (0..DCT_SIZE)
+        .map(|x| cells[y * DCT_SIZE + x] * cosine(u, x))
+        .sum();
+    }
+  }
+  let mut coefficients = Vec::with_capacity(LOW_FREQUENCIES * LOW_FREQUENCIES);
+  for v in 0..LOW_FREQUENCIES {
+    for u in 0..LOW_FREQUENCIES {
+      coefficients.push(
+        (0..DCT_SIZE)
+          .map(|y| rows[y * LOW_FREQUENCIES + u] * cosine(v, y))
+          .sum::<f64>(),
+      );
+    }
+  }
+
+  let mut sorted = coefficients[1..].to_vec();
+  sorted.sort_unstable_by(f64::total_cmp);
+  let median = sorted[sorted.len() / 2];
+  Ok(
+    coefficients
+      .iter()
+      .fold(0, |hash, &c| hash << 1 | (c > median) as u64),
+  )
+}
+
+/// The number of bits two hashes differ in, from 0 for identical images to 64.
+pub fn distance(a: u64, b: u64) -> u32 {
+  (a ^ b).count_ones()
+}
+
+/// Writes a hash as 16 lowercase hex digits.
+pub fn format_hash(hash: u64) -> String {
+  format!("{:016x}", hash)
+}
+
+/// Parses a hash written by [`format_hash`].
+pub fn parse_hash(s: &str) -> Result<u64> {
+  match u64::from_str_radix(s, 16) {
+    Ok(hash) if s.len() == 16 && s.bytes().all(|c| c.is_ascii_hexdigit()) => Ok(hash),
+    _ => Err(Error::invalid(format!(
+      "Invalid hash `{}`: expected 16 hex digits",
+      s
+    ))),
+  }
+}
+
+/// The hashes of a frame, see [`Fingerprint`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameHash {
+  /// The difference hash of the frame, see [`dhash`].
+  pub dhash: u64,
+  /// The perceptual hash of the frame, see [`phash`].
+  pub phash: u64,
+  /// How long the frame is shown, in units of 10 ms.
+  pub delay: u32,
+}
+
+impl FrameHash {
+  /// Hashes a composited frame, see [`dhash`] and [`phash`].
+  pub fn new(rgba: &[u8], width: u16, height: u16, delay: u16) -> Result<Self> {
+    Ok(Self {
+      dhash: dhash(rgba, width, height)?,
+      phash: phash(rgba, width, height)?,
+      delay: delay as u32,
+    })
+  }
+
+  /// 1 for frames that look the same, around 0.5 for unrelated ones.
+  fn similarity(&self, other: &FrameHash) -> f64 {
+    let bits = distance(self.dhash, other.dhash) + distance(self.phash, other.phash);
+    1.0 - bits as f64 / 128.0
+  }
+}
+
+/// The hashes of the composited frames of an animation along with their timing.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Fingerprint {
+  /// Consecutive frames with the same hashes are merged into one, adding up their delays.
+  pub frames: Vec<FrameHash>,
+}
+
+impl Fingerprint {
+  /// An empty fingerprint, to [`push`](Self::push) the frames into.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Adds the next frame, merging it into the previous one if their hashes are the same.
+  /// Encoders and optimizers often merge identical frames, so this keeps fingerprints comparable.
+  pub fn push(&mut self, frame: FrameHash) {
+    match self.frames.last_mut() {
+      Some(last) if last.dhash == frame.dhash && last.phash == frame.phash => {
+        last.delay += frame.delay
+      }
+      _ => self.frames.push(frame),
+    }
+  }
+
+  /// The summed delays of all frames, in units of 10 ms.
+  pub fn duration(&self) -> u32 {
+    self.frames.iter().map(|f| f.delay).sum()
+  }
+
+  /// The start of every frame and the end of the last one as fractions of the animation's length.
+  /// Frames without a delay count as one unit, so that animations without timing can still be compared.
+  fn timeline(&self) -> Vec<f64> {
+    let total: u32 = self.frames.iter().map(|f| f.delay.max(1)).sum();
+    let mut time = 0;
+    let mut timeline = vec![0.0];
+    for f in &self.frames {
+      time += f.delay.max(1);
+      timeline.push(time as f64 / total as f64);
+    }
+    timeline
+  }
+
+  /// How alike two animations are, in the range 0-1 where 1 means they look the same throughout.
+  ///
+  /// Both animations are stretched to the same length and compared over time, so that a frame
+  /// counts as much as it is shown. Speeding an animation up or down keeps it similar, reordering frames does not.
+  pub fn similarity(&self, other: &Fingerprint) -> f64 {
+    if self.frames.is_empty() || other.frames.is_empty() {
+      return (self.frames.is_empty() && other.frames.is_empty()) as u8 as f64;
+    }
+
+    let (ta, tb) = (self.timeline(), other.timeline());
+    let (mut i, mut j) = (0, 0);
+    let (mut start, mut total) = (0.0, 0.0);
+    while i < self.frames.len() && j < other.frames.len() {
+      let end = ta[i + 1].min(tb[j + 1]);
+      total += (end - start) * self.frames[i].similarity(&other.frames[j]);
+      start = end;
+      if ta[i + 1] <= end {
+        i += 1;
+      }
+      if tb[j + 1] <= end {
+        j += 1;
+      }
+    }
+    total
+  }
+}
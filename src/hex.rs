@@ -0,0 +1,271 @@
+use crate::{css, Error, Result};
+
+const HEX_CHARS: &[u8; 16] = b"0123456789ABCDEF";
+const HEX_DECODE: [u8; 256] = {
+  let mut table = [0u8; 256];
+  let mut i = 0;
+  while i < 10 {
+    table[(b'0' + i) as usize] = i;
+    i += 1;
+  }
+  i = 0;
+  while i < 6 {
+    table[(b'a' + i) as usize] = 10 + i;
+    table[(b'A' + i) as usize] = 10 + i;
+    i += 1;
+  }
+  table
+};
+
+fn can_shorten(r: u8, g: u8, b: u8, a: Option<u8>) -> bool {
+  [r, g, b, a.unwrap_or(0)]
+    .iter()
+    .all(|v| v.is_multiple_of(17))
+}
+
+/// Rejects anything but hex digits after the first `start` bytes of `h`, naming the offending character and its index.
+fn check_hex_digits(h: &str, start: usize) -> Result<()> {
+  match h
+    .char_indices()
+    .find(|&(i, c)| i >= start && !c.is_ascii_hexdigit())
+  {
+    Some((i, c)) => Err(Error::invalid(format!(
+      "Invalid hex character `{}` at index {} in {}",
+      c, i, h
+    ))),
+    None => Ok(()),
+  }
+}
+
+fn hex_as_u8(chars: &[u8]) -> u8 {
+  (HEX_DECODE[chars[0] as usize] << 4) | HEX_DECODE[chars[1] as usize]
+}
+
+fn push_hex(s: &mut Vec<u8>, r: u8, g: u8, b: u8, a: Option<u8>, short: bool) {
+  s.reserve(if short {
+    1 + 3 + a.is_some() as usize
+  } else {
+    1 + 6 + a.is_some() as usize * 2
+  });
+
+  s.push(b'#');
+  for val in [r, g, b].into_iter().chain(a) {
+    if short {
+      s.push(HEX_CHARS[(val & 0x0F) as usize]);
+    } else {
+      s.push(HEX_CHARS[(val >> 4) as usize]);
+      s.push(HEX_CHARS[(val & 0x0F) as usize]);
+    }
+  }
+}
+
+/// Converts RGBA pixels to concatenated `#RRGGBB` strings, as held by frames with hex pixels, adding `AA` for non-opaque pixels or when `always_include_alpha` is set.
+/// With `allow_short`, colors that fit are written as `#RGB`/`#RGBA`.
+pub fn rgba_to_hex(rgba: &[u8], always_include_alpha: bool, allow_short: bool) -> Result<Vec<u8>> {
+  if !rgba.len().is_multiple_of(4) {
+    return Err(Error::invalid("RGBA length must be a multiple of 4"));
+  }
+
+  let mut buffer = Vec::with_capacity(rgba.len() / 4 * 9);
+
+  for chunk in rgba.chunks_exact(4) {
+    let a = if always_include_alpha || chunk[3] != 255 {
+      Some(chunk[3])
+    } else {
+      None
+    };
+
+    push_hex(
+      &mut buffer,
+      chunk[0],
+      chunk[1],
+      chunk[2],
+      a,
+      allow_short && can_shorten(chunk[0], chunk[1], chunk[2], a),
+    );
+  }
+
+  Ok(buffer)
+}
+
+/// Parses pixels stored as consecutive `#RRGGBBAA` colors, as in frames holding hex data, into RGBA pixels.
+pub fn hex_pixels_to_rgba(pixels: &[u8]) -> Result<Vec<u8>> {
+  if !pixels.len().is_multiple_of(9) {
+    return Err(Error::invalid("Hex pixel length must be a multiple of 9"));
+  }
+
+  let mut rgba = Vec::with_capacity(pixels.len() / 9 * 4);
+  for (n, chunk) in pixels.chunks_exact(9).enumerate() {
+    if chunk[0] != b'#' {
+      return Err(Error::invalid("Hex pixels must start with `#`"));
+    }
+    if let Some(i) = chunk[1..].iter().position(|c| !c.is_ascii_hexdigit()) {
+      return Err(Error::invalid(format!(
+        "Invalid hex character at index {}",
+        n * 9 + 1 + i
+      )));
+    }
+    for i in 0..4 {
+      rgba.push(hex_as_u8(&chunk[1 + i * 2..3 + i * 2]));
+    }
+  }
+  Ok(rgba)
+}
+
+/// Parses hex or other CSS colors into RGBA pixels, see [`css::parse_color`]. Colors without alpha are opaque.
+pub fn hex_to_rgba<S: AsRef<str>>(hexes: &[S]) -> Result<Vec<u8>> {
+  let mut rgba = Vec::with_capacity(hexes.len() * 4);
+  for h in hexes {
+    rgba.extend_from_slice(&css::parse_color(h.as_ref())?);
+  }
+  Ok(rgba)
+}
+
+/// Parses a single `#RGB`, `#RGBA`, `#RRGGBB` or `#RRGGBBAA` color. The `#` is optional.
+pub fn parse_hex_rgba(h: &str) -> Result<[u8; 4]> {
+  let b = h.as_bytes();
+  let offset = if b.first() == Some(&b'#') { 1 } else { 0 };
+  check_hex_digits(h, offset)?;
+
+  match b.len() - offset {
+    8 | 6 => Ok([
+      hex_as_u8(&b[offset..offset + 2]),
+      hex_as_u8(&b[offset + 2..offset + 4]),
+      hex_as_u8(&b[offset + 4..offset + 6]),
+      if b.len() - offset == 8 {
+        hex_as_u8(&b[offset + 6..offset + 8])
+      } else {
+        255
+      },
+    ]),
+    4 | 3 => Ok([
+      (HEX_DECODE[b[offset] as usize] << 4) | HEX_DECODE[b[offset] as usize],
+      (HEX_DECODE[b[offset + 1] as usize] << 4) | HEX_DECODE[b[offset + 1] as usize],
+      (HEX_DECODE[b[offset + 2] as usize] << 4) | HEX_DECODE[b[offset + 2] as usize],
+      if b.len() - offset == 4 {
+        (HEX_DECODE[b[offset + 3] as usize] << 4) | HEX_DECODE[b[offset + 3] as usize]
+      } else {
+        255
+      },
+    ]),
+    _ => Err(Error::invalid(format!("Invalid hex: {}", h))),
+  }
+}
+
+/// Parses `#RGB` or `#RRGGBB` colors, or other CSS colors whose alpha is dropped, into RGB pixels.
+pub fn hex_to_rgb<S: AsRef<str>>(hexes: &[S]) -> Result<Vec<u8>> {
+  let mut rgb = Vec::with_capacity(hexes.len() * 3);
+  for h in hexes {
+    let h = h.as_ref();
+    let b = h.trim().as_bytes();
+    let offset = if b.first() == Some(&b'#') { 1 } else { 0 };
+    if matches!(b.len() - offset, 4 | 8) && b[offset..].iter().all(u8::is_ascii_hexdigit) {
+      return Err(Error::invalid(format!("Invalid RGB hex: {}", h)));
+    }
+
+    let [r, g, b, _] = css::parse_color(h)?;
+    rgb.extend_from_slice(&[r, g, b]);
+  }
+  Ok(rgb)
+}
+
+/// Resolves indexed pixels against an RGB palette. Indices outside of the palette become opaque black.
+pub fn indexed_to_rgba(pixels: &[u8], palette: &[u8], transparent_index: Option<u8>) -> Vec<u8> {
+  crate::pixels::indexed_to_rgba(pixels, palette, transparent_index)
+}
+
+/// How [`rgba_to_strings`], [`rgb_to_strings`] and [`indexed_to_strings`] write colors.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TextFormat {
+  /// Include the alpha channel of opaque colors too.
+  pub always_include_alpha: bool,
+  /// Write `#RGB`/`#RGBA` for colors that fit. Ignored for CSS output.
+  pub allow_short: bool,
+  /// Write hex digits in lowercase.
+  pub lowercase: bool,
+  /// Write CSS `rgb()`/`rgba()` functions instead of hex colors, with alpha in the range 0-1.
+  pub css: bool,
+}
+
+impl TextFormat {
+  /// Writes a single color. Alpha is left out for opaque colors unless `always_include_alpha` is set.
+  pub fn format(&self, r: u8, g: u8, b: u8, a: u8) -> String {
+    let a = (self.always_include_alpha || a != 255).then_some(a);
+    if self.css {
+      return match a {
+        Some(a) => format!("rgba({}, {}, {}, {})", r, g, b, css_alpha(a)),
+        None => format!("rgb({}, {}, {})", r, g, b),
+      };
+    }
+
+    let mut s = Vec::new();
+    let short = self.allow_short && can_shorten(r, g, b, a);
+    push_hex(&mut s, r, g, b, a, short);
+    if self.lowercase {
+      s.make_ascii_lowercase();
+    }
+    s.into_iter().map(char::from).collect()
+  }
+}
+
+/// Writes alpha in the range 0-1 with up to three decimals, enough to tell all 256 levels apart.
+fn css_alpha(a: u8) -> String {
+  let s = format!("{:.3}", a as f32 / 255.0);
+  s.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+/// Converts RGBA pixels to one color string per pixel, see [`TextFormat`].
+pub fn rgba_to_strings(rgba: &[u8], format: TextFormat) -> Result<Vec<String>> {
+  if !rgba.len().is_multiple_of(4) {
+    return Err(Error::invalid("RGBA length must be a multiple of 4"));
+  }
+
+  Ok(
+    rgba
+      .chunks_exact(4)
+      .map(|p| format.format(p[0], p[1], p[2], p[3]))
+      .collect(),
+  )
+}
+
+/// Converts RGB pixels to one color string per pixel, see [`TextFormat`].
+pub fn rgb_to_strings(rgb: &[u8], format: TextFormat) -> Result<Vec<String>> {
+  if !rgb.len().is_multiple_of(3) {
+    return Err(Error::invalid("RGB length must be a multiple of 3"));
+  }
+
+  Ok(
+    rgb
+      .chunks_exact(3)
+      .map(|p| format.format(p[0], p[1], p[2], 255))
+      .collect(),
+  )
+}
+
+/// Resolves indexed pixels against an RGB palette as one color string per pixel, see [`TextFormat`].
+/// Indices outside of the palette become opaque black.
+pub fn indexed_to_strings(
+  pixels: &[u8],
+  palette: &[u8],
+  transparent_index: Option<u8>,
+  format: TextFormat,
+) -> Vec<String> {
+  let colors: Vec<String> = palette
+    .chunks_exact(3)
+    .enumerate()
+    .map(|(i, rgb)| {
+      let a = if Some(i) == transparent_index.map(usize::from) {
+        0
+      } else {
+        255
+      };
+      format.format(rgb[0], rgb[1], rgb[2], a)
+    })
+    .collect();
+  let black = format.format(0, 0, 0, 255);
+
+  pixels
+    .iter()
+    .map(|&i| colors.get(i as usize).unwrap_or(&black).clone())
+    .collect()
+}
@@ -1,6 +1,51 @@
-pub mod decoder;
-pub mod encoder;
-pub mod enums;
-pub mod frame;
-pub mod neuquant;
-pub mod util;
+//! GIF tooling built on the `gif` crate: lossy LZW compression, parallel quantization, frame compositing,
+//! PNG/APNG conversion, sprite sheets, resizing and cropping, hex and CSS color parsing, color space conversions and distances,
+//! SIMD pixel format conversions, dominant color extraction, pixel statistics, perceptual comparison and hashing.
+//! Long-running operations take a [`progress::Control`] for cancellation and progress reporting.
+//!
+//! The Node.js bindings live in [`node`] and are enabled by the default `napi` feature.
+//! Disable default features to use the crate as a plain Rust library.
+//! The `wasm` feature provides the same API for browsers through [`wasm`].
+
+pub mod apng;
+pub mod color;
+pub mod compare;
+pub mod compositor;
+pub mod convert;
+pub mod css;
+pub mod encode;
+pub mod error;
+pub mod hash;
+pub mod hex;
+pub mod lzw;
+pub mod palette;
+pub mod pixels;
+pub mod progress;
+pub mod quantize;
+pub mod scan;
+pub mod sprite;
+pub mod stats;
+pub mod transform;
+
+#[cfg(feature = "napi")]
+pub mod node;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+pub use error::{Error, Result};
+pub use gif;
+
+/// Layout of a frame's pixel buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PixelFormat {
+  /// Four bytes per pixel: red, green, blue and alpha.
+  Rgba,
+  /// Three bytes per pixel: red, green and blue.
+  Rgb,
+  /// One palette index per pixel.
+  Indexed,
+  /// Nine bytes per pixel: an ASCII `#RRGGBBAA` color.
+  Hex,
+  /// LZW-compressed indexed pixels, starting with the minimum code size.
+  Lzw,
+}
@@ -0,0 +1,279 @@
+use crate::{Error, Result};
+
+const MAX_CODE_SIZE: u8 = 12;
+const MAX_ENTRIES: usize = 1 << MAX_CODE_SIZE;
+
+/// LZW dictionary, stored as a trie of `(prefix code, symbol) -> code`.
+struct Dictionary {
+  /// Children of every code as `(symbol, code)` pairs.
+  children: Vec<Vec<(u8, u16)>>,
+  root_len: usize,
+}
+
+impl Dictionary {
+  fn new(min_code_size: u8) -> Self {
+    let root_len = (1 << min_code_size) + 2;
+    let mut children = Vec::with_capacity(MAX_ENTRIES + 1);
+    children.resize_with(root_len, Vec::new);
+    Self { children, root_len }
+  }
+
+  fn reset(&mut self) {
+    self.children.truncate(self.root_len);
+    self.children.iter_mut().for_each(|c| c.clear());
+  }
+
+  fn len(&self) -> usize {
+    self.children.len()
+  }
+
+  fn child(&self, code: u16, symbol: u8) -> Option<u16> {
+    self.children[code as usize]
+      .iter()
+      .find(|&&(s, _)| s == symbol)
+      .map(|&(_, c)| c)
+  }
+
+  fn append(&mut self, code: u16, symbol: u8) {
+    let next = self.children.len() as u16;
+    self.children[code as usize].push((symbol, next));
+    self.children.push(Vec::new());
+  }
+}
+
+/// LSB-first bit packer for variable-width codes.
+struct BitWriter<'a> {
+  out: &'a mut Vec<u8>,
+  acc: u32,
+  bits: u8,
+  code_size: u8,
+}
+
+impl BitWriter<'_> {
+  fn write(&mut self, code: u16) {
+    self.acc |= (code as u32) << self.bits;
+    self.bits += self.code_size;
+    while self.bits >= 8 {
+      self.out.push(self.acc as u8);
+      self.acc >>= 8;
+      self.bits -= 8;
+    }
+  }
+
+  fn finish(&mut self) {
+    if self.bits > 0 {
+      self.out.push(self.acc as u8);
+      self.acc = 0;
+      self.bits = 0;
+    }
+  }
+}
+
+fn color_distance(palette: &[u8], a: u8, b: u8) -> Option<u32> {
+  let a = palette.get(a as usize * 3..a as usize * 3 + 3)?;
+  let b = palette.get(b as usize * 3..b as usize * 3 + 3)?;
+  Some(
+    a.iter()
+      .zip(b)
+      .map(|(&x, &y)| (x as i32 - y as i32).pow(2) as u32)
+      .sum(),
+  )
+}
+
+/// Minimum code size needed to encode `pixels`, as chosen by the `gif` crate.
+fn min_code_size(pixels: &[u8]) -> u8 {
+  let max = pixels.iter().copied().max().unwrap_or(0);
+  (max as u32 + 1).max(4).next_power_of_two().trailing_zeros() as u8
+}
+
+/// Encodes indexed pixels with LZW, letting dictionary matches extend over pixels whose palette colour
+/// lies within `tolerance` of the actual pixel colour. A `tolerance` of `0` produces lossless output.
+///
+/// The result starts with the minimum code size byte, as expected by `gif::Encoder::write_lzw_pre_encoded_frame`.
+pub fn encode_lossy(
+  pixels: &[u8],
+  palette: &[u8],
+  transparent: Option<u8>,
+  tolerance: u32,
+) -> Vec<u8> {
+  let min_code_size = min_code_size(pixels);
+  let clear_code = 1u16 << min_code_size;
+  let end_code = clear_code + 1;
+  let max_distance = tolerance * tolerance;
+
+  let mut out = Vec::with_capacity(pixels.len() / 2 + 1);
+  out.push(min_code_size);
+
+  let mut dict = Dictionary::new(min_code_size);
+  let mut writer = BitWriter {
+    out: &mut out,
+    acc: 0,
+    bits: 0,
+    code_size: min_code_size + 1,
+  };
+  writer.write(clear_code);
+
+  let mut pixels = pixels.iter().copied();
+  let Some(first) = pixels.next() else {
+    writer.write(end_code);
+    writer.finish();
+    return out;
+  };
+  let mut current = first as u16;
+
+  for pixel in pixels {
+    if let Some(code) = dict.child(current, pixel) {
+      current = code;
+      continue;
+    }
+
+    if max_distance > 0 && Some(pixel) != transparent {
+      let near = dict.children[current as usize]
+        .iter()
+        .filter(|&&(s, _)| Some(s) != transparent)
+        .filter_map(|&(s, c)| Some((color_distance(palette, s, pixel)?, c)))
+        .filter(|&(d, _)| d <= max_distance)
+        .min_by_key(|&(d, _)| d);
+
+      if let Some((_, code)) = near {
+        current = code;
+        continue;
+      }
+    }
+
+    dict.append(current, pixel);
+    writer.write(current);
+    current = pixel as u16;
+
+    if dict.len() > 1 << writer.code_size && writer.code_size < MAX_CODE_SIZE {
+      writer.code_size += 1;
+    }
+
+    if dict.len() > MAX_ENTRIES {
+      writer.write(clear_code);
+      dict.reset();
+      writer.code_size = min_code_size + 1;
+    }
+  }
+
+  writer.write(current);
+  // The decoder adds one more entry after reading the last code, which may bump its code size.
+  if dict.len() >= 1 << writer.code_size && writer.code_size < MAX_CODE_SIZE {
+    writer.code_size += 1;
+  }
+  writer.write(end_code);
+  writer.finish();
+
+  out
+}
+
+/// Checks that a lossy compression level is in the range 0-200.
+pub fn check_lossy(level: u32) -> Result<()> {
+  if level > 200 {
+    return Err(Error::invalid("Lossy level needs to be in the range 0-200"));
+  }
+  Ok(())
+}
+
+/// Replaces the frame's indexed buffer with LZW data for `gif::Encoder::write_lzw_pre_encoded_frame`.
+/// A `lossy` level of `0` compresses losslessly; otherwise see [`encode_lossy`].
+pub fn compress_frame(frame: &mut gif::Frame, global_palette: Option<&[u8]>, lossy: u32) {
+  if lossy == 0 {
+    frame.make_lzw_pre_encoded();
+    return;
+  }
+
+  let palette = frame.palette.as_deref().or(global_palette).unwrap_or(&[]);
+  frame.buffer = encode_lossy(&frame.buffer, palette, frame.transparent, lossy).into();
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Pseudo-random indices below `colors`.
+  fn noise(len: usize, colors: u32) -> Vec<u8> {
+    let mut state = 0x2545_f491_u32;
+    (0..len)
+      .map(|_| {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        ((state >> 8) % colors) as u8
+      })
+      .collect()
+  }
+
+  /// Writes `lzw` as the only frame of a GIF and decodes its indexed pixels with the `gif` crate.
+  fn decode(lzw: Vec<u8>, width: u16, height: u16, palette: &[u8]) -> Vec<u8> {
+    let mut gif = Vec::new();
+    {
+      let mut encoder = gif::Encoder::new(&mut gif, width, height, palette).unwrap();
+      let frame = gif::Frame {
+        width,
+        height,
+        buffer: lzw.into(),
+        ..gif::Frame::default()
+      };
+      encoder.write_lzw_pre_encoded_frame(&frame).unwrap();
+    }
+
+    let mut options = gif::DecodeOptions::new();
+    options.set_color_output(gif::ColorOutput::Indexed);
+    let mut decoder = options.read_info(gif.as_slice()).unwrap();
+    decoder.read_next_frame().unwrap().unwrap().buffer.to_vec()
+  }
+
+  #[test]
+  fn lossless_round_trip() {
+    // 256 by 256 pixels fill the dictionary, so every case also writes clear codes
+    let (width, height) = (256, 256);
+    for colors in [2, 8, 16, 256] {
+      let pixels = noise(width as usize * height as usize, colors);
+      let palette: Vec<u8> = (0..colors * 3).map(|i| i as u8).collect();
+
+      let lzw = encode_lossy(&pixels, &palette, None, 0);
+      assert_eq!(lzw[0], min_code_size(&pixels));
+      assert_eq!(decode(lzw, width, height, &palette), pixels);
+    }
+  }
+
+  #[test]
+  fn lossless_round_trip_of_short_buffers() {
+    let palette = [0, 0, 0, 255, 255, 255];
+    for pixels in [vec![1], vec![0, 1, 1, 0], vec![1; 100]] {
+      let lzw = encode_lossy(&pixels, &palette, Some(0), 0);
+      assert_eq!(decode(lzw, pixels.len() as u16, 1, &palette), pixels);
+    }
+  }
+
+  #[test]
+  fn lossy_stays_within_tolerance() {
+    // A noisy gradient over a gray ramp, so that neighbouring indices have similar colours
+    let (width, height) = (256, 64);
+    let palette: Vec<u8> = (0..=255).flat_map(|v| [v, v, v]).collect();
+    let pixels: Vec<u8> = noise(width as usize * height as usize, 32)
+      .iter()
+      .enumerate()
+      .map(|(i, &n)| ((i % width as usize) as u8 / 2).saturating_add(n))
+      .collect();
+
+    let tolerance = 80;
+    let lossless = encode_lossy(&pixels, &palette, None, 0);
+    let lossy = encode_lossy(&pixels, &palette, None, tolerance);
+    assert!(lossy.len() < lossless.len());
+
+    let decoded = decode(lossy, width, height, &palette);
+    assert_eq!(decoded.len(), pixels.len());
+    assert_ne!(decoded, pixels);
+    for (&a, &b) in decoded.iter().zip(&pixels) {
+      assert!(color_distance(&palette, a, b).unwrap() <= tolerance * tolerance);
+    }
+  }
+
+  #[test]
+  fn check_lossy_rejects_levels_above_200() {
+    assert!(check_lossy(200).is_ok());
+    assert!(check_lossy(201).is_err());
+  }
+}
@@ -0,0 +1,969 @@
+use super::enums::{ColorOutput, FrameBufType};
+use super::frame::Frame;
+use super::hash::Fingerprint;
+use super::progress::{Hooks, TaskOptions};
+use super::sprite::{self, SpriteSheet, SpriteSheetOptions};
+use super::util::{self, ImageStats, PaletteColor, PaletteOptions};
+use crate::compositor::Compositor;
+use crate::progress::{Control, Progress};
+use crate::{apng, convert, hash, scan, stats};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::io::Read;
+use std::num::NonZero;
+use std::ptr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+#[derive(Clone)]
+struct CustomOptions {
+  pub(crate) frame_buf_type: FrameBufType,
+  pub(crate) skip_frame_decoding: bool,
+}
+
+/// The bytes of a GIF passed as a whole.
+enum Data {
+  /// Borrowed from JavaScript, only read on the main thread.
+  Borrowed(Uint8Array),
+  /// Copied before the first task off the main thread, where JavaScript could write to the buffer while it is read.
+  Owned(Vec<u8>),
+}
+
+impl Data {
+  fn bytes(&self) -> &[u8] {
+    match self {
+      Data::Borrowed(buffer) => buffer,
+      Data::Owned(data) => data,
+    }
+  }
+}
+
+/// Where the decoder reads the GIF from.
+enum Source {
+  /// The whole GIF and how far it has been read.
+  Buffer {
+    data: Arc<Mutex<Data>>,
+    position: usize,
+  },
+  /// Bytes that arrived so far and have not been read, see `DecoderFeed`.
+  Feed(Arc<Mutex<VecDeque<u8>>>),
+}
+
+/// The GIF input, counting the bytes consumed by the decoder.
+struct Input {
+  source: Source,
+  consumed: Arc<AtomicUsize>,
+}
+
+impl Read for Input {
+  fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+    let n = match &mut self.source {
+      Source::Buffer { data, position } => {
+        let n = lock(data)
+          .bytes()
+          .get(*position..)
+          .unwrap_or(&[])
+          .read(buf)?;
+        *position += n;
+        n
+      }
+      Source::Feed(queue) => lock(queue).read(buf)?,
+    };
+    self.consumed.fetch_add(n, Ordering::Relaxed);
+    Ok(n)
+  }
+}
+
+/// Decoding state, shared with frame iterators and off-thread reads.
+struct State {
+  w: gif::Decoder<Input>,
+  custom_options: CustomOptions,
+  compositor: Option<Compositor>,
+  consumed: Arc<AtomicUsize>,
+  /// The size of the GIF, unknown while it is still arriving.
+  size: Option<usize>,
+}
+
+impl State {
+  fn new(
+    source: Source,
+    options: gif::DecodeOptions,
+    custom_options: CustomOptions,
+  ) -> napi::Result<Arc<Mutex<State>>> {
+    let size = match &source {
+      Source::Buffer { data, .. } => Some(lock(data).bytes().len()),
+      Source::Feed(_) => None,
+    };
+    let consumed = Arc::new(AtomicUsize::new(0));
+    let w = options
+      .read_info(Input {
+        source,
+        consumed: consumed.clone(),
+      })
+      .map_err(|e| {
+        Error::new(
+          Status::GenericFailure,
+          format!("Failed to create a GIF decoder: {}", e),
+        )
+      })?;
+
+    Ok(Arc::new(Mutex::new(State {
+      w,
+      custom_options,
+      compositor: None,
+      consumed,
+      size,
+    })))
+  }
+
+  fn progress(&self, frames: usize) -> Progress {
+    Progress {
+      frames,
+      total_frames: None,
+      bytes: Some(self.consumed.load(Ordering::Relaxed)),
+      total_bytes: self.size,
+    }
+  }
+
+  fn read_next_frame(&mut self) -> napi::Result<Option<Frame<'static>>> {
+    let buf_type = if self.custom_options.skip_frame_decoding {
+      FrameBufType::Lzw
+    } else {
+      self.custom_options.frame_buf_type.clone()
+    };
+
+    let Some(f) = self.w.read_next_frame().map_err(|e| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Failed to get next frame info: {}", e),
+      )
+    })?
+    else {
+      return Ok(None);
+    };
+
+    let mut f = f.to_owned();
+    // The gif crate only decodes to RGBA or indexed pixels, other representations are converted from RGBA.
+    if matches!(buf_type, FrameBufType::Rgb | FrameBufType::Hex) {
+      let rgba = std::mem::take(&mut f.buffer).into_owned();
+      f.buffer = Cow::Owned(convert::rgba_to(rgba, (&buf_type).into())?);
+    }
+    let mut frame = Frame::from_gif_frame(f, buf_type);
+    if frame.buf_type == FrameBufType::Lzw && frame.w.palette.is_none() {
+      // Keep the palette the frame decodes against, for `<Encoder>.addRawFrame`.
+      frame.source_palette = self.w.global_palette().map(|p| p.to_vec());
+    }
+    Ok(Some(frame))
+  }
+
+  /// Reads the next frame along with its pixels as RGBA.
+  fn read_next_rgba_frame(&mut self) -> napi::Result<Option<(gif::Frame<'static>, Vec<u8>)>> {
+    let Some(frame) = self.read_next_frame()? else {
+      return Ok(None);
+    };
+    let rgba = apng::frame_to_rgba(&frame.w, (&frame.buf_type).into(), self.w.global_palette())?;
+    Ok(Some((frame.w, rgba)))
+  }
+
+  fn read_next_composited_frame(&mut self) -> napi::Result<Option<Frame<'static>>> {
+    let Some((frame, rgba)) = self.read_next_rgba_frame()? else {
+      return Ok(None);
+    };
+
+    let (width, height) = (self.w.width(), self.w.height());
+    let canvas = self
+      .compositor
+      .get_or_insert_with(|| Compositor::new(width, height))
+      .draw(&frame, &rgba);
+
+    Ok(Some(Frame::from_gif_frame(
+      gif::Frame {
+        width,
+        height,
+        delay: frame.delay,
+        dispose: gif::DisposalMethod::Background,
+        buffer: Cow::Owned(canvas.to_vec()),
+        ..gif::Frame::default()
+      },
+      FrameBufType::Rgba,
+    )))
+  }
+
+  fn next(&mut self, composited: bool) -> napi::Result<Option<Frame<'static>>> {
+    if composited {
+      self.read_next_composited_frame()
+    } else {
+      self.read_next_frame()
+    }
+  }
+
+  fn encode_apng(&mut self, control: Control<'_>) -> napi::Result<Vec<u8>> {
+    let mut frames = Vec::new();
+    loop {
+      control.check()?;
+      let Some(frame) = self.read_next_rgba_frame()? else {
+        break;
+      };
+      frames.push(frame);
+      control.report(self.progress(frames.len()));
+    }
+
+    Ok(apng::encode_apng(
+      self.w.width(),
+      self.w.height(),
+      self.w.repeat(),
+      &frames,
+    )?)
+  }
+
+  fn extract_palette(
+    &mut self,
+    count: u32,
+    options: Option<PaletteOptions>,
+    control: Control<'_>,
+  ) -> napi::Result<Vec<PaletteColor>> {
+    let mut builder = util::palette_builder(count, options)?;
+    let mut frames = 0;
+    loop {
+      control.check()?;
+      let Some((_, rgba)) = self.read_next_rgba_frame()? else {
+        break;
+      };
+      builder.add_rgba(&rgba)?;
+      frames += 1;
+      control.report(self.progress(frames));
+    }
+    Ok(util::palette_colors(builder))
+  }
+
+  fn stats(&mut self, control: Control<'_>) -> napi::Result<ImageStats> {
+    let mut builder = stats::StatsBuilder::new();
+    let mut frames = 0;
+    loop {
+      control.check()?;
+      let Some(frame) = self.read_next_frame()? else {
+        break;
+      };
+      stats::add_frame(
+        &mut builder,
+        &frame.w,
+        (&frame.buf_type).into(),
+        self.w.global_palette(),
+      )?;
+      frames += 1;
+      control.report(self.progress(frames));
+    }
+    Ok(builder.finish().into())
+  }
+
+  fn fingerprint(&mut self, control: Control<'_>) -> napi::Result<Fingerprint> {
+    let mut fingerprint = hash::Fingerprint::new();
+    let mut frames = 0;
+    loop {
+      control.check()?;
+      let Some(frame) = self.read_next_composited_frame()? else {
+        break;
+      };
+      let w = &frame.w;
+      fingerprint.push(hash::FrameHash::new(&w.buffer, w.width, w.height, w.delay)?);
+      frames += 1;
+      control.report(self.progress(frames));
+    }
+    Ok(fingerprint.into())
+  }
+
+  fn build_sprite_sheet(
+    &mut self,
+    options: Option<SpriteSheetOptions>,
+    control: Control<'_>,
+  ) -> napi::Result<SpriteSheet> {
+    let mut frames = Vec::new();
+    loop {
+      control.check()?;
+      let Some(frame) = self.read_next_composited_frame()? else {
+        break;
+      };
+      frames.push((frame.w.delay, frame.w.buffer.into_owned()));
+      control.report(self.progress(frames.len()));
+    }
+
+    sprite::build_sheet(self.w.width(), self.w.height(), &frames, options)
+  }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+  mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// An iterator item that throws its error in JavaScript instead of yielding it.
+pub struct NextFrame(napi::Result<Frame<'static>>);
+
+impl ToNapiValue for NextFrame {
+  unsafe fn to_napi_value(env: sys::napi_env, val: Self) -> napi::Result<sys::napi_value> {
+    match val.0 {
+      Ok(frame) => Frame::to_napi_value(env, frame),
+      Err(e) => {
+        JsError::from(e).throw_into(env);
+        Ok(ptr::null_mut())
+      }
+    }
+  }
+}
+
+/// The GIF Decoder.
+/// Iterating over the decoder with `for...of` reads the remaining frames, like `<Decoder>.readNextFrame`.
+/// `for await...of` reads them off the main thread, see `<Decoder>.readNextFrameAsync`.
+#[napi(iterator)]
+pub struct Decoder {
+  state: Arc<Mutex<State>>,
+  /// The input passed as a whole, `None` for decoders of a `DecoderFeed`.
+  input: Option<Arc<Mutex<Data>>>,
+}
+
+impl Generator for Decoder {
+  type Yield = NextFrame;
+  type Next = ();
+  type Return = ();
+
+  fn next(&mut self, _value: Option<()>) -> Option<NextFrame> {
+    lock(&self.state)
+      .read_next_frame()
+      .transpose()
+      .map(NextFrame)
+  }
+}
+
+impl Decoder {
+  fn from_buffer(
+    buffer: Uint8Array,
+    options: gif::DecodeOptions,
+    custom_options: CustomOptions,
+  ) -> napi::Result<Decoder> {
+    let data = Arc::new(Mutex::new(Data::Borrowed(buffer)));
+    let source = Source::Buffer {
+      data: data.clone(),
+      position: 0,
+    };
+    Ok(Decoder {
+      state: State::new(source, options, custom_options)?,
+      input: Some(data),
+    })
+  }
+
+  /// The state to hand to a task off the main thread, copying a borrowed input first.
+  fn task_state(&self) -> Arc<Mutex<State>> {
+    if let Some(input) = &self.input {
+      let mut data = lock(input);
+      if let Data::Borrowed(buffer) = &*data {
+        *data = Data::Owned(buffer.to_vec());
+      }
+    }
+    self.state.clone()
+  }
+}
+
+#[napi]
+impl Decoder {
+  /// Create a new decoder.
+  /// The buffer is borrowed rather than copied, so it should not be modified while the decoder is in use.
+  /// It is copied once the decoder is first used off the main thread, e.g. by `<Decoder>.readNextFrameAsync`.
+  /// @param buffer - The GIF buffer to decode.
+  /// @param options - The options to use for decoding.
+  #[napi(constructor)]
+  pub fn new(buffer: Uint8Array, options: Option<&DecodeOptions>) -> napi::Result<Decoder> {
+    if let Some(options) = options {
+      return options.read_info(buffer);
+    }
+
+    Decoder::from_buffer(
+      buffer,
+      gif::DecodeOptions::new(),
+      CustomOptions {
+        frame_buf_type: FrameBufType::IndexedPixels,
+        skip_frame_decoding: false,
+      },
+    )
+  }
+
+  /// Returns the next frame info. (skips the buffer)
+  #[napi]
+  pub fn next_frame_info<'a>(&mut self) -> napi::Result<Option<Frame<'a>>> {
+    let mut state = lock(&self.state);
+    let buf_type = state.custom_options.frame_buf_type.clone();
+    let f = state.w.next_frame_info().map_err(|e| {
+      Error::new(
+        Status::GenericFailure,
+        format!("Failed to get next frame info: {}", e),
+      )
+    })?;
+    Ok(f.map(|f| Frame::from_gif_frame(f.to_owned(), buf_type)))
+  }
+
+  /// Reads the next frame from the GIF.
+  /// Do not call `<Decoder>.nextFrameInfo` beforehand. Deinterlaces the result.
+  /// If frame decoding is skipped, the frame's buffer holds the compressed LZW data instead.
+  #[napi]
+  pub fn read_next_frame(&mut self) -> napi::Result<Option<Frame<'static>>> {
+    lock(&self.state).read_next_frame()
+  }
+
+  /// Reads the next frame off the main thread.
+  /// @param composited - Whether to return the composited canvas, like `<Decoder>.readNextCompositedFrame`.
+  #[napi(ts_return_type = "Promise<Frame | null>")]
+  pub fn read_next_frame_async(&self, composited: Option<bool>) -> AsyncTask<ReadFrameTask> {
+    AsyncTask::new(ReadFrameTask {
+      state: self.task_state(),
+      composited: composited.unwrap_or(false),
+    })
+  }
+
+  /// Returns an iterator over the remaining frames, for use with `for...of`.
+  /// @param composited - Whether to yield the composited canvas, like `<Decoder>.readNextCompositedFrame`.
+  #[napi]
+  pub fn frames(&self, composited: Option<bool>) -> DecoderFrames {
+    DecoderFrames {
+      state: self.state.clone(),
+      composited: composited.unwrap_or(false),
+    }
+  }
+
+  /// Reads the remaining frames and encodes them as an APNG.
+  /// Frame offsets, delays and disposal methods are carried over, and the loop count maps to the number of plays.
+  #[napi]
+  pub fn to_apng(&mut self) -> napi::Result<Buffer> {
+    Ok(Buffer::from(
+      lock(&self.state).encode_apng(Control::default())?,
+    ))
+  }
+
+  /// Like `<Decoder>.toApng`, but reads and encodes the frames off the main thread.
+  /// @param options - An `AbortSignal` to cancel the task and a progress callback, called after each frame is read.
+  #[napi(ts_return_type = "Promise<Buffer>")]
+  pub fn to_apng_async(
+    &self,
+    env: Env,
+    options: Option<TaskOptions>,
+  ) -> napi::Result<AsyncTask<ApngTask>> {
+    Ok(AsyncTask::new(ApngTask {
+      state: self.task_state(),
+      hooks: Hooks::new(&env, options)?,
+    }))
+  }
+
+  /// Reads the remaining frames and finds up to `count` colors that best represent all of them together, most common first.
+  /// See `extractPalette`.
+  #[napi]
+  pub fn extract_palette(
+    &mut self,
+    count: u32,
+    options: Option<PaletteOptions>,
+  ) -> napi::Result<Vec<PaletteColor>> {
+    lock(&self.state).extract_palette(count, options, Control::default())
+  }
+
+  /// Like `<Decoder>.extractPalette`, but reads the frames and counts their colors off the main thread.
+  /// @param taskOptions - An `AbortSignal` to cancel the task and a progress callback, called after each frame is read.
+  #[napi(ts_return_type = "Promise<Array<PaletteColor>>")]
+  pub fn extract_palette_async(
+    &self,
+    env: Env,
+    count: u32,
+    options: Option<PaletteOptions>,
+    task_options: Option<TaskOptions>,
+  ) -> napi::Result<AsyncTask<ExtractPaletteTask>> {
+    Ok(AsyncTask::new(ExtractPaletteTask {
+      state: self.task_state(),
+      count,
+      options,
+      hooks: Hooks::new(&env, task_options)?,
+    }))
+  }
+
+  /// Reads the remaining frames and computes their pixel statistics together, see `<Frame>.stats`.
+  /// Bounds cover the visible pixels of all frames, and palette usage is counted for indexed frames.
+  #[napi]
+  pub fn stats(&mut self) -> napi::Result<ImageStats> {
+    lock(&self.state).stats(Control::default())
+  }
+
+  /// Like `<Decoder>.stats`, but reads the frames and counts their pixels off the main thread.
+  /// @param options - An `AbortSignal` to cancel the task and a progress callback, called after each frame is read.
+  #[napi(ts_return_type = "Promise<ImageStats>")]
+  pub fn stats_async(
+    &self,
+    env: Env,
+    options: Option<TaskOptions>,
+  ) -> napi::Result<AsyncTask<StatsTask>> {
+    Ok(AsyncTask::new(StatsTask {
+      state: self.task_state(),
+      hooks: Hooks::new(&env, options)?,
+    }))
+  }
+
+  /// Reads the remaining frames, composites them and hashes each one along with its delay, see `dhash` and `phash`.
+  /// Compare fingerprints with `fingerprintSimilarity` to find copies of a GIF that were re-encoded or resized.
+  #[napi]
+  pub fn fingerprint(&mut self) -> napi::Result<Fingerprint> {
+    lock(&self.state).fingerprint(Control::default())
+  }
+
+  /// Like `<Decoder>.fingerprint`, but reads, composites and hashes the frames off the main thread.
+  /// @param options - An `AbortSignal` to cancel the task and a progress callback, called after each frame is read.
+  #[napi(ts_return_type = "Promise<Fingerprint>")]
+  pub fn fingerprint_async(
+    &self,
+    env: Env,
+    options: Option<TaskOptions>,
+  ) -> napi::Result<AsyncTask<FingerprintTask>> {
+    Ok(AsyncTask::new(FingerprintTask {
+      state: self.task_state(),
+      hooks: Hooks::new(&env, options)?,
+    }))
+  }
+
+  /// Reads the next frame and draws it onto the canvas left by the previous frames, applying their disposal methods.
+  /// Returns the whole canvas as an RGBA frame of the GIF's size, keeping the frame's delay.
+  /// Only frames read with this method are drawn onto the canvas.
+  #[napi]
+  pub fn read_next_composited_frame(&mut self) -> napi::Result<Option<Frame<'static>>> {
+    lock(&self.state).read_next_composited_frame()
+  }
+
+  /// Reads the remaining frames, composites them and lays them out as a sprite sheet.
+  /// @param options - The layout, background and output format of the sheet.
+  #[napi]
+  pub fn to_sprite_sheet(
+    &mut self,
+    options: Option<SpriteSheetOptions>,
+  ) -> napi::Result<SpriteSheet> {
+    lock(&self.state).build_sprite_sheet(options, Control::default())
+  }
+
+  /// Like `<Decoder>.toSpriteSheet`, but composites the frames and builds the sheet off the main thread.
+  /// @param options - The layout, background and output format of the sheet.
+  /// @param taskOptions - An `AbortSignal` to cancel the task and a progress callback, called after each frame is read.
+  #[napi(ts_return_type = "Promise<SpriteSheet>")]
+  pub fn to_sprite_sheet_async(
+    &self,
+    env: Env,
+    options: Option<SpriteSheetOptions>,
+    task_options: Option<TaskOptions>,
+  ) -> napi::Result<AsyncTask<SpriteSheetTask>> {
+    Ok(AsyncTask::new(SpriteSheetTask {
+      state: self.task_state(),
+      options,
+      hooks: Hooks::new(&env, task_options)?,
+    }))
+  }
+
+  /// Output buffer size.
+  #[napi(getter)]
+  pub fn buffer_size(&self) -> u32 {
+    lock(&self.state).w.buffer_size() as u32
+  }
+
+  /// Line length of the current frame.
+  #[napi(getter)]
+  pub fn line_length(&self) -> u32 {
+    lock(&self.state).w.line_length() as u32
+  }
+
+  /// The color palette relevant for the frame that has been decoded.
+  #[napi(getter)]
+  pub fn palette(&self) -> napi::Result<Buffer> {
+    Ok(Buffer::from(lock(&self.state).w.palette().map_err(
+      |e| {
+        Error::new(
+          Status::GenericFailure,
+          format!("Failed to get the palette: {}", e),
+        )
+      },
+    )?))
+  }
+
+  /// The global color palette.
+  #[napi(getter)]
+  pub fn global_palette(&self) -> Option<Buffer> {
+    lock(&self.state)
+      .w
+      .global_palette()
+      .map(|p| Buffer::from(p))
+  }
+
+  /// Width of the GIF.
+  #[napi(getter)]
+  pub fn width(&self) -> u16 {
+    lock(&self.state).w.width()
+  }
+
+  /// Height of the GIF.
+  #[napi(getter)]
+  pub fn height(&self) -> u16 {
+    lock(&self.state).w.height()
+  }
+
+  /// Index of the background color in the global palette
+  /// In practice this is not used, and the background is always transparent
+  #[napi(getter)]
+  pub fn bg_color(&self) -> Option<u16> {
+    lock(&self.state).w.bg_color().map(|c| c as u16)
+  }
+
+  /// Number of loop repetitions.
+  #[napi(getter)]
+  pub fn loops(&self) -> i16 {
+    match lock(&self.state).w.repeat() {
+      gif::Repeat::Finite(v) => v as i16,
+      gif::Repeat::Infinite => -1,
+    }
+  }
+}
+
+/// Options for opening a GIF decoder. `<DecodeOptions>.readInfo` will create a decoder with these options.
+#[napi]
+pub struct DecodeOptions {
+  w: gif::DecodeOptions,
+  custom_options: CustomOptions,
+}
+
+#[napi]
+impl DecodeOptions {
+  /// Create new decode options.
+  #[napi(constructor)]
+  pub fn new() -> DecodeOptions {
+    Self {
+      w: gif::DecodeOptions::new(),
+      custom_options: CustomOptions {
+        frame_buf_type: FrameBufType::IndexedPixels,
+        skip_frame_decoding: false,
+      },
+    }
+  }
+
+  /// Configure how color data is decoded.
+  #[napi]
+  pub fn set_color_output(&mut self, value: ColorOutput) {
+    self.custom_options.frame_buf_type = match value {
+      ColorOutput::Rgba => FrameBufType::Rgba,
+      ColorOutput::IndexedPixels => FrameBufType::IndexedPixels,
+      ColorOutput::Rgb => FrameBufType::Rgb,
+      ColorOutput::Hex => FrameBufType::Hex,
+    };
+
+    self.w.set_color_output(match value {
+      ColorOutput::IndexedPixels => gif::ColorOutput::Indexed,
+      ColorOutput::Rgba | ColorOutput::Rgb | ColorOutput::Hex => gif::ColorOutput::RGBA,
+    });
+  }
+
+  /// Configure a memory limit for decoding.
+  /// @param value - The memory limit in bytes. Negative values are treated as unlimited. (e.g. -1)
+  /// If the provided value is `-1`, the memory limit is set to unlimited. If a positive integer is provided,
+  /// the memory limit will be set in bytes. A non-zero integer is required for this case, and any non-integer or
+  /// invalid value will return an error.
+  #[napi]
+  pub fn set_memory_limit(&mut self, value: i64) -> napi::Result<()> {
+    if value <= -1 {
+      self.w.set_memory_limit(gif::MemoryLimit::Unlimited);
+    } else {
+      self.w.set_memory_limit(gif::MemoryLimit::Bytes(
+        NonZero::new(value as u64).ok_or_else(|| {
+          Error::new(
+            Status::InvalidArg,
+            "Limit must be a positive non-zero integer".to_string(),
+          )
+        })?,
+      ));
+    }
+    Ok(())
+  }
+
+  /// Configure if frames must be within the screen descriptor.
+  /// @param value - Whether to check frame consistency.
+  /// The default is `false`.
+  /// When turned on, all frame descriptors being read must fit within the screen descriptor or otherwise an error is returned and the stream left in an unspecified state.
+  /// When turned off, frames may be arbitrarily larger or offset in relation to the screen. Many other decoder libraries handle this in highly divergent ways. This moves all checks to the caller, for example to emulate a specific style.
+  #[napi]
+  pub fn check_frame_consistency(&mut self, value: bool) {
+    self.w.check_frame_consistency(value);
+  }
+
+  /// Configure whether to skip decoding frames.
+  /// @param value - Whether to skip frame decoding.
+  /// The default is `false`.
+  /// When turned on, LZW decoding is skipped. `<Decoder>.readNextFrame` will return compressed LZW bytes in frame’s data. `<Decoder>.nextFrameInfo` will return the metadata of the next frame as usual. This is useful to count frames without incurring the overhead of decoding.
+  /// Frames read this way can be written back as-is with `<Encoder>.addRawFrame`.
+  #[napi]
+  pub fn skip_frame_decoding(&mut self, value: bool) {
+    self.custom_options.skip_frame_decoding = value;
+    self.w.skip_frame_decoding(value);
+  }
+
+  /// Configure if LZW encoded blocks must end with a marker end code.
+  /// @param value - Whether to check for the end code.
+  /// The default is `false`.
+  /// When turned on, all image data blocks—which are LZW encoded—must contain a special bit sequence signalling the end of the data. LZW processing terminates when this code is encountered. The specification states that it must be the last code output by the encoder for an image.
+  /// When turned off then image data blocks can simply end. Note that this might silently ignore some bits of the last or second to last byte.
+  #[napi]
+  pub fn check_lzw_end_code(&mut self, value: bool) {
+    self.w.check_lzw_end_code(value);
+  }
+
+  /// Configure if unknown blocks are allowed to be decoded.
+  /// @param value - Whether to allow unknown blocks.
+  /// The default is `false`.
+  /// When turned on, the decoder will allow unknown blocks to be in the `BlockStart` position.
+  /// When turned off, decoded block starts must mark an `Image`, `Extension`, or `Trailer` block. Otherwise, the decoded image will return an error. If an unknown block error is returned from decoding, enabling this setting may allow for a further state of decoding on the next attempt.
+  #[napi]
+  pub fn allow_unknown_blocks(&mut self, value: bool) {
+    self.w.allow_unknown_blocks(value);
+  }
+
+  /// Reads the logical screen descriptor including the global color palette
+  /// Returns a Decoder. All decoder configuration has to be done beforehand.
+  /// The buffer is borrowed rather than copied, so it should not be modified while the decoder is in use.
+  /// It is copied once the decoder is first used off the main thread, e.g. by `<Decoder>.readNextFrameAsync`.
+  /// @param buffer - The GIF buffer to decode.
+  #[napi]
+  pub fn read_info(&self, buffer: Uint8Array) -> napi::Result<Decoder> {
+    Decoder::from_buffer(buffer, self.w.clone(), self.custom_options.clone())
+  }
+}
+
+/// A GIF that arrives in chunks, read by a decoder as soon as its header is complete. Used by `createDecodeStream`.
+/// Only the bytes the decoder has not read yet are kept.
+#[napi]
+pub struct DecoderFeed {
+  queue: Arc<Mutex<VecDeque<u8>>>,
+  scanner: scan::Scanner,
+  options: gif::DecodeOptions,
+  custom_options: CustomOptions,
+  decoder: Option<Arc<Mutex<State>>>,
+  ended: bool,
+}
+
+#[napi]
+impl DecoderFeed {
+  /// Create a new feed.
+  /// @param options - The options to use for decoding.
+  #[napi(constructor)]
+  pub fn new(options: Option<&DecodeOptions>) -> DecoderFeed {
+    let options = options.map_or_else(DecodeOptions::new, |o| DecodeOptions {
+      w: o.w.clone(),
+      custom_options: o.custom_options.clone(),
+    });
+    Self {
+      queue: Arc::new(Mutex::new(VecDeque::new())),
+      scanner: scan::Scanner::new(),
+      options: options.w,
+      custom_options: options.custom_options,
+      decoder: None,
+      ended: false,
+    }
+  }
+
+  /// Appends the next chunk of the GIF.
+  #[napi]
+  pub fn push(&mut self, chunk: &[u8]) {
+    self.scanner.update(chunk);
+    lock(&self.queue).extend(chunk);
+  }
+
+  /// Marks the GIF as complete, so that the decoder can be created and read to the end even if it is truncated.
+  #[napi]
+  pub fn end(&mut self) {
+    self.ended = true;
+  }
+
+  /// The number of frames that have completely arrived. Reading more frames before `<DecoderFeed>.end` fails.
+  #[napi(getter)]
+  pub fn frames(&self) -> u32 {
+    self.scanner.frames() as u32
+  }
+
+  /// Returns the decoder once the header has arrived, always the same one. Returns `null` before.
+  #[napi]
+  pub fn decoder(&mut self) -> napi::Result<Option<Decoder>> {
+    if self.decoder.is_none() && (self.scanner.header_complete() || self.ended) {
+      self.decoder = Some(State::new(
+        Source::Feed(self.queue.clone()),
+        self.options.clone(),
+        self.custom_options.clone(),
+      )?);
+    }
+    Ok(
+      self
+        .decoder
+        .clone()
+        .map(|state| Decoder { state, input: None }),
+    )
+  }
+}
+
+/// Iterator over the remaining frames of a decoder. See `<Decoder>.frames`.
+#[napi(iterator)]
+pub struct DecoderFrames {
+  state: Arc<Mutex<State>>,
+  composited: bool,
+}
+
+impl Generator for DecoderFrames {
+  type Yield = NextFrame;
+  type Next = ();
+  type Return = ();
+
+  fn next(&mut self, _value: Option<()>) -> Option<NextFrame> {
+    lock(&self.state)
+      .next(self.composited)
+      .transpose()
+      .map(NextFrame)
+  }
+}
+
+/// Reads the next frame of a decoder on the libuv thread pool.
+pub struct ReadFrameTask {
+  state: Arc<Mutex<State>>,
+  composited: bool,
+}
+
+impl Task for ReadFrameTask {
+  type Output = Option<Frame<'static>>;
+  type JsValue = Option<Frame<'static>>;
+
+  fn compute(&mut self) -> napi::Result<Self::Output> {
+    lock(&self.state).next(self.composited)
+  }
+
+  fn resolve(&mut self, _env: Env, output: Self::Output) -> napi::Result<Self::JsValue> {
+    Ok(output)
+  }
+}
+
+/// Reads the remaining frames of a decoder and encodes them as an APNG on the libuv thread pool.
+pub struct ApngTask {
+  state: Arc<Mutex<State>>,
+  hooks: Hooks,
+}
+
+impl Task for ApngTask {
+  type Output = Vec<u8>;
+  type JsValue = Buffer;
+
+  fn compute(&mut self) -> napi::Result<Self::Output> {
+    let mut state = lock(&self.state);
+    self.hooks.run(|control| state.encode_apng(control))
+  }
+
+  fn resolve(&mut self, _env: Env, output: Self::Output) -> napi::Result<Self::JsValue> {
+    Ok(Buffer::from(output))
+  }
+
+  fn finally(&mut self, env: Env) -> napi::Result<()> {
+    self.hooks.release(env)
+  }
+}
+
+/// Reads the remaining frames of a decoder into a sprite sheet on the libuv thread pool.
+pub struct SpriteSheetTask {
+  state: Arc<Mutex<State>>,
+  options: Option<SpriteSheetOptions>,
+  hooks: Hooks,
+}
+
+impl Task for SpriteSheetTask {
+  type Output = SpriteSheet;
+  type JsValue = SpriteSheet;
+
+  fn compute(&mut self) -> napi::Result<Self::Output> {
+    let mut state = lock(&self.state);
+    self
+      .hooks
+      .run(|control| state.build_sprite_sheet(self.options.take(), control))
+  }
+
+  fn resolve(&mut self, _env: Env, output: Self::Output) -> napi::Result<Self::JsValue> {
+    Ok(output)
+  }
+
+  fn finally(&mut self, env: Env) -> napi::Result<()> {
+    self.hooks.release(env)
+  }
+}
+
+/// Reads the remaining frames of a decoder and extracts their palette on the libuv thread pool.
+pub struct ExtractPaletteTask {
+  state: Arc<Mutex<State>>,
+  count: u32,
+  options: Option<PaletteOptions>,
+  hooks: Hooks,
+}
+
+impl Task for ExtractPaletteTask {
+  type Output = Vec<PaletteColor>;
+  type JsValue = Vec<PaletteColor>;
+
+  fn compute(&mut self) -> napi::Result<Self::Output> {
+    let mut state = lock(&self.state);
+    self
+      .hooks
+      .run(|control| state.extract_palette(self.count, self.options.take(), control))
+  }
+
+  fn resolve(&mut self, _env: Env, output: Self::Output) -> napi::Result<Self::JsValue> {
+    Ok(output)
+  }
+
+  fn finally(&mut self, env: Env) -> napi::Result<()> {
+    self.hooks.release(env)
+  }
+}
+
+/// Reads the remaining frames of a decoder and computes their statistics on the libuv thread pool.
+pub struct StatsTask {
+  state: Arc<Mutex<State>>,
+  hooks: Hooks,
+}
+
+impl Task for StatsTask {
+  type Output = ImageStats;
+  type JsValue = ImageStats;
+
+  fn compute(&mut self) -> napi::Result<Self::Output> {
+    let mut state = lock(&self.state);
+    self.hooks.run(|control| state.stats(control))
+  }
+
+  fn resolve(&mut self, _env: Env, output: Self::Output) -> napi::Result<Self::JsValue> {
+    Ok(output)
+  }
+
+  fn finally(&mut self, env: Env) -> napi::Result<()> {
+    self.hooks.release(env)
+  }
+}
+
+/// Reads the remaining frames of a decoder and fingerprints them on the libuv thread pool.
+pub struct FingerprintTask {
+  state: Arc<Mutex<State>>,
+  hooks: Hooks,
+}
+
+impl Task for FingerprintTask {
+  type Output = Fingerprint;
+  type JsValue = Fingerprint;
+
+  fn compute(&mut self) -> napi::Result<Self::Output> {
+    let mut state = lock(&self.state);
+    self.hooks.run(|control| state.fingerprint(control))
+  }
+
+  fn resolve(&mut self, _env: Env, output: Self::Output) -> napi::Result<Self::JsValue> {
+    Ok(output)
+  }
+
+  fn finally(&mut self, env: Env) -> napi::Result<()> {
+    self.hooks.release(env)
+  }
+}
@@ -0,0 +1,339 @@
+use super::enums::{FrameBufType, PaletteOrder};
+use super::frame::Frame;
+use super::progress::{Hooks, TaskOptions};
+use crate::progress::Control;
+use crate::{encode, lzw, palette, quantize};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use std::io::Cursor;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+type Writer = gif::Encoder<Cursor<Vec<u8>>>;
+
+fn lock(w: &Mutex<Writer>) -> MutexGuard<'_, Writer> {
+  w.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[napi]
+pub struct Encoder {
+  w: Arc<Mutex<Writer>>,
+  palette: Option<Vec<u8>>,
+  lossy: u32,
+  compact_palettes: Option<palette::PaletteOrder>,
+  /// The gif width.
+  #[napi(readonly)]
+  pub width: u16,
+  /// The gif height.
+  #[napi(readonly)]
+  pub height: u16,
+}
+
+#[napi]
+impl Encoder {
+  /// Create a new encoder.
+  #[napi(constructor)]
+  pub fn new(width: u16, height: u16, palette: Option<&[u8]>) -> napi::Result<Encoder> {
+    Ok(Encoder {
+      width,
+      height,
+      palette: palette.map(|p| p.to_vec()),
+      lossy: 0,
+      compact_palettes: None,
+      w: Arc::new(Mutex::new(
+        gif::Encoder::new(
+          Cursor::new(Vec::new()),
+          width,
+          height,
+          &palette.unwrap_or(&[]),
+        )
+        .map_err(|e| {
+          Error::new(
+            Status::GenericFailure,
+            format!("Failed to create a GIF Encoder: {}", e),
+          )
+        })?,
+      )),
+    })
+  }
+
+  /// Add a frame to the gif.
+  ///
+  /// ### Notes:
+  /// - The frame must fit within the gif's `width` and `height`, taking `left` and `top` into account.
+  /// - Frames holding RGB, RGBA or hex pixels are quantized first, see `<Frame>.convertTo`.
+  #[napi]
+  pub fn add_frame(&mut self, frame: &Frame) -> napi::Result<()> {
+    if frame.buf_type == FrameBufType::Lzw {
+      return Err(Error::new(
+        Status::InvalidArg,
+        "Frame holds LZW data, use `<Encoder>.addRawFrame` instead",
+      ));
+    }
+
+    self.check_bounds(frame)?;
+
+    // RGB, RGBA and hex frames, e.g. from a decoder, are quantized first.
+    Ok(encode::write_frame(
+      &mut lock(&self.w),
+      &frame.gif_frame(),
+      (&frame.buf_type).into(),
+      self.compact_palettes,
+      frame.lossy.unwrap_or(self.lossy),
+      self.palette.as_deref(),
+    )?)
+  }
+
+  /// Add multiple frames from RGBA pixel data to the gif.
+  /// The frames are quantized and compressed in parallel, then written in order.
+  ///
+  /// ### Notes:
+  /// - Speed needs to be in the range 1-30. Higher is faster, lower CPU usage but worse quality.
+  /// - The size of every buffer should match the expected size based on `width`, `height`.
+  /// - `delays`, if provided, needs one entry per frame.
+  /// - `threads` defaults to the number of CPU cores.
+  /// - The buffers are quantized in place, so their pixels change.
+  #[napi]
+  pub fn add_rgba_frames(
+    &mut self,
+    frames: Vec<Uint8Array>,
+    delays: Option<Vec<u16>>,
+    speed: Option<i32>,
+    threads: Option<u32>,
+  ) -> napi::Result<()> {
+    self
+      .rgba_frames(frames, delays, speed, threads)?
+      .write(Control::default())
+  }
+
+  /// Like `<Encoder>.addRgbaFrames`, but quantizes and writes the frames off the main thread.
+  /// Other calls on the encoder wait until the frames are written.
+  /// The frames are copied first, so the buffers are left untouched and can be reused right away.
+  /// @param options - An `AbortSignal` to cancel the task and a progress callback, called as each frame is quantized.
+  #[napi(ts_return_type = "Promise<void>")]
+  pub fn add_rgba_frames_async(
+    &self,
+    env: Env,
+    frames: Vec<Uint8Array>,
+    delays: Option<Vec<u16>>,
+    speed: Option<i32>,
+    threads: Option<u32>,
+    options: Option<TaskOptions>,
+  ) -> napi::Result<AsyncTask<AddRgbaFramesTask>> {
+    Ok(AsyncTask::new(AddRgbaFramesTask {
+      frames: self.rgba_frames(
+        frames.iter().map(|f| f.to_vec()).collect(),
+        delays,
+        speed,
+        threads,
+      )?,
+      hooks: Hooks::new(&env, options)?,
+    }))
+  }
+
+  /// Sets the lossy LZW compression level used for frames without their own `lossy` level.
+  /// Higher values allow pixels to be replaced by more distant palette colours, producing smaller files at the cost of quality.
+  ///
+  /// ### Notes:
+  /// - The level needs to be in the range 0-200. `0` disables lossy compression; 20-80 is a reasonable range.
+  #[napi]
+  pub fn set_lossy(&mut self, value: u32) -> napi::Result<()> {
+    lzw::check_lossy(value)?;
+    self.lossy = value;
+    Ok(())
+  }
+
+  /// Compacts the palette of every frame with a palette of its own added with `addFrame`, see `<Frame>.compactPalette`.
+  /// Decoded frames often carry 256-color palettes of which only a few entries are used, which costs space in every frame.
+  /// @param order - How to order the colors that are kept. Defaults to `PaletteOrder.Original`.
+  #[napi]
+  pub fn set_compact_palettes(&mut self, value: bool, order: Option<PaletteOrder>) {
+    self.compact_palettes = value.then(|| order.map(Into::into).unwrap_or_default());
+  }
+
+  /// Add a frame whose buffer is already LZW-compressed to the gif, without re-encoding it.
+  ///
+  /// ### Notes:
+  /// - The frame is usually read from a decoder with `<DecodeOptions>.skipFrameDecoding` enabled.
+  /// - A frame without a palette of its own keeps decoding against the global palette of the GIF it was read from, which becomes its own palette unless it matches the encoder's.
+  /// - The frame must fit within the gif's `width` and `height`, taking `left` and `top` into account.
+  #[napi]
+  pub fn add_raw_frame(&mut self, frame: &Frame) -> napi::Result<()> {
+    if frame.buf_type != FrameBufType::Lzw {
+      return Err(Error::new(
+        Status::InvalidArg,
+        "Frame does not hold LZW data, use `<Encoder>.addFrame` instead",
+      ));
+    }
+
+    self.check_bounds(frame)?;
+
+    let w = frame.gif_frame();
+    let prepared =
+      encode::prepare_raw_frame(&w, frame.source_palette.as_deref(), self.palette.as_deref())?;
+    lock(&self.w)
+      .write_lzw_pre_encoded_frame(&prepared)
+      .map_err(|e| {
+        Error::new(
+          Status::GenericFailure,
+          format!("Failed to write a raw frame: {}", e),
+        )
+      })?;
+    Ok(())
+  }
+
+  /// Sets the repeat count for the gif. If the value is -1, the gif will repeat infinitely; otherwise, the gif will repeat a `value` number of times.
+  #[napi]
+  pub fn set_repeat(&mut self, value: i16) {
+    let _ = lock(&self.w).set_repeat(if value <= -1 {
+      gif::Repeat::Infinite
+    } else {
+      gif::Repeat::Finite(value as u16)
+    });
+  }
+
+  /// Returns the gif buffer.
+  #[napi]
+  pub fn get_buffer(&mut self) -> napi::Result<Buffer> {
+    let mut buf = lock(&self.w).get_ref().get_ref().clone();
+    buf.push(0x3B);
+    Ok(Buffer::from(buf))
+  }
+
+  /// Returns the bytes written since the encoder was created or since the last call, and removes them from the encoder.
+  ///
+  /// ### Notes:
+  /// - `<Encoder>.getBuffer` only returns the bytes written after the last call, followed by the trailer.
+  #[napi]
+  pub fn take_buffer(&mut self) -> Buffer {
+    Buffer::from(self.take())
+  }
+
+  /// Returns the gif buffer like `<Encoder>.getBuffer`, but hands it over without copying it.
+  ///
+  /// ### Notes:
+  /// - The encoder is left empty, like after `<Encoder>.takeBuffer`. Frames added afterwards are not part of the returned gif.
+  #[napi]
+  pub fn finish(&mut self) -> Buffer {
+    let mut buf = self.take();
+    buf.push(0x3B);
+    Buffer::from(buf)
+  }
+
+  fn take(&self) -> Vec<u8> {
+    let mut w = lock(&self.w);
+    let cursor = w.get_mut();
+    cursor.set_position(0);
+    std::mem::take(cursor.get_mut())
+  }
+
+  fn rgba_frames<F>(
+    &self,
+    frames: Vec<F>,
+    delays: Option<Vec<u16>>,
+    speed: Option<i32>,
+    threads: Option<u32>,
+  ) -> napi::Result<RgbaFrames<F>> {
+    if let Some(delays) = &delays {
+      if delays.len() != frames.len() {
+        return Err(Error::new(
+          Status::InvalidArg,
+          format!("Expected {} delays, got {}", frames.len(), delays.len()),
+        ));
+      }
+    }
+
+    Ok(RgbaFrames {
+      w: self.w.clone(),
+      width: self.width,
+      height: self.height,
+      palette: self.palette.clone(),
+      lossy: self.lossy,
+      frames,
+      delays,
+      speed: speed.unwrap_or(15),
+      threads: threads.map(|t| t as usize),
+    })
+  }
+
+  fn check_bounds(&self, frame: &Frame) -> napi::Result<()> {
+    if frame.w.left as u32 + frame.w.width as u32 > self.width as u32
+      || frame.w.top as u32 + frame.w.height as u32 > self.height as u32
+    {
+      return Err(Error::new(
+        Status::GenericFailure,
+        format!(
+          "Frame {}x{} at {},{} does not fit within encoder dimensions {}x{}",
+          frame.w.width, frame.w.height, frame.w.left, frame.w.top, self.width, self.height
+        ),
+      ));
+    }
+    Ok(())
+  }
+}
+
+/// RGBA frames to quantize and write to an encoder, see `<Encoder>.addRgbaFrames`.
+struct RgbaFrames<F> {
+  w: Arc<Mutex<Writer>>,
+  width: u16,
+  height: u16,
+  palette: Option<Vec<u8>>,
+  lossy: u32,
+  frames: Vec<F>,
+  delays: Option<Vec<u16>>,
+  speed: i32,
+  threads: Option<usize>,
+}
+
+impl<F: AsMut<[u8]>> RgbaFrames<F> {
+  fn write(&mut self, control: Control<'_>) -> napi::Result<()> {
+    let mut w = lock(&self.w);
+    let encoded = quantize::quantize_rgba_batch(
+      self.width,
+      self.height,
+      self.frames.iter_mut().map(|f| f.as_mut()).collect(),
+      self.speed,
+      self.palette.as_deref(),
+      self.lossy,
+      self.threads,
+      control,
+    )?;
+
+    for (i, mut frame) in encoded.into_iter().enumerate() {
+      if let Some(delays) = &self.delays {
+        frame.delay = delays[i];
+      }
+
+      w.write_lzw_pre_encoded_frame(&frame).map_err(|e| {
+        Error::new(
+          Status::GenericFailure,
+          format!("Failed to write a frame: {}", e),
+        )
+      })?;
+    }
+    Ok(())
+  }
+}
+
+/// Quantizes and writes RGBA frames to an encoder on the libuv thread pool.
+pub struct AddRgbaFramesTask {
+  frames: RgbaFrames<Vec<u8>>,
+  hooks: Hooks,
+}
+
+impl Task for AddRgbaFramesTask {
+  type Output = ();
+  type JsValue = ();
+
+  fn compute(&mut self) -> napi::Result<Self::Output> {
+    let frames = &mut self.frames;
+    self.hooks.run(|control| frames.write(control))
+  }
+
+  fn resolve(&mut self, _env: Env, _output: Self::Output) -> napi::Result<Self::JsValue> {
+    Ok(())
+  }
+
+  fn finally(&mut self, env: Env) -> napi::Result<()> {
+    self.hooks.release(env)
+  }
+}
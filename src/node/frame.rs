@@ -0,0 +1,679 @@
+use std::borrow::Cow;
+
+use super::enums::{DisposalMethod, FrameBufType, InputFormat, PaletteOrder};
+use super::sprite::{self, SpriteFrame};
+use super::util::{self, ImageStats, PaletteColor, PaletteOptions};
+use crate::quantize::{self, check_speed};
+use crate::{apng, convert, css, hex, lzw, palette, pixels, stats};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+const PNG_SIGNATURE: &[u8] = &[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+
+/// Options for `Frame.fromPixels`.
+#[napi(object)]
+pub struct PixelsOptions {
+  /// Defaults to `"rgba"`.
+  pub format: Option<InputFormat>,
+  /// The number of bytes from the start of one row to the next, for buffers with padding after each row.
+  /// Defaults to the length of a row.
+  pub stride: Option<u32>,
+}
+
+/// How semi-transparent pixels are handled when quantizing RGBA pixels. GIF pixels are either fully opaque or fully transparent.
+#[napi(object)]
+pub struct AlphaOptions {
+  /// Pixels with an alpha below this become transparent, all others opaque. The default is `1`, only making fully transparent pixels transparent.
+  pub threshold: Option<u8>,
+  /// Replace the threshold with an ordered dither pattern, so that soft edges fade out instead of being cut off. The default is `false`.
+  pub dither: Option<bool>,
+  /// Blend semi-transparent pixels that stay opaque over this hex or other CSS color instead of dropping their alpha.
+  pub matte: Option<String>,
+  /// Reserve this palette index for transparent pixels. Opaque pixels never use it, even if the frame has no transparent pixels.
+  pub transparent_index: Option<u8>,
+}
+
+fn alpha_options(options: Option<AlphaOptions>) -> napi::Result<quantize::AlphaOptions> {
+  let Some(options) = options else {
+    return Ok(quantize::AlphaOptions::default());
+  };
+  Ok(quantize::AlphaOptions {
+    threshold: options.threshold.unwrap_or(1),
+    dither: options.dither.unwrap_or(false),
+    matte: match &options.matte {
+      Some(c) => {
+        let [r, g, b, _] = css::parse_color(c)?;
+        Some([r, g, b])
+      }
+      None => None,
+    },
+    transparent_index: options.transparent_index,
+  })
+}
+
+/// A GIF frame.
+#[napi]
+pub struct Frame<'a> {
+  pub buf_type: FrameBufType,
+  pub(crate) w: gif::Frame<'a>,
+  pub(crate) lossy: Option<u32>,
+  /// The JavaScript buffer holding the pixels once `buffer` has been read, leaving `w.buffer` empty.
+  /// Only read on the main thread, see [`Frame::gif_frame`].
+  view: Option<Buffer>,
+  /// The global palette of the GIF a frame holding LZW data was read from, if the frame has no palette of its own.
+  pub(crate) source_palette: Option<Vec<u8>>,
+}
+
+#[napi]
+impl<'a> Frame<'a> {
+  fn new(
+    width: u16,
+    height: u16,
+    buf: &mut [u8],
+    buf_type: FrameBufType,
+    speed: i32,
+    transparent: Option<u8>,
+    alpha: &quantize::AlphaOptions,
+  ) -> napi::Result<Frame<'a>> {
+    if buf_type == FrameBufType::Lzw {
+      return Err(Error::new(
+        Status::InvalidArg,
+        "Frames can not be created from LZW data",
+      ));
+    }
+
+    // GIF frames hold palette indices, so colors are quantized and the frame always ends up indexed.
+    Ok(Self {
+      w: match &buf_type {
+        FrameBufType::Rgba => quantize::quantize_rgba_with_alpha(width, height, buf, speed, alpha)?,
+        FrameBufType::Hex => quantize::quantize_rgba_with_alpha(
+          width,
+          height,
+          &mut hex::hex_pixels_to_rgba(buf)?,
+          speed,
+          alpha,
+        )?,
+        FrameBufType::Rgb => quantize::quantize_rgb(width, height, buf, speed)?,
+        FrameBufType::IndexedPixels => {
+          if buf.len() != width as usize * height as usize {
+            return Err(Error::new(Status::InvalidArg, "Buffer size mismatch"));
+          }
+          gif::Frame::from_indexed_pixels(width, height, buf, transparent)
+        }
+        FrameBufType::Lzw => unreachable!(),
+      },
+      buf_type: FrameBufType::IndexedPixels,
+      lossy: None,
+      view: None,
+      source_palette: None,
+    })
+  }
+
+  #[napi(getter)]
+  pub fn width(&self) -> u16 {
+    self.w.width
+  }
+  #[napi(getter)]
+  pub fn height(&self) -> u16 {
+    self.w.height
+  }
+
+  #[napi(getter)]
+  pub fn delay(&self) -> u16 {
+    self.w.delay
+  }
+  #[napi(setter)]
+  pub fn set_delay(&mut self, val: u16) {
+    self.w.delay = val;
+  }
+
+  #[napi(getter)]
+  pub fn dispose(&self) -> DisposalMethod {
+    self.w.dispose.into()
+  }
+  #[napi(setter)]
+  pub fn set_dispose(&mut self, val: DisposalMethod) {
+    self.w.dispose = match val {
+      DisposalMethod::Any => gif::DisposalMethod::Any,
+      DisposalMethod::Keep => gif::DisposalMethod::Keep,
+      DisposalMethod::Background => gif::DisposalMethod::Background,
+      DisposalMethod::Previous => gif::DisposalMethod::Previous,
+    };
+  }
+
+  #[napi(getter)]
+  pub fn needs_user_input(&self) -> bool {
+    self.w.needs_user_input
+  }
+  #[napi(setter)]
+  pub fn set_needs_user_input(&mut self, val: bool) {
+    self.w.needs_user_input = val;
+  }
+
+  #[napi(getter)]
+  pub fn transparent(&self) -> Option<u8> {
+    self.w.transparent
+  }
+  #[napi(setter)]
+  pub fn set_transparent(&mut self, val: Option<u8>) {
+    self.w.transparent = val;
+  }
+
+  #[napi(getter)]
+  pub fn interlaced(&self) -> bool {
+    self.w.interlaced
+  }
+  #[napi(setter)]
+  pub fn set_interlaced(&mut self, val: bool) {
+    self.w.interlaced = val;
+  }
+
+  #[napi(getter)]
+  pub fn top(&self) -> u16 {
+    self.w.top
+  }
+  #[napi(setter)]
+  pub fn set_top(&mut self, val: u16) {
+    self.w.top = val;
+  }
+
+  #[napi(getter)]
+  pub fn left(&self) -> u16 {
+    self.w.left
+  }
+  #[napi(setter)]
+  pub fn set_left(&mut self, val: u16) {
+    self.w.left = val;
+  }
+
+  /// Lossy LZW compression level for this frame, overriding the encoder's level. See `<Encoder>.setLossy`.
+  #[napi(getter)]
+  pub fn lossy(&self) -> Option<u32> {
+    self.lossy
+  }
+  #[napi(setter)]
+  pub fn set_lossy(&mut self, val: Option<u32>) -> napi::Result<()> {
+    if let Some(val) = val {
+      lzw::check_lossy(val)?;
+    }
+    self.lossy = val;
+    Ok(())
+  }
+
+  /// The frame's palette.
+  ///
+  /// ### Notes:
+  /// - Unlike `<Frame>.buffer`, this is a copy of at most 768 bytes. Writing to it does not change the frame; use `<Frame>.setPalette`.
+  #[napi(getter)]
+  pub fn get_palette(&self) -> Option<Uint8Array> {
+    // `gif::Frame` owns its palette as a `Vec`, which can't borrow JavaScript memory the way the `Cow` buffer does.
+    self
+      .w
+      .palette
+      .as_ref()
+      .map(|palette| Uint8Array::new(palette.clone()))
+  }
+
+  #[napi]
+  pub fn set_palette(&mut self, val: Option<&[u8]>) {
+    self.w.palette = val.map(|p| p.to_vec());
+  }
+
+  /// The frame's buffer.
+  ///
+  /// ### Notes:
+  /// - The buffer is a view into the frame's data, not a copy. Writing to it changes the frame.
+  #[napi(getter)]
+  pub fn get_buffer(&mut self, env: Env) -> napi::Result<Buffer> {
+    if let Some(view) = &self.view {
+      return Ok(view.clone());
+    }
+
+    // Hand the pixels over to JavaScript and keep a reference, so every read returns the same buffer.
+    let data = std::mem::take(&mut self.w.buffer).into_owned();
+    // SAFETY: both calls get the environment of the current call, and `value` is the buffer just created.
+    let view = unsafe {
+      let value = Buffer::to_napi_value(env.raw(), Buffer::from(data))?;
+      Buffer::from_napi_value(env.raw(), value)?
+    };
+    self.view = Some(view.clone());
+    Ok(view)
+  }
+
+  #[napi]
+  pub fn set_buffer(&mut self, buf: &[u8]) {
+    self.w.buffer = Cow::Owned(buf.to_owned());
+    self.view = None;
+  }
+
+  /// Encodes the frame as a PNG.
+  /// Indexed frames are written as an indexed PNG, keeping the palette and the transparent index.
+  /// @param globalPalette - The palette to use if the frame has no palette of its own. (e.g. `<Decoder>.globalPalette`)
+  #[napi]
+  pub fn to_png(&self, global_palette: Option<&[u8]>) -> napi::Result<Buffer> {
+    Ok(Buffer::from(apng::encode_png(
+      &self.gif_frame(),
+      (&self.buf_type).into(),
+      global_palette,
+    )?))
+  }
+
+  /// Finds up to `count` colors that best represent the frame, most common first. See `extractPalette`.
+  /// @param globalPalette - The palette to use if the frame has no palette of its own. (e.g. `<Decoder>.globalPalette`)
+  #[napi]
+  pub fn extract_palette(
+    &self,
+    count: u32,
+    options: Option<PaletteOptions>,
+    global_palette: Option<&[u8]>,
+  ) -> napi::Result<Vec<PaletteColor>> {
+    let rgba = apng::frame_to_rgba(&self.gif_frame(), (&self.buf_type).into(), global_palette)?;
+    util::extract_palette_from([rgba], count, options)
+  }
+
+  /// Computes the frame's pixel statistics: distinct colors, channel means and variances, transparency and the bounds of its visible pixels.
+  /// Indexed frames also count how often each palette index is used.
+  /// @param globalPalette - The palette to use if the frame has no palette of its own. (e.g. `<Decoder>.globalPalette`)
+  #[napi]
+  pub fn stats(&self, global_palette: Option<&[u8]>) -> napi::Result<ImageStats> {
+    Ok(stats::frame_stats(&self.gif_frame(), (&self.buf_type).into(), global_palette)?.into())
+  }
+
+  /// Returns a copy of the frame with unused palette entries dropped and duplicate colors merged, see `compactPalette`.
+  /// Frames without a palette of their own get the compacted `globalPalette` as their own.
+  ///
+  /// ### Notes:
+  /// - Only frames holding indexed pixels can be compacted.
+  /// @param order - How to order the colors that are kept. Defaults to `PaletteOrder.Original`.
+  /// @param globalPalette - The palette to use if the frame has no palette of its own. (e.g. `<Decoder>.globalPalette`)
+  #[napi]
+  pub fn compact_palette(
+    &self,
+    order: Option<PaletteOrder>,
+    global_palette: Option<&[u8]>,
+  ) -> napi::Result<Frame<'a>> {
+    if self.buf_type != FrameBufType::IndexedPixels {
+      return Err(Error::new(
+        Status::InvalidArg,
+        "Only frames holding indexed pixels can be compacted",
+      ));
+    }
+
+    let mut w = self.w.clone();
+    if let Some(view) = &self.view {
+      w.buffer = Cow::Owned(view.to_vec());
+    }
+    palette::compact_frame_palette(
+      &mut w,
+      global_palette,
+      order.map(Into::into).unwrap_or_default(),
+    )?;
+    let mut frame = Frame::from_gif_frame(w, FrameBufType::IndexedPixels);
+    frame.lossy = self.lossy;
+    Ok(frame)
+  }
+
+  /// Returns a copy of the frame with its buffer converted to another representation.
+  /// Indexed pixels are resolved against the frame's palette and transparent index, or `globalPalette` if the frame has none.
+  ///
+  /// ### Notes:
+  /// - Converting to indexed pixels keeps the exact colors of frames with up to 256 colors, and quantizes the others.
+  /// - Speed needs to be in the range 1-30. Higher is faster, lower CPU usage but worse quality.
+  /// - Frames holding LZW data can not be converted.
+  /// @param bufType - The representation to convert to.
+  /// @param globalPalette - The palette to use if the frame has no palette of its own. (e.g. `<Decoder>.globalPalette`)
+  /// @param speed - The quantization speed, used when converting to indexed pixels.
+  #[napi]
+  pub fn convert_to(
+    &self,
+    buf_type: FrameBufType,
+    global_palette: Option<&[u8]>,
+    speed: Option<i32>,
+  ) -> napi::Result<Frame<'a>> {
+    let mut frame = Frame::from_gif_frame(
+      convert::convert_frame(
+        &self.gif_frame(),
+        (&self.buf_type).into(),
+        (&buf_type).into(),
+        global_palette,
+        speed.unwrap_or(15),
+      )?,
+      buf_type,
+    );
+    frame.lossy = self.lossy;
+    Ok(frame)
+  }
+
+  /// Creates a frame from RGBA pixel data.
+  ///
+  /// ### Notes:
+  /// - Speed needs to be in the range 1-30. Higher is faster, lower CPU usage but worse quality.
+  /// - The size of `buffer` should match the expected size based on `width`, `height`.
+  /// - Only fully transparent pixels become transparent unless set otherwise with `alpha`.
+  #[napi]
+  pub fn from_rgba(
+    width: u16,
+    height: u16,
+    mut buffer: Uint8Array,
+    speed: Option<i32>,
+    alpha: Option<AlphaOptions>,
+  ) -> napi::Result<Frame<'a>> {
+    if let Some(speed) = speed {
+      check_speed(speed)?;
+    }
+
+    unsafe {
+      Self::new(
+        width,
+        height,
+        buffer.as_mut(),
+        FrameBufType::Rgba,
+        speed.unwrap_or(15),
+        None,
+        &alpha_options(alpha)?,
+      )
+    }
+  }
+
+  /// Creates a frame from RGB pixel data.
+  ///
+  /// ### Notes:
+  /// - Speed needs to be in the range 1-30. Higher is faster, lower CPU usage but worse quality.
+  /// - The size of `buffer` should match the expected size based on `width`, `height`.
+  #[napi]
+  pub fn from_rgb(
+    width: u16,
+    height: u16,
+    mut buffer: Uint8Array,
+    speed: Option<i32>,
+  ) -> napi::Result<Frame<'a>> {
+    unsafe {
+      Self::new(
+        width,
+        height,
+        buffer.as_mut(),
+        FrameBufType::Rgb,
+        speed.unwrap_or(15),
+        None,
+        &quantize::AlphaOptions::default(),
+      )
+    }
+  }
+
+  /// Creates a frame from BGRA, ARGB, premultiplied RGBA or grayscale pixel data, converting it to RGBA first.
+  ///
+  /// ### Notes:
+  /// - Speed needs to be in the range 1-30. Higher is faster, lower CPU usage but worse quality.
+  /// - Rows may be padded up to `stride` bytes. The padding after the last row can be left out.
+  /// - Only fully transparent pixels become transparent unless set otherwise with `alpha`.
+  #[napi]
+  pub fn from_pixels(
+    width: u16,
+    height: u16,
+    buffer: &[u8],
+    options: Option<PixelsOptions>,
+    speed: Option<i32>,
+    alpha: Option<AlphaOptions>,
+  ) -> napi::Result<Frame<'a>> {
+    let speed = speed.unwrap_or(15);
+    check_speed(speed)?;
+
+    let (format, stride) = options.map_or((None, None), |o| (o.format, o.stride));
+    let mut rgba = pixels::to_rgba(
+      buffer,
+      width as usize,
+      height as usize,
+      format.map_or(pixels::InputFormat::Rgba, Into::into),
+      stride.map(|s| s as usize),
+    )?;
+    Self::new(
+      width,
+      height,
+      &mut rgba,
+      FrameBufType::Rgba,
+      speed,
+      None,
+      &alpha_options(alpha)?,
+    )
+  }
+
+  /// Creates a frame from hex colors, one per pixel. (e.g. `["#FF0000", "#00FF0080"]`)
+  ///
+  /// ### Notes:
+  /// - Speed needs to be in the range 1-30. Higher is faster, lower CPU usage but worse quality.
+  /// - The length of `buffer` should match the number of pixels based on `width`, `height`.
+  /// - Only fully transparent pixels become transparent unless set otherwise with `alpha`.
+  #[napi]
+  pub fn from_hex(
+    width: u16,
+    height: u16,
+    buffer: Vec<String>,
+    speed: Option<i32>,
+    alpha: Option<AlphaOptions>,
+  ) -> napi::Result<Frame<'a>> {
+    Self::new(
+      width,
+      height,
+      &mut hex::hex_to_rgba(&buffer)?,
+      FrameBufType::Rgba,
+      speed.unwrap_or(15),
+      None,
+      &alpha_options(alpha)?,
+    )
+  }
+
+  /// Creates a frame from a PNG image.
+  ///
+  /// ### Notes:
+  /// - Speed needs to be in the range 1-30. Higher is faster, lower CPU usage but worse quality.
+  /// - Only fully transparent pixels become transparent unless set otherwise with `alpha`.
+  /// - For an APNG, only the first frame is used. See `Frame.fromApng`.
+  #[napi]
+  pub fn from_png(
+    buffer: &[u8],
+    speed: Option<i32>,
+    alpha: Option<AlphaOptions>,
+  ) -> napi::Result<Frame<'a>> {
+    let png = apng::decode_png(buffer)?
+      .into_iter()
+      .next()
+      .ok_or_else(|| Error::new(Status::InvalidArg, "PNG has no frames"))?;
+    Self::from_png_frame(png, speed.unwrap_or(15), &alpha_options(alpha)?)
+  }
+
+  /// Creates frames from every frame of an APNG, keeping their offsets, delays and disposal methods.
+  ///
+  /// ### Notes:
+  /// - Speed needs to be in the range 1-30. Higher is faster, lower CPU usage but worse quality.
+  /// - Only fully transparent pixels become transparent unless set otherwise with `alpha`.
+  /// - APNGs whose frames clear pixels with the `source` blend operation are composited into full-size frames, as GIF frames are always drawn over the canvas.
+  #[napi]
+  pub fn from_apng(
+    buffer: &[u8],
+    speed: Option<i32>,
+    alpha: Option<AlphaOptions>,
+  ) -> napi::Result<Vec<Frame<'a>>> {
+    let speed = speed.unwrap_or(15);
+    let alpha = alpha_options(alpha)?;
+    apng::decode_png(buffer)?
+      .into_iter()
+      .map(|png| Self::from_png_frame(png, speed, &alpha))
+      .collect()
+  }
+
+  /// Creates frames by cutting them out of a sprite sheet.
+  /// @param buffer - The sheet as raw RGBA or as a PNG.
+  /// @param frames - Placement of every frame in the sheet. (e.g. `spriteGrid(...)` or `<SpriteSheet>.frames`)
+  /// @param sheetWidth - Width of the sheet, required for raw RGBA.
+  ///
+  /// ### Notes:
+  /// - Speed needs to be in the range 1-30. Higher is faster, lower CPU usage but worse quality.
+  /// - Only fully transparent pixels become transparent unless set otherwise with `alpha`.
+  /// - Frames are positioned at their `left` and `top` offsets and cleared to the background once displayed.
+  #[napi]
+  pub fn from_sprite_sheet(
+    buffer: &[u8],
+    frames: Vec<SpriteFrame>,
+    sheet_width: Option<u32>,
+    speed: Option<i32>,
+    alpha: Option<AlphaOptions>,
+  ) -> napi::Result<Vec<Frame<'a>>> {
+    let speed = speed.unwrap_or(15);
+    let alpha = alpha_options(alpha)?;
+    let (sheet, sheet_width) = if buffer.starts_with(PNG_SIGNATURE) {
+      let png = apng::decode_png(buffer)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| Error::new(Status::InvalidArg, "PNG has no frames"))?;
+      (png.rgba, png.width as u32)
+    } else {
+      let sheet_width = sheet_width.ok_or_else(|| {
+        Error::new(
+          Status::InvalidArg,
+          "Sheet width is required for raw RGBA sheets",
+        )
+      })?;
+      (buffer.to_vec(), sheet_width)
+    };
+
+    sprite::slice_sheet(&sheet, sheet_width, &frames)?
+      .into_iter()
+      .zip(&frames)
+      .map(|(rgba, sprite)| {
+        Self::from_png_frame(
+          apng::PngFrame {
+            width: apng::to_u16(sprite.width)?,
+            height: apng::to_u16(sprite.height)?,
+            left: apng::to_u16(sprite.left)?,
+            top: apng::to_u16(sprite.top)?,
+            delay: sprite.delay.min(u16::MAX as u32) as u16,
+            dispose: gif::DisposalMethod::Background,
+            rgba,
+          },
+          speed,
+          &alpha,
+        )
+      })
+      .collect()
+  }
+
+  fn from_png_frame(
+    mut png: apng::PngFrame,
+    speed: i32,
+    alpha: &quantize::AlphaOptions,
+  ) -> napi::Result<Frame<'a>> {
+    check_speed(speed)?;
+    // Clearing transparent pixels first keeps them to a single palette entry. Quantizing applies the options again, which leaves them as they are.
+    quantize::apply_alpha(&mut png.rgba, png.width, alpha);
+
+    let mut frame = Self::new(
+      png.width,
+      png.height,
+      &mut png.rgba,
+      FrameBufType::Rgba,
+      speed,
+      None,
+      alpha,
+    )?;
+    frame.w.left = png.left;
+    frame.w.top = png.top;
+    frame.w.delay = png.delay;
+    frame.w.dispose = png.dispose;
+    Ok(frame)
+  }
+
+  /// Creates a frame from indexed pixel data.
+  ///
+  /// ### Notes:
+  /// - The size of `buffer` should match the expected size based on `width`, `height`.
+  #[napi]
+  pub fn from_indexed_pixels(
+    width: u16,
+    height: u16,
+    mut pixels: Uint8Array,
+    palette: Option<Vec<u8>>,
+    transparent: Option<u8>,
+  ) -> napi::Result<Frame<'a>> {
+    let mut frame = unsafe {
+      Self::new(
+        width,
+        height,
+        pixels.as_mut(),
+        FrameBufType::IndexedPixels,
+        0,
+        transparent,
+        &quantize::AlphaOptions::default(),
+      )?
+    };
+    frame.w.palette = palette;
+    frame.w.transparent = transparent;
+    Ok(frame)
+  }
+
+  /// The frame to encode or convert, with the pixels of the JavaScript buffer if `buffer` has been read.
+  /// The borrow ends with the current call, during which JavaScript can not run.
+  pub(crate) fn gif_frame(&self) -> Cow<'_, gif::Frame<'_>> {
+    match &self.view {
+      Some(view) => Cow::Owned(gif::Frame {
+        buffer: Cow::Borrowed(view),
+        ..self.w.clone()
+      }),
+      None => Cow::Borrowed(&self.w),
+    }
+  }
+
+  pub fn from_gif_frame(f: gif::Frame, buf_type: FrameBufType) -> Frame {
+    Frame {
+      w: f,
+      buf_type,
+      lossy: None,
+      view: None,
+      source_palette: None,
+    }
+  }
+
+  /*pub fn to_gif_frame(&self) -> gif::Frame<'static> {
+    let mut frame = match self.buf_type {
+      FrameBufType::Rgba | FrameBufType::Hex => gif::Frame::from_rgba_speed(
+        self.width,
+        self.height,
+        &mut self.buf.clone(),
+        self.speed.unwrap_or(10).into(),
+      ),
+      FrameBufType::Rgb => gif::Frame::from_rgb_speed(
+        self.width,
+        self.height,
+        &mut self.buf.clone(),
+        self.speed.unwrap_or(10).into(),
+      ),
+      FrameBufType::IndexedPixels => {
+        gif::Frame::from_indexed_pixels(self.width, self.height, self.buf.clone(), self.transparent)
+      }
+    };
+
+    if self.palette.is_some() {
+      frame.palette = self.palette.clone()
+    }
+    if frame.transparent.is_some() {
+      frame.transparent = self.transparent
+    }
+
+    frame.delay = self.delay;
+    frame.dispose = self.dispose.clone().into();
+    frame.needs_user_input = self.needs_user_input;
+    frame.top = self.top;
+    frame.left = self.left;
+    frame
+  }*/
+}
+
+impl Into<DisposalMethod> for gif::DisposalMethod {
+  fn into(self) -> DisposalMethod {
+    match self {
+      gif::DisposalMethod::Any => DisposalMethod::Any,
+      gif::DisposalMethod::Keep => DisposalMethod::Keep,
+      gif::DisposalMethod::Background => DisposalMethod::Background,
+      gif::DisposalMethod::Previous => DisposalMethod::Previous,
+    }
+  }
+}
@@ -0,0 +1,102 @@
+use crate::hash;
+use napi_derive::napi;
+
+/// The hashes of a composited frame, see `<Decoder>.fingerprint`.
+#[napi(object)]
+pub struct FrameHash {
+  /// The difference hash as 16 hex digits, see `dhash`.
+  pub dhash: String,
+  /// The perceptual hash as 16 hex digits, see `phash`.
+  pub phash: String,
+  /// How long the frame is shown in units of 10ms.
+  pub delay: u32,
+}
+
+/// The hashes of the composited frames of a GIF along with their timing, see `<Decoder>.fingerprint`.
+#[napi(object)]
+pub struct Fingerprint {
+  /// One entry per frame. Consecutive frames with the same hashes are merged into one, adding up their delays.
+  pub frames: Vec<FrameHash>,
+  /// The summed delays of all frames in units of 10ms.
+  pub duration: u32,
+}
+
+impl From<hash::Fingerprint> for Fingerprint {
+  fn from(f: hash::Fingerprint) -> Self {
+    Self {
+      duration: f.duration(),
+      frames: f
+        .frames
+        .into_iter()
+        .map(|h| FrameHash {
+          dhash: hash::format_hash(h.dhash),
+          phash: hash::format_hash(h.phash),
+          delay: h.delay,
+        })
+        .collect(),
+    }
+  }
+}
+
+impl TryFrom<Fingerprint> for hash::Fingerprint {
+  type Error = crate::Error;
+
+  fn try_from(f: Fingerprint) -> crate::Result<Self> {
+    let frames = f
+      .frames
+      .iter()
+      .map(|h| {
+        Ok(hash::FrameHash {
+          dhash: hash::parse_hash(&h.dhash)?,
+          phash: hash::parse_hash(&h.phash)?,
+          delay: h.delay,
+        })
+      })
+      .collect::<crate::Result<_>>()?;
+    Ok(hash::Fingerprint { frames })
+  }
+}
+
+/// The difference hash of RGBA pixels as 16 hex digits.
+/// Each bit tells whether a cell of a 9 by 8 grayscale grid is brighter than its right neighbour.
+///
+/// ### Notes:
+/// - Fast, and robust to resizing and re-encoding. See `hashDistance` to compare hashes.
+/// - The size of `rgba` should match the expected size based on `width`, `height`.
+#[napi]
+pub fn dhash(rgba: &[u8], width: u16, height: u16) -> napi::Result<String> {
+  Ok(hash::format_hash(hash::dhash(rgba, width, height)?))
+}
+
+/// The perceptual hash of RGBA pixels as 16 hex digits.
+/// Each bit tells whether one of the 64 lowest frequencies of the DCT of a 32 by 32 grayscale version is above their median.
+///
+/// ### Notes:
+/// - Slower than `dhash`, but also robust to changes in brightness and contrast. See `hashDistance` to compare hashes.
+/// - The size of `rgba` should match the expected size based on `width`, `height`.
+#[napi]
+pub fn phash(rgba: &[u8], width: u16, height: u16) -> napi::Result<String> {
+  Ok(hash::format_hash(hash::phash(rgba, width, height)?))
+}
+
+/// The number of bits two hashes from `dhash` or `phash` differ in, from 0 for identical images to 64.
+/// Images with a distance of up to about 10 usually look alike.
+#[napi]
+pub fn hash_distance(a: String, b: String) -> napi::Result<u32> {
+  Ok(hash::distance(hash::parse_hash(&a)?, hash::parse_hash(&b)?))
+}
+
+/// How alike two GIFs are based on their fingerprints, in the range 0-1 where 1 means they look the same throughout.
+///
+/// ### Notes:
+/// - Both animations are stretched to the same length and compared over time, so every frame counts as much as it is shown.
+/// - Speeding a GIF up or down keeps it similar, reordering its frames does not.
+/// - Unrelated GIFs score around 0.5; re-encoded or resized copies usually score above 0.9.
+#[napi]
+pub fn fingerprint_similarity(a: Fingerprint, b: Fingerprint) -> napi::Result<f64> {
+  let (a, b) = (
+    hash::Fingerprint::try_from(a)?,
+    hash::Fingerprint::try_from(b)?,
+  );
+  Ok(a.similarity(&b))
+}
@@ -0,0 +1,12 @@
+//! N-API bindings wrapping the core of the crate.
+
+pub mod compare;
+pub mod decoder;
+pub mod encoder;
+pub mod enums;
+pub mod frame;
+pub mod hash;
+pub mod neuquant;
+pub mod progress;
+pub mod sprite;
+pub mod util;
@@ -0,0 +1,154 @@
+use crate::progress::{self, CancelToken, Control};
+use napi::bindgen_prelude::*;
+use napi::threadsafe_function::{ErrorStrategy, ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi::{JsObject, Ref};
+use napi_derive::napi;
+
+/// How far a long-running operation has got.
+#[napi(object)]
+pub struct Progress {
+  /// Frames processed so far.
+  pub frames: u32,
+  /// The number of frames to process, when known up front.
+  pub total_frames: Option<u32>,
+  /// Input bytes consumed so far, for operations reading a GIF.
+  pub bytes: Option<u32>,
+  /// The size of the input in bytes, for operations reading a GIF.
+  pub total_bytes: Option<u32>,
+}
+
+impl From<progress::Progress> for Progress {
+  fn from(p: progress::Progress) -> Self {
+    Progress {
+      frames: p.frames as u32,
+      total_frames: p.total_frames.map(|t| t as u32),
+      bytes: p.bytes.map(|b| b as u32),
+      total_bytes: p.total_bytes.map(|b| b as u32),
+    }
+  }
+}
+
+/// Options for operations running off the main thread.
+#[napi(object, object_to_js = false)]
+pub struct TaskOptions {
+  /// Stops the operation before its next frame once aborted. The promise then rejects with a `Cancelled` error.
+  #[napi(ts_type = "AbortSignal")]
+  pub signal: Option<JsObject>,
+  /// Called as frames are processed.
+  #[napi(ts_type = "(progress: Progress) => void")]
+  pub on_progress: Option<JsFunction>,
+}
+
+/// The abort listener a task added to its signal, removed once the task settles.
+struct AbortListener {
+  signal: Ref<()>,
+  listener: Ref<()>,
+}
+
+/// The cancellation token and progress callback of a task, see `TaskOptions`.
+pub(crate) struct Hooks {
+  cancel: CancelToken,
+  on_progress: Option<ThreadsafeFunction<Progress, ErrorStrategy::Fatal>>,
+  abort: Option<AbortListener>,
+}
+
+impl Hooks {
+  pub(crate) fn new(env: &Env, options: Option<TaskOptions>) -> napi::Result<Hooks> {
+    let cancel = CancelToken::new();
+    let Some(options) = options else {
+      return Ok(Hooks {
+        cancel,
+        on_progress: None,
+        abort: None,
+      });
+    };
+
+    let mut abort = None;
+    if let Some(signal) = options.signal {
+      if signal.get_named_property::<bool>("aborted")? {
+        cancel.cancel();
+      }
+
+      let token = cancel.clone();
+      let listener = env.create_function_from_closure("onabort", move |ctx| {
+        token.cancel();
+        ctx.env.get_undefined()
+      })?;
+      let mut listener_options = env.create_object()?;
+      listener_options.set_named_property("once", true)?;
+      let add_event_listener: JsFunction = signal.get_named_property("addEventListener")?;
+      let event = env.create_string("abort")?;
+
+      // The reference has to be released by hand if the listener cannot be registered
+      let mut listener_ref = env.create_reference(&listener)?;
+      let registered = add_event_listener
+        .call(
+          Some(&signal),
+          &[
+            event.into_unknown(),
+            listener.into_unknown(),
+            listener_options.into_unknown(),
+          ],
+        )
+        .and_then(|_| env.create_reference(&signal));
+      match registered {
+        Ok(signal_ref) => {
+          abort = Some(AbortListener {
+            signal: signal_ref,
+            listener: listener_ref,
+          })
+        }
+        Err(e) => {
+          listener_ref.unref(*env)?;
+          return Err(e);
+        }
+      }
+    }
+
+    let on_progress = options
+      .on_progress
+      .map(|f| f.create_threadsafe_function(0, |ctx| Ok(vec![ctx.value])))
+      .transpose()?;
+
+    Ok(Hooks {
+      cancel,
+      on_progress,
+      abort,
+    })
+  }
+
+  /// Removes the abort listener from the signal. Call from `Task::finally` once the task has settled.
+  pub(crate) fn release(&mut self, env: Env) -> napi::Result<()> {
+    let Some(mut abort) = self.abort.take() else {
+      return Ok(());
+    };
+
+    let signal: JsObject = env.get_reference_value(&abort.signal)?;
+    let listener: JsFunction = env.get_reference_value(&abort.listener)?;
+    let remove_event_listener: JsFunction = signal.get_named_property("removeEventListener")?;
+    remove_event_listener.call(
+      Some(&signal),
+      &[
+        env.create_string("abort")?.into_unknown(),
+        listener.into_unknown(),
+      ],
+    )?;
+    abort.signal.unref(env)?;
+    abort.listener.unref(env)?;
+    Ok(())
+  }
+
+  /// Runs `f` with a [`Control`] that forwards progress to the JavaScript callback.
+  pub(crate) fn run<T>(&self, f: impl FnOnce(Control<'_>) -> T) -> T {
+    let on_progress = |p: progress::Progress| {
+      if let Some(tsfn) = &self.on_progress {
+        tsfn.call(p.into(), ThreadsafeFunctionCallMode::NonBlocking);
+      }
+    };
+
+    f(Control {
+      cancel: Some(&self.cancel),
+      on_progress: Some(&on_progress),
+    })
+  }
+}
@@ -0,0 +1,180 @@
+use crate::sprite::{self, SheetOptions, SpriteRect};
+use crate::{apng, css, PixelFormat};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+/// Options for building a sprite sheet.
+#[napi(object)]
+pub struct SpriteSheetOptions {
+  /// Number of columns of the grid. Defaults to a roughly square grid. Ignored when `packed` is set.
+  pub columns: Option<u32>,
+  /// Space in pixels between frames and around the edge of the sheet. The default is `0`.
+  pub padding: Option<u32>,
+  /// Background color of the sheet as a hex or other CSS color. The default is transparent.
+  pub background: Option<String>,
+  /// Trim transparent borders from every frame and pack them in rows instead of a uniform grid.
+  pub packed: Option<bool>,
+  /// Encode the sheet as a PNG instead of raw RGBA.
+  pub png: Option<bool>,
+}
+
+/// A frame's placement in a sprite sheet.
+#[napi(object)]
+#[derive(Clone)]
+pub struct SpriteFrame {
+  /// Horizontal position of the frame in the sheet.
+  pub x: u32,
+  /// Vertical position of the frame in the sheet.
+  pub y: u32,
+  /// Width of the frame in the sheet.
+  pub width: u32,
+  /// Height of the frame in the sheet.
+  pub height: u32,
+  /// Horizontal offset of the frame within the animation, non-zero for trimmed frames.
+  pub left: u32,
+  /// Vertical offset of the frame within the animation, non-zero for trimmed frames.
+  pub top: u32,
+  /// The delay for this frame in units of 10ms.
+  pub delay: u32,
+}
+
+/// A sprite sheet built from the frames of an animation.
+#[napi(object)]
+pub struct SpriteSheet {
+  /// Width of the sheet.
+  pub width: u32,
+  /// Height of the sheet.
+  pub height: u32,
+  /// Width of the animation the frames were taken from.
+  pub frame_width: u32,
+  /// Height of the animation the frames were taken from.
+  pub frame_height: u32,
+  /// The sheet as raw RGBA, or as a PNG if requested.
+  pub buffer: Buffer,
+  /// Placement of every frame in the sheet.
+  pub frames: Vec<SpriteFrame>,
+  /// JSON atlas in the common `frames`/`meta` layout, with durations in milliseconds.
+  pub atlas: String,
+}
+
+/// Creates the placements of `count` frames laid out in a uniform grid.
+/// @param count - The number of frames.
+/// @param frameWidth - Width of every frame.
+/// @param frameHeight - Height of every frame.
+/// @param columns - Number of columns. Defaults to a roughly square grid.
+/// @param padding - Space in pixels between frames and around the edge of the sheet.
+/// @param delay - The delay for every frame in units of 10ms.
+#[napi]
+pub fn sprite_grid(
+  count: u32,
+  frame_width: u32,
+  frame_height: u32,
+  columns: Option<u32>,
+  padding: Option<u32>,
+  delay: Option<u32>,
+) -> Vec<SpriteFrame> {
+  sprite::grid(
+    count,
+    frame_width,
+    frame_height,
+    columns,
+    padding.unwrap_or(0),
+    delay.unwrap_or(0),
+  )
+  .into_iter()
+  .map(SpriteFrame::from)
+  .collect()
+}
+
+impl From<SpriteRect> for SpriteFrame {
+  fn from(r: SpriteRect) -> Self {
+    Self {
+      x: r.x,
+      y: r.y,
+      width: r.width,
+      height: r.height,
+      left: r.left,
+      top: r.top,
+      delay: r.delay,
+    }
+  }
+}
+
+impl From<&SpriteFrame> for SpriteRect {
+  fn from(f: &SpriteFrame) -> Self {
+    Self {
+      x: f.x,
+      y: f.y,
+      width: f.width,
+      height: f.height,
+      left: f.left,
+      top: f.top,
+      delay: f.delay,
+    }
+  }
+}
+
+/// Lays out composited RGBA frames of `frame_width`x`frame_height` as a sprite sheet.
+pub(crate) fn build_sheet(
+  frame_width: u16,
+  frame_height: u16,
+  frames: &[(u16, Vec<u8>)],
+  options: Option<SpriteSheetOptions>,
+) -> napi::Result<SpriteSheet> {
+  let options = options.unwrap_or(SpriteSheetOptions {
+    columns: None,
+    padding: None,
+    background: None,
+    packed: None,
+    png: None,
+  });
+  let background = match &options.background {
+    Some(h) => css::parse_color(h)?,
+    None => [0; 4],
+  };
+
+  let sheet = sprite::build_sheet(
+    frame_width,
+    frame_height,
+    frames,
+    &SheetOptions {
+      columns: options.columns,
+      padding: options.padding.unwrap_or(0),
+      background,
+      packed: options.packed.unwrap_or(false),
+    },
+  )?;
+  let atlas = sheet.atlas_json();
+
+  let buffer = if options.png.unwrap_or(false) {
+    let frame = gif::Frame {
+      width: sheet.width as u16,
+      height: sheet.height as u16,
+      buffer: sheet.rgba.into(),
+      ..gif::Frame::default()
+    };
+    apng::encode_png(&frame, PixelFormat::Rgba, None)?
+  } else {
+    sheet.rgba
+  };
+
+  Ok(SpriteSheet {
+    width: sheet.width,
+    height: sheet.height,
+    frame_width: sheet.frame_width,
+    frame_height: sheet.frame_height,
+    buffer: Buffer::from(buffer),
+    frames: sheet.frames.into_iter().map(SpriteFrame::from).collect(),
+    atlas,
+  })
+}
+
+/// Cuts the frames out of an RGBA sprite sheet, returning each frame's RGBA pixels.
+pub(crate) fn slice_sheet(
+  sheet: &[u8],
+  sheet_width: u32,
+  frames: &[SpriteFrame],
+) -> napi::Result<Vec<Vec<u8>>> {
+  let rects: Vec<SpriteRect> = frames.iter().map(SpriteRect::from).collect();
+  Ok(sprite::slice_sheet(sheet, sheet_width, &rects)?)
+}
@@ -0,0 +1,361 @@
+use super::enums::{ColorSpace, DistanceMetric, PaletteOrder};
+use crate::{color, hex, palette, pixels, stats};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+/// Options for the text written by `rgbaToHex`, `rgbToHex` and `indexedToHex`.
+#[napi(object)]
+pub struct HexOptions {
+  /// Write hex digits in lowercase. The default is `false`.
+  pub lowercase: Option<bool>,
+  /// Write CSS `rgb()`/`rgba()` functions instead of hex colors, with alpha in the range 0-1. `allowShort` is ignored. The default is `false`.
+  pub css: Option<bool>,
+}
+
+fn text_format(
+  always_include_alpha: Option<bool>,
+  allow_short: Option<bool>,
+  options: Option<HexOptions>,
+) -> hex::TextFormat {
+  let options = options.unwrap_or(HexOptions {
+    lowercase: None,
+    css: None,
+  });
+  hex::TextFormat {
+    always_include_alpha: always_include_alpha.unwrap_or(false),
+    allow_short: allow_short.unwrap_or(false),
+    lowercase: options.lowercase.unwrap_or(false),
+    css: options.css.unwrap_or(false),
+  }
+}
+
+#[napi]
+pub fn rgba_to_hex(
+  rgba: &[u8],
+  always_include_alpha: Option<bool>,
+  allow_short: Option<bool>,
+  options: Option<HexOptions>,
+) -> napi::Result<Vec<String>> {
+  Ok(hex::rgba_to_strings(
+    rgba,
+    text_format(always_include_alpha, allow_short, options),
+  )?)
+}
+
+#[napi]
+pub fn rgb_to_hex(
+  rgb: &[u8],
+  allow_short: Option<bool>,
+  options: Option<HexOptions>,
+) -> napi::Result<Vec<String>> {
+  Ok(hex::rgb_to_strings(
+    rgb,
+    text_format(None, allow_short, options),
+  )?)
+}
+
+#[napi]
+pub fn hex_to_rgba(hexes: Vec<String>) -> napi::Result<Buffer> {
+  Ok(Buffer::from(hex::hex_to_rgba(&hexes)?))
+}
+
+#[napi]
+pub fn hex_to_rgb(hexes: Vec<String>) -> napi::Result<Buffer> {
+  Ok(Buffer::from(hex::hex_to_rgb(&hexes)?))
+}
+
+#[napi]
+pub fn indexed_to_rgba(pixels: &[u8], palette: &[u8], transparent_index: Option<u8>) -> Buffer {
+  Buffer::from(hex::indexed_to_rgba(pixels, palette, transparent_index))
+}
+
+#[napi]
+pub fn indexed_to_hex(
+  pixels: &[u8],
+  palette: &[u8],
+  transparent_index: Option<u8>,
+  always_include_alpha: Option<bool>,
+  allow_short: Option<bool>,
+  options: Option<HexOptions>,
+) -> Vec<String> {
+  hex::indexed_to_strings(
+    pixels,
+    palette,
+    transparent_index,
+    text_format(always_include_alpha, allow_short, options),
+  )
+}
+
+/// Converts RGB or RGBA pixels to another color space, three floats per pixel.
+/// @param alpha - Whether the pixels are RGBA. The alpha channel is kept as a fourth float in the range 0-1.
+#[napi]
+pub fn rgb_to_color_space(
+  pixels: &[u8],
+  space: ColorSpace,
+  alpha: Option<bool>,
+) -> napi::Result<Float32Array> {
+  Ok(Float32Array::new(color::pixels_to_space(
+    pixels,
+    space.into(),
+    alpha.unwrap_or(false),
+  )?))
+}
+
+/// Converts colors from another color space back to RGB or RGBA pixels, clamping colors outside the sRGB gamut.
+/// @param alpha - Whether the colors have a fourth alpha float in the range 0-1, producing RGBA pixels.
+#[napi]
+pub fn color_space_to_rgb(
+  values: Float32Array,
+  space: ColorSpace,
+  alpha: Option<bool>,
+) -> napi::Result<Buffer> {
+  Ok(Buffer::from(color::space_to_pixels(
+    &values,
+    space.into(),
+    alpha.unwrap_or(false),
+  )?))
+}
+
+/// Measures how different each pair of pixels in two RGB or RGBA buffers of the same length looks.
+/// @param alpha - Whether the pixels are RGBA. The alpha channel is ignored.
+/// @returns One distance per pixel.
+#[napi]
+pub fn color_distance(
+  a: &[u8],
+  b: &[u8],
+  metric: DistanceMetric,
+  alpha: Option<bool>,
+) -> napi::Result<Float32Array> {
+  Ok(Float32Array::new(color::distances(
+    a,
+    b,
+    metric.into(),
+    alpha.unwrap_or(false),
+  )?))
+}
+
+/// Returns the index of the palette color that looks closest to an RGB color, or `null` for an empty palette.
+#[napi]
+pub fn nearest_color(
+  palette: &[u8],
+  color: &[u8],
+  metric: DistanceMetric,
+) -> napi::Result<Option<u32>> {
+  let [r, g, b, ..] = *color else {
+    return Err(Error::new(
+      Status::InvalidArg,
+      "Color must have at least 3 components",
+    ));
+  };
+  Ok(color::nearest(palette, [r, g, b], metric.into()).map(|i| i as u32))
+}
+
+/// Drops the alpha channel of RGBA pixels.
+#[napi]
+pub fn rgba_to_rgb(rgba: &[u8]) -> Buffer {
+  Buffer::from(pixels::rgba_to_rgb(rgba))
+}
+
+/// Adds an opaque alpha channel to RGB pixels.
+#[napi]
+pub fn rgb_to_rgba(rgb: &[u8]) -> Buffer {
+  Buffer::from(pixels::rgb_to_rgba(rgb))
+}
+
+/// Swaps the red and blue channels of BGRA pixels. The same call converts RGBA pixels to BGRA.
+#[napi]
+pub fn bgra_to_rgba(bgra: &[u8]) -> Buffer {
+  let mut rgba = bgra.to_vec();
+  pixels::swap_red_blue(&mut rgba);
+  Buffer::from(rgba)
+}
+
+/// Multiplies the color channels of RGBA pixels by their alpha, as expected by canvas and GPU APIs.
+#[napi]
+pub fn premultiply_alpha(rgba: &[u8]) -> Buffer {
+  let mut premultiplied = rgba.to_vec();
+  pixels::premultiply(&mut premultiplied);
+  Buffer::from(premultiplied)
+}
+
+/// Reverses `premultiplyAlpha`. Fully transparent pixels are left unchanged.
+#[napi]
+pub fn unpremultiply_alpha(rgba: &[u8]) -> Buffer {
+  let mut straight = rgba.to_vec();
+  pixels::unpremultiply(&mut straight);
+  Buffer::from(straight)
+}
+
+/// Which pixels `extractPalette` leaves out.
+#[napi(object)]
+pub struct PaletteOptions {
+  /// Skip pixels with an alpha below 128. The default is `true`.
+  pub ignore_transparent: Option<bool>,
+  /// Skip near-white pixels. The default is `false`.
+  pub ignore_white: Option<bool>,
+  /// Skip near-black pixels. The default is `false`.
+  pub ignore_black: Option<bool>,
+  /// How close to white or black every channel of a pixel needs to be to be skipped. The default is `16`.
+  pub tolerance: Option<u8>,
+  /// The quantization speed in the range 1-30, used when there are more distinct colors than requested. The default is `10`.
+  pub speed: Option<i32>,
+}
+
+/// A color found by `extractPalette`.
+#[napi(object)]
+pub struct PaletteColor {
+  /// The color as `#RRGGBB`.
+  pub color: String,
+  /// The color as red, green and blue.
+  pub rgb: Vec<u8>,
+  /// The number of pixels closest to this color.
+  pub count: u32,
+  /// The share of the counted pixels closest to this color, in the range 0-100.
+  pub percentage: f64,
+}
+
+pub(crate) fn extract_palette_from(
+  buffers: impl IntoIterator<Item = impl AsRef<[u8]>>,
+  count: u32,
+  options: Option<PaletteOptions>,
+) -> napi::Result<Vec<PaletteColor>> {
+  let mut builder = palette_builder(count, options)?;
+  for buffer in buffers {
+    builder.add_rgba(buffer.as_ref())?;
+  }
+  Ok(palette_colors(builder))
+}
+
+pub(crate) fn palette_builder(
+  count: u32,
+  options: Option<PaletteOptions>,
+) -> napi::Result<palette::PaletteBuilder> {
+  let defaults = palette::ExtractOptions::default();
+  let options = options.map_or(defaults, |o| palette::ExtractOptions {
+    ignore_transparent: o.ignore_transparent.unwrap_or(defaults.ignore_transparent),
+    ignore_white: o.ignore_white.unwrap_or(defaults.ignore_white),
+    ignore_black: o.ignore_black.unwrap_or(defaults.ignore_black),
+    tolerance: o.tolerance.unwrap_or(defaults.tolerance),
+    speed: o.speed.unwrap_or(defaults.speed),
+  });
+  Ok(palette::PaletteBuilder::new(count as usize, &options)?)
+}
+
+pub(crate) fn palette_colors(builder: palette::PaletteBuilder) -> Vec<PaletteColor> {
+  builder
+    .finish()
+    .into_iter()
+    .map(|c| {
+      let [r, g, b] = c.rgb;
+      PaletteColor {
+        color: hex::TextFormat::default().format(r, g, b, 255),
+        rgb: c.rgb.to_vec(),
+        count: c.count as u32,
+        percentage: c.percentage,
+      }
+    })
+    .collect()
+}
+
+/// A rectangle on the GIF's canvas.
+#[napi(object)]
+pub struct Bounds {
+  pub left: u32,
+  pub top: u32,
+  pub width: u32,
+  pub height: u32,
+}
+
+/// Pixel statistics of a frame or a whole GIF, see `<Frame>.stats` and `<Decoder>.stats`.
+#[napi(object)]
+pub struct ImageStats {
+  /// The number of pixels counted.
+  pub pixels: u32,
+  /// The number of distinct RGBA colors.
+  pub distinct_colors: u32,
+  /// The mean of the red, green, blue and alpha channels, in the range 0-255.
+  pub mean: Vec<f64>,
+  /// The variance of the red, green, blue and alpha channels.
+  pub variance: Vec<f64>,
+  /// The share of fully transparent pixels, in the range 0-1.
+  pub transparent_fraction: f64,
+  /// The smallest rectangle on the canvas holding every pixel that is not fully transparent. Missing if all pixels are transparent.
+  pub bounds: Option<Bounds>,
+  /// How many pixels use each of the 256 palette indices. Only set for indexed frames.
+  pub palette_usage: Option<Vec<u32>>,
+}
+
+impl From<stats::Stats> for ImageStats {
+  fn from(s: stats::Stats) -> Self {
+    Self {
+      pixels: s.pixels as u32,
+      distinct_colors: s.distinct_colors as u32,
+      mean: s.mean.to_vec(),
+      variance: s.variance.to_vec(),
+      transparent_fraction: s.transparent_fraction,
+      bounds: s.bounds.map(|b| Bounds {
+        left: b.left,
+        top: b.top,
+        width: b.width,
+        height: b.height,
+      }),
+      palette_usage: s
+        .palette_usage
+        .map(|u| u.into_iter().map(|n| n as u32).collect()),
+    }
+  }
+}
+
+/// Finds up to `count` colors that best represent RGBA pixels, most common first.
+/// See `<Frame>.extractPalette` and `<Decoder>.extractPalette` to use frames or a whole GIF.
+///
+/// ### Notes:
+/// - Images with at most `count` distinct colors are counted exactly, others are quantized with NeuQuant.
+/// - `count` needs to be in the range 1-256.
+#[napi]
+pub fn extract_palette(
+  rgba: &[u8],
+  count: u32,
+  options: Option<PaletteOptions>,
+) -> napi::Result<Vec<PaletteColor>> {
+  extract_palette_from([rgba], count, options)
+}
+
+/// Indexed pixels with a palette rewritten by `compactPalette`.
+#[napi(object)]
+pub struct CompactedPalette {
+  /// The pixels, pointing into the new palette.
+  pub pixels: Buffer,
+  /// The new palette, padded to a power of two.
+  pub palette: Buffer,
+  /// The new transparent index. Missing if no pixel is transparent.
+  pub transparent: Option<u8>,
+}
+
+/// Drops unused palette entries and merges duplicate colors of indexed pixels, remapping the pixels and the transparent index.
+/// See `<Frame>.compactPalette` to compact a frame's palette.
+///
+/// ### Notes:
+/// - The palette is padded with black to the smallest power of two that GIF palettes can hold.
+/// - The transparent entry is kept apart from opaque entries of the same color, and dropped if no pixel uses it.
+/// @param order - How to order the colors that are kept. Defaults to `PaletteOrder.Original`.
+#[napi]
+pub fn compact_palette(
+  pixels: &[u8],
+  palette: &[u8],
+  transparent: Option<u8>,
+  order: Option<PaletteOrder>,
+) -> napi::Result<CompactedPalette> {
+  let mut pixels = pixels.to_vec();
+  let (palette, transparent) = palette::compact_palette(
+    &mut pixels,
+    palette,
+    transparent,
+    order.map(Into::into).unwrap_or_default(),
+  )?;
+  Ok(CompactedPalette {
+    pixels: pixels.into(),
+    palette: palette.into(),
+    transparent,
+  })
+}
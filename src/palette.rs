@@ -0,0 +1,310 @@
+//! Dominant color extraction, built on the same NeuQuant quantizer as the encoder, and palette compaction.
+
+use crate::quantize::check_speed;
+use crate::{apng, Error, Result};
+use std::collections::HashMap;
+
+/// Which pixels [`extract_palette`] leaves out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExtractOptions {
+  /// Skip pixels with an alpha below 128. Defaults to `true`.
+  pub ignore_transparent: bool,
+  /// Skip pixels whose channels are all within `tolerance` of 255.
+  pub ignore_white: bool,
+  /// Skip pixels whose channels are all within `tolerance` of 0.
+  pub ignore_black: bool,
+  /// How close to white or black a pixel needs to be to be skipped. Defaults to `16`.
+  pub tolerance: u8,
+  /// The NeuQuant speed, used when there are more distinct colors than requested. Defaults to `10`.
+  pub speed: i32,
+}
+
+impl Default for ExtractOptions {
+  fn default() -> Self {
+    Self {
+      ignore_transparent: true,
+      ignore_white: false,
+      ignore_black: false,
+      tolerance: 16,
+      speed: 10,
+    }
+  }
+}
+
+impl ExtractOptions {
+  fn skips(&self, p: &[u8]) -> bool {
+    let t = self.tolerance;
+    (self.ignore_transparent && p[3] < 128)
+      || (self.ignore_white && p[..3].iter().all(|&c| c >= 255 - t))
+      || (self.ignore_black && p[..3].iter().all(|&c| c <= t))
+  }
+}
+
+/// A color found by [`extract_palette`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DominantColor {
+  /// The color, as red, green and blue.
+  pub rgb: [u8; 3],
+  /// The number of pixels closest to this color.
+  pub count: usize,
+  /// The share of the counted pixels closest to this color, in the range 0-100.
+  pub percentage: f64,
+}
+
+/// A group of pixels, kept as channel sums so that groups can be merged.
+#[derive(Clone, Copy)]
+struct Cluster {
+  sum: [f64; 3],
+  count: usize,
+}
+
+impl Cluster {
+  fn mean(&self) -> [f64; 3] {
+    self.sum.map(|c| c / self.count as f64)
+  }
+
+  /// How much merging two clusters increases the squared error (Ward's criterion).
+  fn merge_cost(&self, other: &Cluster) -> f64 {
+    let (a, b) = (self.mean(), other.mean());
+    let d: f64 = (0..3).map(|i| (a[i] - b[i]).powi(2)).sum();
+    d * (self.count * other.count) as f64 / (self.count + other.count) as f64
+  }
+}
+
+/// Finds up to `count` colors that best represent the RGBA pixels of all `buffers` together, most common first.
+/// See [`PaletteBuilder`].
+pub fn extract_palette(
+  buffers: impl IntoIterator<Item = impl AsRef<[u8]>>,
+  count: usize,
+  options: &ExtractOptions,
+) -> Result<Vec<DominantColor>> {
+  let mut builder = PaletteBuilder::new(count, options)?;
+  for buffer in buffers {
+    builder.add_rgba(buffer.as_ref())?;
+  }
+  Ok(builder.finish())
+}
+
+/// Accumulates the pixels of any number of frames for [`extract_palette`], without keeping the frames.
+///
+/// The pixels are grouped by exact color, or by the NeuQuant palette they map to if there are more than 256 colors.
+/// The closest groups are then merged until `count` remain, and each color is the average of its pixels.
+#[derive(Debug, Clone)]
+pub struct PaletteBuilder {
+  count: usize,
+  options: ExtractOptions,
+  /// The number of counted pixels of each color.
+  colors: HashMap<[u8; 3], usize>,
+  /// Every `speed`th counted pixel, which NeuQuant learns from if there are more than 256 colors.
+  sample: Vec<u8>,
+  pixels: usize,
+}
+
+impl PaletteBuilder {
+  /// Checks that `count` is in the range 1-256 and that the speed is valid.
+  pub fn new(count: usize, options: &ExtractOptions) -> Result<Self> {
+    if !(1..=256).contains(&count) {
+      return Err(Error::invalid("Color count needs to be in the range 1-256"));
+    }
+    check_speed(options.speed)?;
+
+    Ok(Self {
+      count,
+      options: *options,
+      colors: HashMap::new(),
+      sample: Vec::new(),
+      pixels: 0,
+    })
+  }
+
+  /// Counts the RGBA pixels of a frame.
+  pub fn add_rgba(&mut self, rgba: &[u8]) -> Result<()> {
+    if !rgba.len().is_multiple_of(4) {
+      return Err(Error::invalid("RGBA length must be a multiple of 4"));
+    }
+
+    let speed = self.options.speed as usize;
+    for p in rgba.chunks_exact(4).filter(|p| !self.options.skips(p)) {
+      *self.colors.entry([p[0], p[1], p[2]]).or_insert(0) += 1;
+      if self.pixels.is_multiple_of(speed) {
+        // Alpha would otherwise pull colors apart in NeuQuant.
+        self.sample.extend_from_slice(&[p[0], p[1], p[2], 255]);
+      }
+      self.pixels += 1;
+    }
+    Ok(())
+  }
+
+  /// The colors found in the counted pixels, most common first.
+  pub fn finish(self) -> Vec<DominantColor> {
+    let mut clusters: Vec<Cluster> = if self.colors.len() <= 256 {
+      self
+        .colors
+        .into_iter()
+        .map(|(rgb, count)| Cluster {
+          sum: rgb.map(|c| c as f64 * count as f64),
+          count,
+        })
+        .collect()
+    } else {
+      // NeuQuant needs a full palette to converge, so it is only used to split the pixels into 256 groups.
+      // The sample is already thinned out by the speed, so NeuQuant learns from all of it.
+      let nq = color_quant::NeuQuant::new(1, 256, &self.sample);
+      let mut clusters = vec![
+        Cluster {
+          sum: [0.0; 3],
+          count: 0
+        };
+        256
+      ];
+      for (rgb, count) in self.colors {
+        let cluster = &mut clusters[nq.index_of(&[rgb[0], rgb[1], rgb[2], 255])];
+        for (s, c) in cluster.sum.iter_mut().zip(rgb) {
+          *s += c as f64 * count as f64;
+        }
+        cluster.count += count;
+      }
+      clusters.retain(|c| c.count > 0);
+      clusters
+    };
+
+    while clusters.len() > self.count {
+      let mut best = (f64::INFINITY, 0, 0);
+      for i in 0..clusters.len() {
+        for j in i + 1..clusters.len() {
+          let cost = clusters[i].merge_cost(&clusters[j]);
+          if cost < best.0 {
+            best = (cost, i, j);
+          }
+        }
+      }
+      let (_, i, j) = best;
+      let merged = clusters.swap_remove(j);
+      for k in 0..3 {
+        clusters[i].sum[k] += merged.sum[k];
+      }
+      clusters[i].count += merged.count;
+    }
+
+    let total = self.pixels as f64;
+    let mut colors: Vec<DominantColor> = clusters
+      .into_iter()
+      .map(|c| DominantColor {
+        rgb: c.mean().map(|v| v.round() as u8),
+        count: c.count,
+        percentage: c.count as f64 * 100.0 / total,
+      })
+      .collect();
+    colors.sort_unstable_by(|a, b| b.count.cmp(&a.count).then(a.rgb.cmp(&b.rgb)));
+    colors
+  }
+}
+
+/// How [`compact_palette`] orders the colors it keeps.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PaletteOrder {
+  /// Keep the colors in their original order.
+  #[default]
+  Original,
+  /// Darkest first.
+  Luminance,
+  /// Most used first. Runs of low indices tend to compress better with LZW.
+  Frequency,
+}
+
+/// A palette entry kept by [`compact_palette`], along with how many pixels use it.
+struct Entry {
+  rgb: [u8; 3],
+  transparent: bool,
+  count: usize,
+}
+
+/// Drops unused palette entries and merges duplicate colors, rewriting `pixels` to the new indices.
+/// Returns the new palette, padded with black to the smallest power of two that GIF palettes can hold, and the new transparent index.
+///
+/// The transparent entry is never merged with an opaque entry of the same color, and is dropped if no pixel uses it.
+pub fn compact_palette(
+  pixels: &mut [u8],
+  palette: &[u8],
+  transparent: Option<u8>,
+  order: PaletteOrder,
+) -> Result<(Vec<u8>, Option<u8>)> {
+  if !palette.len().is_multiple_of(3) || palette.len() > 256 * 3 {
+    return Err(Error::invalid(
+      "Palette length must be a multiple of 3, up to 768 bytes",
+    ));
+  }
+
+  let colors = palette.len() / 3;
+  let mut usage = [0usize; 256];
+  for &p in pixels.iter() {
+    usage[p as usize] += 1;
+  }
+  if let Some(i) = (colors..256).find(|&i| usage[i] > 0) {
+    return Err(Error::invalid(format!(
+      "Pixel index {} is outside of the palette with {} colors",
+      i, colors
+    )));
+  }
+
+  let mut entries: Vec<Entry> = Vec::new();
+  let mut slots = [0usize; 256];
+  let mut lookup: HashMap<([u8; 3], bool), usize> = HashMap::new();
+  for i in (0..colors).filter(|&i| usage[i] > 0) {
+    let rgb = [palette[i * 3], palette[i * 3 + 1], palette[i * 3 + 2]];
+    let is_transparent = transparent == Some(i as u8);
+    let slot = *lookup.entry((rgb, is_transparent)).or_insert_with(|| {
+      entries.push(Entry {
+        rgb,
+        transparent: is_transparent,
+        count: 0,
+      });
+      entries.len() - 1
+    });
+    entries[slot].count += usage[i];
+    slots[i] = slot;
+  }
+
+  let mut sorted: Vec<usize> = (0..entries.len()).collect();
+  match order {
+    PaletteOrder::Original => {}
+    PaletteOrder::Luminance => sorted.sort_by_key(|&e| {
+      let [r, g, b] = entries[e].rgb.map(u32::from);
+      299 * r + 587 * g + 114 * b
+    }),
+    PaletteOrder::Frequency => sorted.sort_by(|&a, &b| entries[b].count.cmp(&entries[a].count)),
+  }
+
+  let mut ranks = vec![0u8; entries.len()];
+  for (rank, &e) in sorted.iter().enumerate() {
+    ranks[e] = rank as u8;
+  }
+  for p in pixels.iter_mut() {
+    *p = ranks[slots[*p as usize]];
+  }
+
+  let size = entries.len().next_power_of_two().max(2);
+  let mut compacted = Vec::with_capacity(size * 3);
+  for &e in &sorted {
+    compacted.extend_from_slice(&entries[e].rgb);
+  }
+  compacted.resize(size * 3, 0);
+
+  let transparent = entries.iter().position(|e| e.transparent).map(|e| ranks[e]);
+  Ok((compacted, transparent))
+}
+
+/// Compacts the palette of an indexed frame, see [`compact_palette`].
+/// Frames without a palette of their own use `global_palette`, and get the compacted palette as their own.
+pub fn compact_frame_palette(
+  frame: &mut gif::Frame,
+  global_palette: Option<&[u8]>,
+  order: PaletteOrder,
+) -> Result<()> {
+  let palette = apng::frame_palette(frame, global_palette)?.to_vec();
+  let (palette, transparent) =
+    compact_palette(frame.buffer.to_mut(), &palette, frame.transparent, order)?;
+  frame.palette = Some(palette);
+  frame.transparent = transparent;
+  Ok(())
+}
@@ -0,0 +1,443 @@
+//! Bulk conversions between pixel layouts.
+//!
+//! Each conversion works on 16 bytes at a time with the [`wide`] vector types, which use the SIMD
+//! instructions of the target (SSE, NEON or WebAssembly SIMD) when they are enabled and plain arrays otherwise.
+//! The leftover pixels are handled by the scalar implementations in [`scalar`], which produce identical output.
+//! Trailing bytes that do not make up a whole pixel are ignored.
+
+use crate::{Error, Result};
+use bytemuck::cast;
+use std::borrow::Cow;
+use wide::{i8x16, u16x8, u32x4, u8x16};
+
+fn load(bytes: &[u8]) -> u8x16 {
+  let mut lanes = [0; 16];
+  lanes.copy_from_slice(&bytes[..16]);
+  u8x16::new(lanes)
+}
+
+fn shuffle(v: u8x16, indices: [i8; 16]) -> [u8; 16] {
+  cast(v.swizzle(i8x16::new(indices)))
+}
+
+/// Lanes holding the alpha channel of four RGBA pixels.
+const ALPHA: [u8; 16] = [0, 0, 0, 255, 0, 0, 0, 255, 0, 0, 0, 255, 0, 0, 0, 255];
+
+/// `x * y / 255`, rounded, for `x` and `y` up to 255.
+fn mul_div255(x: u16x8, y: u16x8) -> u16x8 {
+  let t = x * y + u16x8::splat(128);
+  (t + (t >> 8)) >> 8
+}
+
+/// Resolves indexed pixels against an RGB palette. Indices outside of the palette become opaque black.
+pub fn indexed_to_rgba(pixels: &[u8], palette: &[u8], transparent_index: Option<u8>) -> Vec<u8> {
+  let colors = scalar::color_table(palette, transparent_index);
+  let mut rgba = vec![0; pixels.len() * 4];
+
+  let mut src = pixels.chunks_exact(4);
+  let mut dst = rgba.chunks_exact_mut(16);
+  for (i, out) in (&mut src).zip(&mut dst) {
+    let v = u32x4::new([
+      colors[i[0] as usize],
+      colors[i[1] as usize],
+      colors[i[2] as usize],
+      colors[i[3] as usize],
+    ]);
+    out.copy_from_slice(&cast::<_, [u8; 16]>(v));
+  }
+  scalar::lookup(src.remainder(), &colors, dst.into_remainder());
+  rgba
+}
+
+/// Drops the alpha channel of RGBA pixels.
+pub fn rgba_to_rgb(rgba: &[u8]) -> Vec<u8> {
+  let mut rgb = vec![0; rgba.len() / 4 * 3];
+
+  let mut src = rgba.chunks_exact(16);
+  let mut dst = rgb.chunks_exact_mut(12);
+  for (p, out) in (&mut src).zip(&mut dst) {
+    let v = shuffle(
+      load(p),
+      [0, 1, 2, 4, 5, 6, 8, 9, 10, 12, 13, 14, -1, -1, -1, -1],
+    );
+    out.copy_from_slice(&v[..12]);
+  }
+  scalar::drop_alpha(src.remainder(), dst.into_remainder());
+  rgb
+}
+
+/// Adds an opaque alpha channel to RGB pixels.
+pub fn rgb_to_rgba(rgb: &[u8]) -> Vec<u8> {
+  let mut rgba = vec![0; rgb.len() / 3 * 4];
+
+  // Each step reads 16 bytes but only converts the first 12, so it stops while 16 bytes are left.
+  let steps = rgb.len().saturating_sub(4) / 12;
+  let alpha = u8x16::new(ALPHA);
+  for (i, out) in rgba.chunks_exact_mut(16).take(steps).enumerate() {
+    let v = shuffle(
+      load(&rgb[i * 12..]),
+      [0, 1, 2, -1, 3, 4, 5, -1, 6, 7, 8, -1, 9, 10, 11, -1],
+    );
+    out.copy_from_slice(&cast::<_, [u8; 16]>(u8x16::new(v) | alpha));
+  }
+  scalar::add_alpha(&rgb[steps * 12..], &mut rgba[steps * 16..]);
+  rgba
+}
+
+/// Swaps the red and blue channels of 4-byte pixels, converting BGRA to RGBA and back.
+pub fn swap_red_blue(pixels: &mut [u8]) {
+  let mut chunks = pixels.chunks_exact_mut(16);
+  for p in &mut chunks {
+    let v = shuffle(
+      load(p),
+      [2, 1, 0, 3, 6, 5, 4, 7, 10, 9, 8, 11, 14, 13, 12, 15],
+    );
+    p.copy_from_slice(&v);
+  }
+  scalar::swap_red_blue(chunks.into_remainder());
+}
+
+/// Moves the alpha channel of ARGB pixels to the end, converting them to RGBA.
+pub fn argb_to_rgba(pixels: &mut [u8]) {
+  let mut chunks = pixels.chunks_exact_mut(16);
+  for p in &mut chunks {
+    let v = shuffle(
+      load(p),
+      [1, 2, 3, 0, 5, 6, 7, 4, 9, 10, 11, 8, 13, 14, 15, 12],
+    );
+    p.copy_from_slice(&v);
+  }
+  scalar::argb_to_rgba(chunks.into_remainder());
+}
+
+/// Multiplies the color channels of RGBA pixels by their alpha.
+pub fn premultiply(rgba: &mut [u8]) {
+  let keep_alpha = u8x16::new(ALPHA);
+  let mut chunks = rgba.chunks_exact_mut(16);
+  for p in &mut chunks {
+    let v = load(p);
+    let a = u8x16::new(shuffle(
+      v,
+      [3, 3, 3, -1, 7, 7, 7, -1, 11, 11, 11, -1, 15, 15, 15, -1],
+    ));
+    let lo = mul_div255(u16x8::from_u8x16_low(v), u16x8::from_u8x16_low(a));
+    let hi = mul_div255(u16x8::from_u8x16_high(v), u16x8::from_u8x16_high(a));
+    let out = keep_alpha.blend(v, u8x16::narrow_i16x8(cast(lo), cast(hi)));
+    p.copy_from_slice(&cast::<_, [u8; 16]>(out));
+  }
+  scalar::premultiply(chunks.into_remainder());
+}
+
+/// Divides the color channels of premultiplied RGBA pixels by their alpha. Fully transparent pixels are left as they are.
+pub fn unpremultiply(rgba: &mut [u8]) {
+  let mut reciprocals = scalar::reciprocal_table();
+  // Like `reciprocals[255]`, this leaves the channels as they are.
+  reciprocals[0] = 1 << 16;
+  let max = u32x4::splat(255);
+  let half = u32x4::splat(1 << 15);
+
+  let mut chunks = rgba.chunks_exact_mut(16);
+  for p in &mut chunks {
+    let r = u32x4::new([3, 7, 11, 15].map(|i| reciprocals[p[i] as usize]));
+    // One channel of the four pixels at a time.
+    for c in 0..3 {
+      let v = u32x4::new([c, c + 4, c + 8, c + 12].map(|i| p[i] as u32));
+      let out = ((v * r + half) >> 16_u32).min(max).to_array();
+      for (i, v) in out.into_iter().enumerate() {
+        p[c + i * 4] = v as u8;
+      }
+    }
+  }
+  scalar::unpremultiply(chunks.into_remainder());
+}
+
+/// The layout of pixels passed to [`to_rgba`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum InputFormat {
+  /// Red, green, blue and straight alpha.
+  #[default]
+  Rgba,
+  /// Blue, green, red and alpha, as produced by most screen capture APIs.
+  Bgra,
+  /// Alpha, red, green and blue.
+  Argb,
+  /// RGBA with the color channels multiplied by alpha, as held by canvas surfaces.
+  RgbaPremultiplied,
+  /// One luminance byte per pixel.
+  Gray,
+  /// Luminance and alpha.
+  GrayAlpha,
+}
+
+impl InputFormat {
+  /// The number of bytes per pixel.
+  pub fn channels(self) -> usize {
+    match self {
+      InputFormat::Gray => 1,
+      InputFormat::GrayAlpha => 2,
+      _ => 4,
+    }
+  }
+}
+
+/// Converts `height` rows of `width` pixels in `format` to straight-alpha RGBA.
+///
+/// `stride` is the distance in bytes between the starts of two rows, for buffers with padding after each row.
+/// It defaults to the length of a row, and the last row may leave its padding out.
+pub fn to_rgba(
+  buffer: &[u8],
+  width: usize,
+  height: usize,
+  format: InputFormat,
+  stride: Option<usize>,
+) -> Result<Vec<u8>> {
+  let too_large = || Error::invalid("Buffer dimensions are too large");
+  let row = width.checked_mul(format.channels()).ok_or_else(too_large)?;
+  let stride = stride.unwrap_or(row);
+  if stride < row {
+    return Err(Error::invalid(format!(
+      "Stride of {} bytes is shorter than a row of {} bytes",
+      stride, row
+    )));
+  }
+  let max = stride.checked_mul(height).ok_or_else(too_large)?;
+  // Cannot overflow now that `stride * height` does not and `row <= stride`
+  let min = if height == 0 { 0 } else { max - stride + row };
+  if buffer.len() < min || buffer.len() > max {
+    return Err(Error::invalid(format!(
+      "Buffer size mismatch: expected {} bytes for {} rows with a stride of {}, got {}",
+      min,
+      height,
+      stride,
+      buffer.len()
+    )));
+  }
+
+  let packed = if stride == row {
+    Cow::Borrowed(&buffer[..min])
+  } else {
+    Cow::Owned(
+      buffer
+        .chunks(stride)
+        .flat_map(|r| &r[..row])
+        .copied()
+        .collect(),
+    )
+  };
+
+  Ok(match format {
+    InputFormat::Rgba => packed.into_owned(),
+    InputFormat::Bgra => {
+      let mut rgba = packed.into_owned();
+      swap_red_blue(&mut rgba);
+      rgba
+    }
+    InputFormat::Argb => {
+      let mut rgba = packed.into_owned();
+      argb_to_rgba(&mut rgba);
+      rgba
+    }
+    InputFormat::RgbaPremultiplied => {
+      let mut rgba = packed.into_owned();
+      unpremultiply(&mut rgba);
+      rgba
+    }
+    InputFormat::Gray => packed.iter().flat_map(|&v| [v, v, v, 255]).collect(),
+    InputFormat::GrayAlpha => packed
+      .chunks_exact(2)
+      .flat_map(|p| [p[0], p[0], p[0], p[1]])
+      .collect(),
+  })
+}
+
+/// One pixel at a time implementations of the conversions, used for the pixels left over by the SIMD loops.
+pub mod scalar {
+  /// The RGBA color of every index as a native-endian `u32`, with out-of-range indices opaque black.
+  pub(crate) fn color_table(palette: &[u8], transparent_index: Option<u8>) -> [u32; 256] {
+    let mut colors = [u32::from_ne_bytes([0, 0, 0, 255]); 256];
+    for (i, rgb) in palette.chunks_exact(3).take(256).enumerate() {
+      let a = if Some(i) == transparent_index.map(usize::from) {
+        0
+      } else {
+        255
+      };
+      colors[i] = u32::from_ne_bytes([rgb[0], rgb[1], rgb[2], a]);
+    }
+    colors
+  }
+
+  pub(crate) fn lookup(pixels: &[u8], colors: &[u32; 256], rgba: &mut [u8]) {
+    for (&i, out) in pixels.iter().zip(rgba.chunks_exact_mut(4)) {
+      out.copy_from_slice(&colors[i as usize].to_ne_bytes());
+    }
+  }
+
+  /// `round(255 * 65536 / a)`, so that `c * 255 / a` is `(c * table[a] + 32768) >> 16`.
+  pub(crate) fn reciprocal_table() -> [u32; 256] {
+    let mut table = [0; 256];
+    for (a, r) in table.iter_mut().enumerate().skip(1) {
+      *r = (255 * 65536 + a as u32 / 2) / a as u32;
+    }
+    table
+  }
+
+  pub(crate) fn drop_alpha(rgba: &[u8], rgb: &mut [u8]) {
+    for (p, out) in rgba.chunks_exact(4).zip(rgb.chunks_exact_mut(3)) {
+      out.copy_from_slice(&p[..3]);
+    }
+  }
+
+  pub(crate) fn add_alpha(rgb: &[u8], rgba: &mut [u8]) {
+    for (p, out) in rgb.chunks_exact(3).zip(rgba.chunks_exact_mut(4)) {
+      out.copy_from_slice(&[p[0], p[1], p[2], 255]);
+    }
+  }
+
+  /// See [`super::indexed_to_rgba`].
+  pub fn indexed_to_rgba(pixels: &[u8], palette: &[u8], transparent_index: Option<u8>) -> Vec<u8> {
+    let mut rgba = vec![0; pixels.len() * 4];
+    lookup(pixels, &color_table(palette, transparent_index), &mut rgba);
+    rgba
+  }
+
+  /// See [`super::rgba_to_rgb`].
+  pub fn rgba_to_rgb(rgba: &[u8]) -> Vec<u8> {
+    let mut rgb = vec![0; rgba.len() / 4 * 3];
+    drop_alpha(rgba, &mut rgb);
+    rgb
+  }
+
+  /// See [`super::rgb_to_rgba`].
+  pub fn rgb_to_rgba(rgb: &[u8]) -> Vec<u8> {
+    let mut rgba = vec![0; rgb.len() / 3 * 4];
+    add_alpha(rgb, &mut rgba);
+    rgba
+  }
+
+  /// See [`super::swap_red_blue`].
+  pub fn swap_red_blue(pixels: &mut [u8]) {
+    for p in pixels.chunks_exact_mut(4) {
+      p.swap(0, 2);
+    }
+  }
+
+  /// See [`super::argb_to_rgba`].
+  pub fn argb_to_rgba(pixels: &mut [u8]) {
+    for p in pixels.chunks_exact_mut(4) {
+      p.rotate_left(1);
+    }
+  }
+
+  /// See [`super::premultiply`].
+  pub fn premultiply(rgba: &mut [u8]) {
+    for p in rgba.chunks_exact_mut(4) {
+      let a = p[3] as u16;
+      for c in &mut p[..3] {
+        let t = *c as u16 * a + 128;
+        *c = ((t + (t >> 8)) >> 8) as u8;
+      }
+    }
+  }
+
+  /// See [`super::unpremultiply`].
+  pub fn unpremultiply(rgba: &mut [u8]) {
+    let reciprocals = reciprocal_table();
+    for p in rgba.chunks_exact_mut(4) {
+      let a = p[3];
+      if a == 0 || a == 255 {
+        continue;
+      }
+      for c in &mut p[..3] {
+        *c = ((*c as u32 * reciprocals[a as usize] + (1 << 15)) >> 16).min(255) as u8;
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Pseudo-random bytes, with lengths that leave a remainder after the 16-byte steps.
+  fn noise(len: usize) -> Vec<u8> {
+    let mut state = 0x2545_f491_u32;
+    (0..len)
+      .map(|_| {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        (state >> 24) as u8
+      })
+      .collect()
+  }
+
+  const LENGTHS: [usize; 6] = [0, 3, 15, 16, 61, 1027];
+
+  #[test]
+  fn conversions_match_scalar() {
+    for len in LENGTHS {
+      let bytes = noise(len);
+      let palette = noise(3 * 200);
+      assert_eq!(
+        indexed_to_rgba(&bytes, &palette, Some(7)),
+        scalar::indexed_to_rgba(&bytes, &palette, Some(7))
+      );
+      assert_eq!(rgba_to_rgb(&bytes), scalar::rgba_to_rgb(&bytes));
+      assert_eq!(rgb_to_rgba(&bytes), scalar::rgb_to_rgba(&bytes));
+    }
+  }
+
+  #[test]
+  fn in_place_conversions_match_scalar() {
+    type InPlace = fn(&mut [u8]);
+    let ops: [(InPlace, InPlace); 4] = [
+      (swap_red_blue, scalar::swap_red_blue),
+      (argb_to_rgba, scalar::argb_to_rgba),
+      (premultiply, scalar::premultiply),
+      (unpremultiply, scalar::unpremultiply),
+    ];
+    for len in LENGTHS {
+      for (simd, fallback) in ops {
+        let (mut a, mut b) = (noise(len), noise(len));
+        simd(&mut a);
+        fallback(&mut b);
+        assert_eq!(a, b);
+      }
+    }
+  }
+
+  #[test]
+  fn premultiply_rounds() {
+    let mut rgba = [255, 128, 0, 128, 10, 20, 30, 0, 1, 2, 3, 255];
+    premultiply(&mut rgba);
+    assert_eq!(rgba, [128, 64, 0, 128, 0, 0, 0, 0, 1, 2, 3, 255]);
+
+    unpremultiply(&mut rgba);
+    assert_eq!(rgba, [255, 128, 0, 128, 0, 0, 0, 0, 1, 2, 3, 255]);
+  }
+
+  #[test]
+  fn to_rgba_skips_row_padding() {
+    // Two rows of two BGRA pixels, the first followed by two bytes of padding
+    let bgra = [3, 2, 1, 4, 7, 6, 5, 8, 0, 0, 13, 12, 11, 14, 17, 16, 15, 18];
+    assert_eq!(
+      to_rgba(&bgra, 2, 2, InputFormat::Bgra, Some(10)).unwrap(),
+      [1, 2, 3, 4, 5, 6, 7, 8, 11, 12, 13, 14, 15, 16, 17, 18]
+    );
+    assert_eq!(
+      to_rgba(&[10, 128, 20, 255], 2, 1, InputFormat::GrayAlpha, None).unwrap(),
+      [10, 10, 10, 128, 20, 20, 20, 255]
+    );
+    assert!(to_rgba(&bgra, 2, 2, InputFormat::Bgra, Some(6)).is_err());
+    assert!(to_rgba(&bgra, 2, 2, InputFormat::Bgra, None).is_err());
+    assert!(to_rgba(
+      &bgra,
+      2,
+      usize::MAX / 4,
+      InputFormat::Bgra,
+      Some(usize::MAX / 2)
+    )
+    .is_err());
+    assert!(to_rgba(&bgra, usize::MAX / 2, 2, InputFormat::Bgra, None).is_err());
+  }
+}
@@ -0,0 +1,59 @@
+use crate::{Error, Result};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// A flag that asks a running operation to stop before its next frame.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Asks the operations holding this token to stop. They return [`Error::Aborted`].
+  pub fn cancel(&self) {
+    self.0.store(true, Ordering::Relaxed);
+  }
+
+  pub fn is_cancelled(&self) -> bool {
+    self.0.load(Ordering::Relaxed)
+  }
+}
+
+/// How far a long-running operation has got.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Progress {
+  /// Frames processed so far.
+  pub frames: usize,
+  /// The number of frames to process, when known up front.
+  pub total_frames: Option<usize>,
+  /// Input bytes consumed so far, for operations reading a GIF.
+  pub bytes: Option<usize>,
+  /// The size of the input in bytes, for operations reading a GIF.
+  pub total_bytes: Option<usize>,
+}
+
+/// Cancellation and progress hooks for long-running operations.
+/// The progress callback may be called from worker threads.
+#[derive(Clone, Copy, Default)]
+pub struct Control<'a> {
+  pub cancel: Option<&'a CancelToken>,
+  pub on_progress: Option<&'a (dyn Fn(Progress) + Sync)>,
+}
+
+impl Control<'_> {
+  /// Returns [`Error::Aborted`] once the operation has been cancelled.
+  pub fn check(&self) -> Result<()> {
+    match self.cancel {
+      Some(token) if token.is_cancelled() => Err(Error::Aborted),
+      _ => Ok(()),
+    }
+  }
+
+  pub fn report(&self, progress: Progress) {
+    if let Some(on_progress) = self.on_progress {
+      on_progress(progress);
+    }
+  }
+}
@@ -0,0 +1,232 @@
+use crate::lzw;
+use crate::progress::{Control, Progress};
+use crate::{Error, Result};
+use rayon::prelude::*;
+use rayon::ThreadPoolBuilder;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Checks that a NeuQuant speed is in the range 1-30.
+pub fn check_speed(speed: i32) -> Result<()> {
+  if !(1..=30).contains(&speed) {
+    return Err(Error::invalid("Speed needs to be in the range 1-30"));
+  }
+  Ok(())
+}
+
+fn check_size(width: u16, height: u16, len: usize, channels: usize) -> Result<()> {
+  if len != width as usize * height as usize * channels {
+    return Err(Error::invalid("Buffer size mismatch"));
+  }
+  Ok(())
+}
+
+/// Quantizes RGBA pixels to an indexed frame with a palette of up to 256 colors.
+/// Pixels with an alpha of `0` become transparent, all others opaque. `rgba` is modified in place.
+pub fn quantize_rgba(
+  width: u16,
+  height: u16,
+  rgba: &mut [u8],
+  speed: i32,
+) -> Result<gif::Frame<'static>> {
+  check_speed(speed)?;
+  check_size(width, height, rgba.len(), 4)?;
+  Ok(gif::Frame::from_rgba_speed(width, height, rgba, speed))
+}
+
+/// How [`quantize_rgba_with_alpha`] decides which pixels become transparent, since GIF pixels are either fully opaque or fully transparent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AlphaOptions {
+  /// Pixels with an alpha below this become transparent, all others opaque. The default of `1` only makes fully transparent pixels transparent.
+  pub threshold: u8,
+  /// Replace the threshold with a 4x4 ordered dither, so that the share of opaque pixels follows their alpha and soft edges fade out.
+  pub dither: bool,
+  /// Blend semi-transparent pixels that stay opaque over this color instead of dropping their alpha.
+  pub matte: Option<[u8; 3]>,
+  /// Reserve this palette index for transparent pixels. Opaque pixels never use it, even if the frame has no transparent pixels.
+  pub transparent_index: Option<u8>,
+}
+
+impl Default for AlphaOptions {
+  fn default() -> Self {
+    Self {
+      threshold: 1,
+      dither: false,
+      matte: None,
+      transparent_index: None,
+    }
+  }
+}
+
+const BAYER_4X4: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// Makes every pixel of `rgba` either fully opaque or fully transparent, as described by `options`.
+/// Transparent pixels are set to `[0, 0, 0, 0]`.
+pub fn apply_alpha(rgba: &mut [u8], width: u16, options: &AlphaOptions) {
+  let width = (width as usize).max(1);
+  for (i, pixel) in rgba.chunks_exact_mut(4).enumerate() {
+    let a = pixel[3];
+    let opaque = if options.dither {
+      // Levels from 8 to 248, so fully transparent and fully opaque pixels are never flipped.
+      a > BAYER_4X4[i / width % 4][i % width % 4] * 16 + 8
+    } else {
+      a >= options.threshold
+    };
+
+    if !opaque {
+      pixel.copy_from_slice(&[0, 0, 0, 0]);
+      continue;
+    }
+    if let Some(matte) = options.matte {
+      for (c, m) in pixel[..3].iter_mut().zip(matte) {
+        *c = ((*c as u32 * a as u32 + m as u32 * (255 - a as u32) + 127) / 255) as u8;
+      }
+    }
+    pixel[3] = 255;
+  }
+}
+
+/// Quantizes RGBA pixels like [`quantize_rgba`], handling semi-transparent pixels as described by `options`.
+/// `rgba` is modified in place.
+pub fn quantize_rgba_with_alpha(
+  width: u16,
+  height: u16,
+  rgba: &mut [u8],
+  speed: i32,
+  options: &AlphaOptions,
+) -> Result<gif::Frame<'static>> {
+  check_speed(speed)?;
+  check_size(width, height, rgba.len(), 4)?;
+  if *options == AlphaOptions::default() {
+    return Ok(gif::Frame::from_rgba_speed(width, height, rgba, speed));
+  }
+
+  apply_alpha(rgba, width, options);
+  let Some(index) = options.transparent_index else {
+    return Ok(gif::Frame::from_rgba_speed(width, height, rgba, speed));
+  };
+
+  // Build a palette of at most 255 colors from the opaque pixels, then insert the transparent color at `index`.
+  let opaque: Vec<u8> = rgba
+    .chunks_exact(4)
+    .filter(|p| p[3] == 255)
+    .flatten()
+    .copied()
+    .collect();
+  let mut colors = HashMap::new();
+  let exact = opaque.chunks_exact(4).all(|p| {
+    let next = colors.len();
+    colors.entry([p[0], p[1], p[2]]).or_insert(next);
+    colors.len() <= 255
+  });
+
+  let nq = (!exact).then(|| color_quant::NeuQuant::new(speed, 255, &opaque));
+  let mut palette = match &nq {
+    Some(nq) => nq.color_map_rgb(),
+    None => {
+      let mut palette = vec![0; colors.len() * 3];
+      for (rgb, &i) in &colors {
+        palette[i * 3..i * 3 + 3].copy_from_slice(rgb);
+      }
+      palette
+    }
+  };
+  let lookup = |p: &[u8]| match &nq {
+    Some(nq) => nq.index_of(p),
+    None => colors[&[p[0], p[1], p[2]]],
+  };
+
+  let index = index as usize;
+  if palette.len() < index * 3 {
+    palette.resize(index * 3, 0);
+  }
+  palette.splice(index * 3..index * 3, [0, 0, 0]);
+
+  let buffer = rgba
+    .chunks_exact(4)
+    .map(|p| match p[3] {
+      0 => index as u8,
+      _ => {
+        let i = lookup(p);
+        (if i >= index { i + 1 } else { i }) as u8
+      }
+    })
+    .collect();
+
+  Ok(gif::Frame {
+    width,
+    height,
+    buffer: Cow::Owned(buffer),
+    palette: Some(palette),
+    transparent: Some(index as u8),
+    ..gif::Frame::default()
+  })
+}
+
+/// Quantizes RGB pixels to an indexed frame with a palette of up to 256 colors.
+pub fn quantize_rgb(
+  width: u16,
+  height: u16,
+  rgb: &[u8],
+  speed: i32,
+) -> Result<gif::Frame<'static>> {
+  check_speed(speed)?;
+  check_size(width, height, rgb.len(), 3)?;
+  Ok(gif::Frame::from_rgb_speed(width, height, rgb, speed))
+}
+
+/// Quantizes and LZW-compresses RGBA frames in parallel, for use with `gif::Encoder::write_lzw_pre_encoded_frame`.
+/// The frames are returned in the order of `buffers`. `threads` defaults to the number of CPU cores.
+/// Progress is reported as each frame is done, possibly from several threads at once.
+#[allow(clippy::too_many_arguments)]
+pub fn quantize_rgba_batch(
+  width: u16,
+  height: u16,
+  buffers: Vec<&mut [u8]>,
+  speed: i32,
+  global_palette: Option<&[u8]>,
+  lossy: u32,
+  threads: Option<usize>,
+  control: Control<'_>,
+) -> Result<Vec<gif::Frame<'static>>> {
+  check_speed(speed)?;
+  lzw::check_lossy(lossy)?;
+  if let Some(i) = buffers
+    .iter()
+    .position(|b| check_size(width, height, b.len(), 4).is_err())
+  {
+    return Err(Error::invalid(format!(
+      "Buffer size mismatch at frame {}",
+      i
+    )));
+  }
+
+  let total = buffers.len();
+  let done = &AtomicUsize::new(0);
+  let encode = move || {
+    buffers
+      .into_par_iter()
+      .map(|buf| {
+        control.check()?;
+        let mut frame = gif::Frame::from_rgba_speed(width, height, buf, speed);
+        lzw::compress_frame(&mut frame, global_palette, lossy);
+        control.report(Progress {
+          frames: done.fetch_add(1, Ordering::Relaxed) + 1,
+          total_frames: Some(total),
+          ..Progress::default()
+        });
+        Ok(frame)
+      })
+      .collect::<Result<Vec<_>>>()
+  };
+
+  match threads {
+    Some(threads) => ThreadPoolBuilder::new()
+      .num_threads(threads)
+      .build()
+      .map_err(|e| Error::Encoding(format!("Failed to create a thread pool: {}", e)))?
+      .install(encode),
+    None => encode(),
+  }
+}
@@ -0,0 +1,202 @@
+//! Finds where the header and the frames of a GIF end while its bytes arrive in chunks, without decoding anything.
+
+/// Length of the signature and logical screen descriptor.
+const SCREEN_LEN: usize = 13;
+/// Length of an image descriptor, after the image separator.
+const IMAGE_DESCRIPTOR_LEN: usize = 9;
+
+/// What comes after skipped bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Then {
+  BlockStart,
+  MinCodeSize,
+  SubBlock { image: bool },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+  /// Reading the signature and logical screen descriptor, with the number of bytes read so far.
+  Screen(usize),
+  /// Reading an image descriptor, with the number of bytes read so far.
+  ImageDescriptor(usize),
+  /// Skipping palettes and sub-block data.
+  Skip(usize, Then),
+  BlockStart,
+  ExtensionLabel,
+  MinCodeSize,
+  /// Expecting the length of the next sub-block of an extension or image data, or `0` at the end of the block.
+  SubBlock {
+    image: bool,
+  },
+  Trailer,
+}
+
+impl From<Then> for State {
+  fn from(then: Then) -> Self {
+    match then {
+      Then::BlockStart => State::BlockStart,
+      Then::MinCodeSize => State::MinCodeSize,
+      Then::SubBlock { image } => State::SubBlock { image },
+    }
+  }
+}
+
+/// Length of the color table announced by the flags of a screen or image descriptor.
+fn palette_len(flags: u8) -> usize {
+  if flags & 0x80 != 0 {
+    3 << ((flags & 0b111) + 1)
+  } else {
+    0
+  }
+}
+
+/// Follows the block structure of a GIF as its bytes arrive, so that a `gif::Decoder` reading the same bytes
+/// is only asked for the header and frames once they are complete and never runs out of data.
+///
+/// Unknown blocks are skipped like extensions. Malformed data is left for the decoder to report.
+#[derive(Debug, Clone)]
+pub struct Scanner {
+  state: State,
+  /// The flags of the logical screen descriptor.
+  flags: u8,
+  /// The first block that is not an extension has started.
+  first_block: bool,
+  header: bool,
+  frames: usize,
+}
+
+impl Default for Scanner {
+  fn default() -> Self {
+    Self {
+      state: State::Screen(0),
+      flags: 0,
+      first_block: false,
+      header: false,
+      frames: 0,
+    }
+  }
+}
+
+impl Scanner {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Scans the next chunk of the GIF.
+  pub fn update(&mut self, mut chunk: &[u8]) {
+    while let Some((&b, rest)) = chunk.split_first() {
+      if let State::Skip(left, then) = self.state {
+        let n = left.min(chunk.len());
+        chunk = &chunk[n..];
+        self.state = if n == left {
+          then.into()
+        } else {
+          State::Skip(left - n, then)
+        };
+        continue;
+      }
+
+      // `gif::DecodeOptions::read_info` looks at one byte past the start of the first image or the trailer.
+      self.header |= self.first_block;
+      chunk = rest;
+      self.state = match self.state {
+        State::Screen(i) => {
+          if i == 10 {
+            self.flags = b;
+          }
+          if i + 1 == SCREEN_LEN {
+            State::Skip(palette_len(self.flags), Then::BlockStart)
+          } else {
+            State::Screen(i + 1)
+          }
+        }
+        State::BlockStart => {
+          self.first_block |= b != 0x21;
+          match b {
+            0x21 => State::ExtensionLabel,
+            0x2C => State::ImageDescriptor(0),
+            0x3B => State::Trailer,
+            _ => State::SubBlock { image: false },
+          }
+        }
+        State::ExtensionLabel => State::SubBlock { image: false },
+        State::ImageDescriptor(i) => {
+          if i + 1 == IMAGE_DESCRIPTOR_LEN {
+            State::Skip(palette_len(b), Then::MinCodeSize)
+          } else {
+            State::ImageDescriptor(i + 1)
+          }
+        }
+        State::MinCodeSize => State::SubBlock { image: true },
+        State::SubBlock { image } if b == 0 => {
+          self.frames += image as usize;
+          State::BlockStart
+        }
+        State::SubBlock { image } => State::Skip(b as usize, Then::SubBlock { image }),
+        State::Trailer => State::Trailer,
+        State::Skip(..) => unreachable!(),
+      };
+    }
+  }
+
+  /// Whether enough bytes have arrived to read the header with `gif::DecodeOptions::read_info`.
+  pub fn header_complete(&self) -> bool {
+    self.header
+  }
+
+  /// The number of frames whose data has completely arrived.
+  pub fn frames(&self) -> usize {
+    self.frames
+  }
+
+  /// Whether the trailer that ends the GIF has arrived.
+  pub fn finished(&self) -> bool {
+    self.state == State::Trailer
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const GIF: &[u8] = include_bytes!("../__test__/encoderinput.gif");
+
+  /// Reads the header and `frames` frames from exactly the bytes that have arrived.
+  fn decode(arrived: &[u8], frames: usize) {
+    let mut decoder = gif::DecodeOptions::new().read_info(arrived).unwrap();
+    for _ in 0..frames {
+      decoder.read_next_frame().unwrap().unwrap();
+    }
+  }
+
+  #[test]
+  fn header_and_frames_are_complete_once_their_bytes_have_arrived() {
+    for chunk_size in [1, 7, 4096, GIF.len()] {
+      let mut scanner = Scanner::new();
+      let (mut arrived, mut header, mut frames) = (0, false, 0);
+      for chunk in GIF.chunks(chunk_size) {
+        scanner.update(chunk);
+        arrived += chunk.len();
+
+        if scanner.header_complete() != header || scanner.frames() != frames {
+          header = scanner.header_complete();
+          frames = scanner.frames();
+          decode(&GIF[..arrived], frames);
+        }
+      }
+
+      assert!(header);
+      assert_eq!(frames, 3);
+      assert!(scanner.finished());
+    }
+  }
+
+  #[test]
+  fn header_needs_the_first_image() {
+    let mut scanner = Scanner::new();
+    scanner.update(&GIF[..SCREEN_LEN]);
+    assert!(!scanner.header_complete());
+    assert_eq!(scanner.frames(), 0);
+    assert!(!scanner.finished());
+  }
+}
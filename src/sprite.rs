@@ -0,0 +1,286 @@
+use crate::{Error, Result};
+
+/// A frame's placement in a sprite sheet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SpriteRect {
+  /// Horizontal position of the frame in the sheet.
+  pub x: u32,
+  /// Vertical position of the frame in the sheet.
+  pub y: u32,
+  /// Width of the frame in the sheet.
+  pub width: u32,
+  /// Height of the frame in the sheet.
+  pub height: u32,
+  /// Horizontal offset of the frame within the animation, non-zero for trimmed frames.
+  pub left: u32,
+  /// Vertical offset of the frame within the animation, non-zero for trimmed frames.
+  pub top: u32,
+  /// The delay for this frame in units of 10ms.
+  pub delay: u32,
+}
+
+/// Layout options for [`build_sheet`].
+#[derive(Debug, Clone, Default)]
+pub struct SheetOptions {
+  /// Number of columns of the grid. Defaults to a roughly square grid. Ignored when `packed` is set.
+  pub columns: Option<u32>,
+  /// Space in pixels between frames and around the edge of the sheet.
+  pub padding: u32,
+  /// Background color of the sheet as RGBA.
+  pub background: [u8; 4],
+  /// Trim transparent borders from every frame and pack them in rows instead of a uniform grid.
+  pub packed: bool,
+}
+
+/// An RGBA sprite sheet.
+#[derive(Debug, Clone)]
+pub struct Sheet {
+  pub width: u32,
+  pub height: u32,
+  /// Size of the animation the frames were taken from.
+  pub frame_width: u32,
+  pub frame_height: u32,
+  pub rgba: Vec<u8>,
+  pub frames: Vec<SpriteRect>,
+}
+
+/// Creates the placements of `count` frames of `frame_width`x`frame_height` laid out in a uniform grid.
+pub fn grid(
+  count: u32,
+  frame_width: u32,
+  frame_height: u32,
+  columns: Option<u32>,
+  padding: u32,
+  delay: u32,
+) -> Vec<SpriteRect> {
+  let columns = columns
+    .unwrap_or_else(|| (count as f64).sqrt().ceil() as u32)
+    .max(1);
+
+  (0..count)
+    .map(|i| SpriteRect {
+      x: (i % columns)
+        .saturating_mul(frame_width.saturating_add(padding))
+        .saturating_add(padding),
+      y: (i / columns)
+        .saturating_mul(frame_height.saturating_add(padding))
+        .saturating_add(padding),
+      width: frame_width,
+      height: frame_height,
+      left: 0,
+      top: 0,
+      delay,
+    })
+    .collect()
+}
+
+/// Bounding box of the non-transparent pixels as `(left, top, width, height)`, at least 1x1.
+fn trim(rgba: &[u8], width: u32, height: u32) -> (u32, u32, u32, u32) {
+  let (mut left, mut top, mut right, mut bottom) = (width, height, 0, 0);
+  for (i, pixel) in rgba.chunks_exact(4).enumerate() {
+    if pixel[3] != 0 {
+      let (x, y) = (i as u32 % width, i as u32 / width);
+      left = left.min(x);
+      top = top.min(y);
+      right = right.max(x + 1);
+      bottom = bottom.max(y + 1);
+    }
+  }
+
+  if right == 0 {
+    (0, 0, 1, 1)
+  } else {
+    (left, top, right - left, bottom - top)
+  }
+}
+
+/// Places trimmed frames left to right in rows, starting a new row once a row grows wider than the target width.
+fn pack(rects: &[(u32, u32, u32, u32)], padding: u32) -> Vec<(u32, u32)> {
+  let area: u64 = rects
+    .iter()
+    .map(|r| (r.2 + padding) as u64 * (r.3 + padding) as u64)
+    .sum();
+  let max_width = rects.iter().map(|r| r.2).max().unwrap_or(0);
+  let target = ((area as f64).sqrt().ceil() as u32).max(max_width + padding * 2);
+
+  let (mut x, mut y, mut row_height) = (padding, padding, 0);
+  rects
+    .iter()
+    .map(|r| {
+      if x > padding && x.saturating_add(r.2 + padding) > target {
+        x = padding;
+        y = y.saturating_add(row_height + padding);
+        row_height = 0;
+      }
+      let position = (x, y);
+      x = x.saturating_add(r.2 + padding);
+      row_height = row_height.max(r.3);
+      position
+    })
+    .collect()
+}
+
+impl Sheet {
+  /// JSON atlas in the common `frames`/`meta` layout, with durations in milliseconds.
+  pub fn atlas_json(&self) -> String {
+    let entries = self
+      .frames
+      .iter()
+      .enumerate()
+      .map(|(i, f)| {
+        format!(
+          concat!(
+            r#"{{"filename":"{}","frame":{{"x":{},"y":{},"w":{},"h":{}}},"rotated":false,"trimmed":{},"#,
+            r#""spriteSourceSize":{{"x":{},"y":{},"w":{},"h":{}}},"sourceSize":{{"w":{},"h":{}}},"duration":{}}}"#
+          ),
+          i,
+          f.x,
+          f.y,
+          f.width,
+          f.height,
+          f.width != self.frame_width || f.height != self.frame_height,
+          f.left,
+          f.top,
+          f.width,
+          f.height,
+          self.frame_width,
+          self.frame_height,
+          f.delay * 10
+        )
+      })
+      .collect::<Vec<_>>()
+      .join(",");
+
+    format!(
+      r#"{{"frames":[{}],"meta":{{"app":"gifsx","format":"RGBA8888","size":{{"w":{},"h":{}}},"scale":"1"}}}}"#,
+      entries, self.width, self.height
+    )
+  }
+}
+
+/// Lays out composited RGBA frames of `frame_width`x`frame_height`, given with their delays, as a sprite sheet.
+pub fn build_sheet(
+  frame_width: u16,
+  frame_height: u16,
+  frames: &[(u16, Vec<u8>)],
+  options: &SheetOptions,
+) -> Result<Sheet> {
+  let padding = options.padding;
+  let (fw, fh) = (frame_width as u32, frame_height as u32);
+  if padding > u16::MAX as u32 {
+    return Err(Error::invalid(format!(
+      "Padding of {} is too large",
+      padding
+    )));
+  }
+
+  let placements = if options.packed {
+    let rects: Vec<_> = frames.iter().map(|(_, rgba)| trim(rgba, fw, fh)).collect();
+    pack(&rects, padding)
+      .into_iter()
+      .zip(&rects)
+      .zip(frames)
+      .map(
+        |(((x, y), &(left, top, width, height)), (delay, _))| SpriteRect {
+          x,
+          y,
+          width,
+          height,
+          left,
+          top,
+          delay: *delay as u32,
+        },
+      )
+      .collect()
+  } else {
+    let mut rects = grid(frames.len() as u32, fw, fh, options.columns, padding, 0);
+    for (rect, (delay, _)) in rects.iter_mut().zip(frames) {
+      rect.delay = *delay as u32;
+    }
+    rects
+  };
+
+  let (mut width, mut height) = (0, 0);
+  for f in &placements {
+    let right = f
+      .x
+      .checked_add(f.width)
+      .and_then(|r| r.checked_add(padding));
+    let bottom = f
+      .y
+      .checked_add(f.height)
+      .and_then(|b| b.checked_add(padding));
+    let (Some(right), Some(bottom)) = (right, bottom) else {
+      return Err(Error::invalid("Sprite sheet is too large"));
+    };
+    width = width.max(right);
+    height = height.max(bottom);
+  }
+  if width > u16::MAX as u32 || height > u16::MAX as u32 {
+    return Err(Error::invalid(format!(
+      "Sprite sheet of {}x{} is too large",
+      width, height
+    )));
+  }
+
+  let mut rgba: Vec<u8> = options
+    .background
+    .iter()
+    .copied()
+    .cycle()
+    .take(width as usize * height as usize * 4)
+    .collect();
+
+  for (placement, (_, frame)) in placements.iter().zip(frames) {
+    let row = placement.width as usize * 4;
+    for y in 0..placement.height as usize {
+      let src = ((placement.top as usize + y) * fw as usize + placement.left as usize) * 4;
+      let dst = ((placement.y as usize + y) * width as usize + placement.x as usize) * 4;
+      for (d, s) in rgba[dst..dst + row]
+        .chunks_exact_mut(4)
+        .zip(frame[src..src + row].chunks_exact(4))
+      {
+        if s[3] != 0 {
+          d.copy_from_slice(s);
+        }
+      }
+    }
+  }
+
+  Ok(Sheet {
+    width,
+    height,
+    frame_width: fw,
+    frame_height: fh,
+    rgba,
+    frames: placements,
+  })
+}
+
+/// Cuts the frames out of an RGBA sprite sheet, returning each frame's RGBA pixels.
+pub fn slice_sheet(sheet: &[u8], sheet_width: u32, frames: &[SpriteRect]) -> Result<Vec<Vec<u8>>> {
+  let sheet_height = sheet.len() as u32 / 4 / sheet_width.max(1);
+  frames
+    .iter()
+    .map(|f| {
+      let right = f.x.checked_add(f.width);
+      let bottom = f.y.checked_add(f.height);
+      if right.is_none_or(|r| r > sheet_width) || bottom.is_none_or(|b| b > sheet_height) {
+        return Err(Error::invalid(format!(
+          "Sprite {}x{} at {},{} is outside of the {}x{} sheet",
+          f.width, f.height, f.x, f.y, sheet_width, sheet_height
+        )));
+      }
+
+      let row = f.width as usize * 4;
+      Ok(
+        (0..f.height as usize)
+          .flat_map(|y| {
+            let start = ((f.y as usize + y) * sheet_width as usize + f.x as usize) * 4;
+            sheet[start..start + row].iter().copied()
+          })
+          .collect(),
+      )
+    })
+    .collect()
+}
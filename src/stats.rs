@@ -0,0 +1,140 @@
+//! Color histograms and pixel statistics of frames and whole animations.
+
+use crate::{apng, Error, PixelFormat, Result};
+use std::collections::HashSet;
+
+/// A rectangle on the GIF's canvas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bounds {
+  pub left: u32,
+  pub top: u32,
+  pub width: u32,
+  pub height: u32,
+}
+
+/// Statistics of the pixels of one or more frames, see [`StatsBuilder`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stats {
+  /// The number of pixels counted.
+  pub pixels: usize,
+  /// The number of distinct RGBA colors.
+  pub distinct_colors: usize,
+  /// The mean of the red, green, blue and alpha channels, in the range 0-255.
+  pub mean: [f64; 4],
+  /// The population variance of the red, green, blue and alpha channels.
+  pub variance: [f64; 4],
+  /// The share of fully transparent pixels, in the range 0-1.
+  pub transparent_fraction: f64,
+  /// The smallest rectangle holding every pixel that is not fully transparent, in canvas coordinates.
+  /// `None` if all pixels are transparent.
+  pub bounds: Option<Bounds>,
+  /// How many pixels use each of the 256 palette indices, if any indexed pixels were counted.
+  pub palette_usage: Option<Vec<usize>>,
+}
+
+/// Accumulates [`Stats`] over any number of frames.
+#[derive(Debug, Clone, Default)]
+pub struct StatsBuilder {
+  pixels: usize,
+  colors: HashSet<[u8; 4]>,
+  sums: [f64; 4],
+  squares: [f64; 4],
+  transparent: usize,
+  /// Left, top, right and bottom edges of the visible pixels.
+  edges: Option<[u32; 4]>,
+  usage: Option<Vec<usize>>,
+}
+
+impl StatsBuilder {
+  /// A builder that has not counted any pixels yet.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Counts RGBA pixels of a frame `width` pixels wide, placed at `left` and `top` on the canvas.
+  pub fn add_rgba(&mut self, rgba: &[u8], width: u16, left: u16, top: u16) -> Result<()> {
+    if width == 0 || !rgba.len().is_multiple_of(width as usize * 4) {
+      return Err(Error::invalid("Buffer size mismatch"));
+    }
+
+    let width = width as usize;
+    for (i, p) in rgba.chunks_exact(4).enumerate() {
+      self.colors.insert([p[0], p[1], p[2], p[3]]);
+      for (c, &v) in p.iter().enumerate() {
+        let v = v as f64;
+        self.sums[c] += v;
+        self.squares[c] += v * v;
+      }
+
+      if p[3] == 0 {
+        self.transparent += 1;
+        continue;
+      }
+      let x = left as u32 + (i % width) as u32;
+      let y = top as u32 + (i / width) as u32;
+      let [l, t, r, b] = self.edges.get_or_insert([x, y, x + 1, y + 1]);
+      *l = (*l).min(x);
+      *t = (*t).min(y);
+      *r = (*r).max(x + 1);
+      *b = (*b).max(y + 1);
+    }
+    self.pixels += rgba.len() / 4;
+    Ok(())
+  }
+
+  /// Counts how often each palette index is used by indexed pixels. Their colors are counted with [`Self::add_rgba`].
+  pub fn add_indices(&mut self, indices: &[u8]) {
+    let usage = self.usage.get_or_insert_with(|| vec![0; 256]);
+    for &i in indices {
+      usage[i as usize] += 1;
+    }
+  }
+
+  /// The statistics of all pixels counted so far. Without any pixels, the means and variances are `0`.
+  pub fn finish(self) -> Stats {
+    let n = self.pixels.max(1) as f64;
+    let mean = self.sums.map(|s| s / n);
+    let variance = std::array::from_fn(|c| (self.squares[c] / n - mean[c] * mean[c]).max(0.0));
+
+    Stats {
+      pixels: self.pixels,
+      distinct_colors: self.colors.len(),
+      mean,
+      variance,
+      transparent_fraction: self.transparent as f64 / n,
+      bounds: self.edges.map(|[l, t, r, b]| Bounds {
+        left: l,
+        top: t,
+        width: r - l,
+        height: b - t,
+      }),
+      palette_usage: self.usage,
+    }
+  }
+}
+
+/// Adds a frame's pixels to `builder`, resolving indexed pixels against the frame's or the global palette.
+pub fn add_frame(
+  builder: &mut StatsBuilder,
+  frame: &gif::Frame,
+  format: PixelFormat,
+  global_palette: Option<&[u8]>,
+) -> Result<()> {
+  let rgba = apng::frame_to_rgba(frame, format, global_palette)?;
+  builder.add_rgba(&rgba, frame.width, frame.left, frame.top)?;
+  if format == PixelFormat::Indexed {
+    builder.add_indices(&frame.buffer);
+  }
+  Ok(())
+}
+
+/// Statistics of a single frame, see [`add_frame`].
+pub fn frame_stats(
+  frame: &gif::Frame,
+  format: PixelFormat,
+  global_palette: Option<&[u8]>,
+) -> Result<Stats> {
+  let mut builder = StatsBuilder::new();
+  add_frame(&mut builder, frame, format, global_palette)?;
+  Ok(builder.finish())
+}
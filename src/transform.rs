@@ -0,0 +1,147 @@
+use crate::{Error, Result};
+
+fn check_rgba(rgba: &[u8], width: u32, height: u32) -> Result<()> {
+  if rgba.len() != width as usize * height as usize * 4 {
+    return Err(Error::invalid("Buffer size mismatch"));
+  }
+  Ok(())
+}
+
+/// Cuts the `width`x`height` area at `x`,`y` out of an RGBA image of `image_width` pixels per row.
+pub fn crop(
+  rgba: &[u8],
+  image_width: u32,
+  image_height: u32,
+  x: u32,
+  y: u32,
+  width: u32,
+  height: u32,
+) -> Result<Vec<u8>> {
+  check_rgba(rgba, image_width, image_height)?;
+  let right = x.checked_add(width);
+  let bottom = y.checked_add(height);
+  if right.is_none_or(|r| r > image_width) || bottom.is_none_or(|b| b > image_height) {
+    return Err(Error::invalid(format!(
+      "Crop {}x{} at {},{} is outside of the {}x{} image",
+      width, height, x, y, image_width, image_height
+    )));
+  }
+
+  let row = width as usize * 4;
+  Ok(
+    (y..y + height)
+      .flat_map(|y| {
+        let start = (y as usize * image_width as usize + x as usize) * 4;
+        rgba[start..start + row].iter().copied()
+      })
+      .collect(),
+  )
+}
+
+/// Resizes an RGBA image with bilinear filtering.
+/// Colors are interpolated with premultiplied alpha, so transparent pixels do not bleed into their neighbours.
+pub fn resize(
+  rgba: &[u8],
+  width: u32,
+  height: u32,
+  new_width: u32,
+  new_height: u32,
+) -> Result<Vec<u8>> {
+  check_rgba(rgba, width, height)?;
+  if new_width == 0 || new_height == 0 || width == 0 || height == 0 {
+    return Err(Error::invalid("Width and height need to be greater than 0"));
+  }
+
+  // Source coordinate and weight of the second sample along one axis, aligning pixel centers.
+  let axis = |size: u32, new_size: u32| -> Vec<(usize, usize, f32)> {
+    let scale = size as f32 / new_size as f32;
+    (0..new_size)
+      .map(|i| {
+        let pos = ((i as f32 + 0.5) * scale - 0.5).clamp(0.0, (size - 1) as f32);
+        let low = pos.floor() as usize;
+        (low, (low + 1).min(size as usize - 1), pos - low as f32)
+      })
+      .collect()
+  };
+  let xs = axis(width, new_width);
+  let ys = axis(height, new_height);
+
+  let premultiplied = |i: usize| -> [f32; 4] {
+    let p = &rgba[i * 4..i * 4 + 4];
+    let a = p[3] as f32 / 255.0;
+    [
+      p[0] as f32 * a,
+      p[1] as f32 * a,
+      p[2] as f32 * a,
+      p[3] as f32,
+    ]
+  };
+
+  let mut out = Vec::with_capacity(new_width as usize * new_height as usize * 4);
+  for &(y0, y1, wy) in &ys {
+    for &(x0, x1, wx) in &xs {
+      let w = width as usize;
+      let samples = [
+        (premultiplied(y0 * w + x0), (1.0 - wx) * (1.0 - wy)),
+        (premultiplied(y0 * w + x1), wx * (1.0 - wy)),
+        (premultiplied(y1 * w + x0), (1.0 - wx) * wy),
+        (premultiplied(y1 * w + x1), wx * wy),
+      ];
+
+      let mut pixel = [0f32; 4];
+      for (sample, weight) in samples {
+        for (p, s) in pixel.iter_mut().zip(sample) {
+          *p += s * weight;
+        }
+      }
+
+      let alpha = pixel[3] / 255.0;
+      for c in &pixel[..3] {
+        out.push(if alpha > 0.0 {
+          (c / alpha).round().clamp(0.0, 255.0) as u8
+        } else {
+          0
+        });
+      }
+      out.push(pixel[3].round().clamp(0.0, 255.0) as u8);
+    }
+  }
+  Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn crop_checks_bounds() {
+    let rgba: Vec<u8> = (0..2 * 2 * 4).collect();
+    assert_eq!(crop(&rgba, 2, 2, 1, 1, 1, 1).unwrap(), [12, 13, 14, 15]);
+    assert_eq!(crop(&rgba, 2, 2, 0, 0, 2, 2).unwrap(), rgba);
+    assert!(crop(&rgba, 2, 2, 1, 0, 2, 1).is_err());
+    assert!(crop(&rgba, 2, 2, 0, 2, 1, 1).is_err());
+    assert!(crop(&rgba, 2, 2, u32::MAX, 0, 2, 1).is_err());
+    assert!(crop(&rgba, 3, 2, 0, 0, 1, 1).is_err());
+  }
+
+  #[test]
+  fn resize_to_the_same_size_keeps_pixels() {
+    let rgba: Vec<u8> = (0..5 * 3)
+      .flat_map(|i| [i * 17, 255 - i * 11, i * 5, 1 + i * 16])
+      .collect();
+    assert_eq!(resize(&rgba, 5, 3, 5, 3).unwrap(), rgba);
+    assert!(resize(&rgba, 5, 3, 0, 3).is_err());
+    assert!(resize(&rgba, 4, 3, 5, 3).is_err());
+  }
+
+  #[test]
+  fn resize_does_not_bleed_transparent_colors() {
+    // An opaque red pixel next to a transparent green one
+    let rgba = [255, 0, 0, 255, 0, 255, 0, 0];
+    let resized = resize(&rgba, 2, 1, 8, 2).unwrap();
+    for pixel in resized.chunks_exact(4).filter(|p| p[3] > 0) {
+      assert_eq!(&pixel[..3], [255, 0, 0]);
+    }
+    assert!(resized.chunks_exact(4).any(|p| p[3] > 0 && p[3] < 255));
+  }
+}
@@ -0,0 +1,145 @@
+use crate::compare;
+use wasm_bindgen::prelude::*;
+
+/// Options for `compare` and `compareFrames`.
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct CompareOptions {
+  w: compare::CompareOptions,
+}
+
+#[wasm_bindgen]
+impl CompareOptions {
+  /// Create new compare options. Any channel difference counts a pixel as changed until changed.
+  #[wasm_bindgen(constructor)]
+  pub fn new() -> CompareOptions {
+    Self::default()
+  }
+
+  /// The largest channel difference that still counts a pixel as unchanged. The default is `0`.
+  #[wasm_bindgen(js_name = setThreshold)]
+  pub fn set_threshold(&mut self, value: u8) {
+    self.w.threshold = value;
+  }
+
+  /// Render a diff image for every frame pair, showing unchanged pixels faded and changed pixels in red.
+  #[wasm_bindgen(js_name = setDiffImages)]
+  pub fn set_diff_images(&mut self, value: bool) {
+    self.w.diff_images = value;
+  }
+}
+
+/// How much two frames differ.
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone)]
+pub struct FrameComparison {
+  /// Peak signal-to-noise ratio over all RGBA channels, in dB. `Infinity` for identical frames.
+  pub psnr: f64,
+  /// Mean structural similarity of the luminance, where 1 means identical.
+  pub ssim: f64,
+  /// The largest difference of any channel of any pixel.
+  #[wasm_bindgen(js_name = maxDifference)]
+  pub max_difference: u8,
+  /// The number of pixels with a channel difference above the threshold.
+  #[wasm_bindgen(js_name = changedPixels)]
+  pub changed_pixels: u32,
+  /// The delay of the frame from the first GIF in units of 10ms. Only set by `compare`.
+  #[wasm_bindgen(js_name = delayA)]
+  pub delay_a: Option<u32>,
+  /// The delay of the frame from the second GIF in units of 10ms. Only set by `compare`.
+  #[wasm_bindgen(js_name = delayB)]
+  pub delay_b: Option<u32>,
+  /// The diff image as opaque RGBA, if requested with `setDiffImages`.
+  pub diff: Option<Vec<u8>>,
+}
+
+impl From<compare::FrameComparison> for FrameComparison {
+  fn from(c: compare::FrameComparison) -> Self {
+    Self {
+      psnr: c.psnr,
+      ssim: c.ssim,
+      max_difference: c.max_difference,
+      changed_pixels: c.changed_pixels as u32,
+      delay_a: None,
+      delay_b: None,
+      diff: c.diff,
+    }
+  }
+}
+
+/// How much two GIFs differ, see `compare`.
+#[wasm_bindgen(getter_with_clone)]
+pub struct CompareReport {
+  /// One comparison per pair of composited frames, up to the length of the shorter GIF.
+  pub frames: Vec<FrameComparison>,
+  /// The number of frames of the first GIF.
+  #[wasm_bindgen(js_name = frameCountA)]
+  pub frame_count_a: u32,
+  /// The number of frames of the second GIF.
+  #[wasm_bindgen(js_name = frameCountB)]
+  pub frame_count_b: u32,
+  /// The summed delays of the first GIF in units of 10ms.
+  #[wasm_bindgen(js_name = durationA)]
+  pub duration_a: u32,
+  /// The summed delays of the second GIF in units of 10ms.
+  #[wasm_bindgen(js_name = durationB)]
+  pub duration_b: u32,
+  /// The lowest PSNR of any frame pair.
+  #[wasm_bindgen(js_name = minPsnr)]
+  pub min_psnr: f64,
+  /// The lowest SSIM of any frame pair.
+  #[wasm_bindgen(js_name = minSsim)]
+  pub min_ssim: f64,
+  /// The largest channel difference of any frame pair.
+  #[wasm_bindgen(js_name = maxDifference)]
+  pub max_difference: u8,
+  /// Both GIFs have the same number of frames with the same delays and pixels.
+  pub identical: bool,
+}
+
+/// Decodes two GIFs of the same size and compares their frames after compositing, pair by pair.
+/// Useful for regression tests of rendering pipelines.
+///
+/// ### Notes:
+/// - Frames are compared as rendered, so GIFs encoded differently can still be identical.
+/// - Timing differences show up in the delays of each pair and the total durations.
+#[wasm_bindgen]
+pub fn compare(
+  a: &[u8],
+  b: &[u8],
+  options: Option<CompareOptions>,
+) -> Result<CompareReport, JsError> {
+  let c = compare::compare_gifs(a, b, &options.unwrap_or_default().w)?;
+  Ok(CompareReport {
+    frames: c
+      .frames
+      .into_iter()
+      .zip(c.delays)
+      .map(|(frame, [delay_a, delay_b])| FrameComparison {
+        delay_a: Some(delay_a as u32),
+        delay_b: Some(delay_b as u32),
+        ..frame.into()
+      })
+      .collect(),
+    frame_count_a: c.frame_counts[0] as u32,
+    frame_count_b: c.frame_counts[1] as u32,
+    duration_a: c.durations[0],
+    duration_b: c.durations[1],
+    min_psnr: c.min_psnr,
+    min_ssim: c.min_ssim,
+    max_difference: c.max_difference,
+    identical: c.identical,
+  })
+}
+
+/// Compares two RGBA images of the same size, see `compare`.
+#[wasm_bindgen(js_name = compareFrames)]
+pub fn compare_frames(
+  a: &[u8],
+  b: &[u8],
+  width: u16,
+  height: u16,
+  options: Option<CompareOptions>,
+) -> Result<FrameComparison, JsError> {
+  Ok(compare::compare_frames(a, b, width, height, &options.unwrap_or_default().w)?.into())
+}
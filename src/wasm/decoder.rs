@@ -0,0 +1,326 @@
+use super::enums::{ColorOutput, FrameBufType};
+use super::frame::Frame;
+use super::hash::Fingerprint;
+use super::util::{self, ImageStats, PaletteColor, PaletteOptions};
+use crate::compositor::Compositor;
+use crate::{apng, convert, hash, stats};
+use std::io::Cursor;
+use std::num::NonZero;
+use wasm_bindgen::prelude::*;
+
+#[derive(Clone)]
+struct CustomOptions {
+  frame_buf_type: FrameBufType,
+  skip_frame_decoding: bool,
+}
+
+impl Default for CustomOptions {
+  fn default() -> Self {
+    Self {
+      frame_buf_type: FrameBufType::IndexedPixels,
+      skip_frame_decoding: false,
+    }
+  }
+}
+
+fn decoding_error(e: gif::DecodingError) -> JsError {
+  JsError::new(&format!("Failed to create a GIF decoder: {}", e))
+}
+
+/// The GIF Decoder.
+#[wasm_bindgen]
+pub struct Decoder {
+  w: gif::Decoder<Cursor<Vec<u8>>>,
+  custom_options: CustomOptions,
+  compositor: Option<Compositor>,
+}
+
+#[wasm_bindgen]
+impl Decoder {
+  /// Create a new decoder. Use `<DecodeOptions>.readInfo` to decode with options.
+  /// @param buffer - The GIF buffer to decode.
+  #[wasm_bindgen(constructor)]
+  pub fn new(buffer: Vec<u8>) -> Result<Decoder, JsError> {
+    Ok(Decoder {
+      w: gif::Decoder::new(Cursor::new(buffer)).map_err(decoding_error)?,
+      custom_options: CustomOptions::default(),
+      compositor: None,
+    })
+  }
+
+  /// Returns the next frame info. (skips the buffer)
+  #[wasm_bindgen(js_name = nextFrameInfo)]
+  pub fn next_frame_info(&mut self) -> Result<Option<Frame>, JsError> {
+    let f = self
+      .w
+      .next_frame_info()
+      .map_err(|e| JsError::new(&format!("Failed to get next frame info: {}", e)))?;
+    Ok(f.map(|f| Frame::from_gif_frame(f.clone(), self.custom_options.frame_buf_type)))
+  }
+
+  /// Reads the next frame from the GIF.
+  /// Do not call `<Decoder>.nextFrameInfo` beforehand. Deinterlaces the result.
+  /// If frame decoding is skipped, the frame's buffer holds the compressed LZW data instead.
+  #[wasm_bindgen(js_name = readNextFrame)]
+  pub fn read_next_frame(&mut self) -> Result<Option<Frame>, JsError> {
+    let buf_type = if self.custom_options.skip_frame_decoding {
+      FrameBufType::Lzw
+    } else {
+      self.custom_options.frame_buf_type
+    };
+
+    let Some(f) = self
+      .w
+      .read_next_frame()
+      .map_err(|e| JsError::new(&format!("Failed to get next frame info: {}", e)))?
+    else {
+      return Ok(None);
+    };
+
+    let mut f = f.clone();
+    // The gif crate only decodes to RGBA or indexed pixels, other representations are converted from RGBA.
+    if matches!(buf_type, FrameBufType::Rgb | FrameBufType::Hex) {
+      let rgba = std::mem::take(&mut f.buffer).into_owned();
+      f.buffer = convert::rgba_to(rgba, buf_type.into())?.into();
+    }
+    let mut frame = Frame::from_gif_frame(f, buf_type);
+    if frame.buf_type == FrameBufType::Lzw && frame.w.palette.is_none() {
+      // Keep the palette the frame decodes against, for `<Encoder>.addRawFrame`.
+      frame.source_palette = self.w.global_palette().map(|p| p.to_vec());
+    }
+    Ok(Some(frame))
+  }
+
+  /// Reads the remaining frames and encodes them as an APNG.
+  #[wasm_bindgen(js_name = toApng)]
+  pub fn to_apng(&mut self) -> Result<Vec<u8>, JsError> {
+    let mut frames = Vec::new();
+    while let Some(frame) = self.read_next_rgba_frame()? {
+      frames.push(frame);
+    }
+
+    Ok(apng::encode_apng(
+      self.w.width(),
+      self.w.height(),
+      self.w.repeat(),
+      &frames,
+    )?)
+  }
+
+  /// Reads the remaining frames and finds up to `count` colors that best represent all of them together, most common first.
+  /// See `extractPalette`.
+  #[wasm_bindgen(js_name = extractPalette)]
+  pub fn extract_palette(
+    &mut self,
+    count: u32,
+    options: Option<PaletteOptions>,
+  ) -> Result<Vec<PaletteColor>, JsError> {
+    let mut builder = util::palette_builder(count, options)?;
+    while let Some((_, rgba)) = self.read_next_rgba_frame()? {
+      builder.add_rgba(&rgba)?;
+    }
+    Ok(util::palette_colors(builder))
+  }
+
+  /// Reads the remaining frames and computes their pixel statistics together, see `<Frame>.stats`.
+  /// Bounds cover the visible pixels of all frames, and palette usage is counted for indexed frames.
+  #[wasm_bindgen]
+  pub fn stats(&mut self) -> Result<ImageStats, JsError> {
+    let mut builder = stats::StatsBuilder::new();
+    while let Some(frame) = self.read_next_frame()? {
+      stats::add_frame(
+        &mut builder,
+        &frame.w,
+        frame.buf_type.into(),
+        self.w.global_palette(),
+      )?;
+    }
+    Ok(builder.finish().into())
+  }
+
+  /// Reads the remaining frames, composites them and hashes each one along with its delay, see `dhash` and `phash`.
+  /// Compare fingerprints with `fingerprintSimilarity` to find copies of a GIF that were re-encoded or resized.
+  #[wasm_bindgen]
+  pub fn fingerprint(&mut self) -> Result<Fingerprint, JsError> {
+    let mut fingerprint = hash::Fingerprint::new();
+    while let Some(frame) = self.read_next_composited_frame()? {
+      let w = &frame.w;
+      fingerprint.push(hash::FrameHash::new(&w.buffer, w.width, w.height, w.delay)?);
+    }
+    Ok(fingerprint.into())
+  }
+
+  /// Reads the next frame and draws it onto the canvas left by the previous frames, applying their disposal methods.
+  /// Returns the whole canvas as an RGBA frame of the GIF's size, keeping the frame's delay.
+  #[wasm_bindgen(js_name = readNextCompositedFrame)]
+  pub fn read_next_composited_frame(&mut self) -> Result<Option<Frame>, JsError> {
+    let Some((frame, rgba)) = self.read_next_rgba_frame()? else {
+      return Ok(None);
+    };
+
+    let (width, height) = (self.w.width(), self.w.height());
+    let canvas = self
+      .compositor
+      .get_or_insert_with(|| Compositor::new(width, height))
+      .draw(&frame, &rgba);
+
+    Ok(Some(Frame::from_gif_frame(
+      gif::Frame {
+        width,
+        height,
+        delay: frame.delay,
+        dispose: gif::DisposalMethod::Background,
+        buffer: canvas.to_vec().into(),
+        ..gif::Frame::default()
+      },
+      FrameBufType::Rgba,
+    )))
+  }
+
+  /// The color palette relevant for the frame that has been decoded.
+  #[wasm_bindgen(getter)]
+  pub fn palette(&self) -> Result<Vec<u8>, JsError> {
+    self
+      .w
+      .palette()
+      .map(|p| p.to_vec())
+      .map_err(|e| JsError::new(&format!("Failed to get the palette: {}", e)))
+  }
+
+  /// The global color palette.
+  #[wasm_bindgen(getter, js_name = globalPalette)]
+  pub fn global_palette(&self) -> Option<Vec<u8>> {
+    self.w.global_palette().map(|p| p.to_vec())
+  }
+
+  /// Width of the GIF.
+  #[wasm_bindgen(getter)]
+  pub fn width(&self) -> u16 {
+    self.w.width()
+  }
+
+  /// Height of the GIF.
+  #[wasm_bindgen(getter)]
+  pub fn height(&self) -> u16 {
+    self.w.height()
+  }
+
+  /// Index of the background color in the global palette
+  #[wasm_bindgen(getter, js_name = bgColor)]
+  pub fn bg_color(&self) -> Option<u16> {
+    self.w.bg_color().map(|c| c as u16)
+  }
+
+  /// Number of loop repetitions.
+  #[wasm_bindgen(getter)]
+  pub fn loops(&self) -> i16 {
+    match self.w.repeat() {
+      gif::Repeat::Finite(v) => v as i16,
+      gif::Repeat::Infinite => -1,
+    }
+  }
+}
+
+impl Decoder {
+  /// Reads the next frame along with its pixels as RGBA.
+  fn read_next_rgba_frame(&mut self) -> Result<Option<(gif::Frame<'static>, Vec<u8>)>, JsError> {
+    let Some(frame) = self.read_next_frame()? else {
+      return Ok(None);
+    };
+    let rgba = apng::frame_to_rgba(&frame.w, frame.buf_type.into(), self.w.global_palette())?;
+    Ok(Some((frame.w, rgba)))
+  }
+}
+
+/// Options for opening a GIF decoder. `<DecodeOptions>.readInfo` will create a decoder with these options.
+#[wasm_bindgen]
+pub struct DecodeOptions {
+  w: gif::DecodeOptions,
+  custom_options: CustomOptions,
+}
+
+#[wasm_bindgen]
+impl DecodeOptions {
+  /// Create new decode options.
+  #[wasm_bindgen(constructor)]
+  #[allow(clippy::new_without_default)]
+  pub fn new() -> DecodeOptions {
+    Self {
+      w: gif::DecodeOptions::new(),
+      custom_options: CustomOptions::default(),
+    }
+  }
+
+  /// Configure how color data is decoded.
+  #[wasm_bindgen(js_name = setColorOutput)]
+  pub fn set_color_output(&mut self, value: ColorOutput) {
+    self.custom_options.frame_buf_type = match value {
+      ColorOutput::Rgba => FrameBufType::Rgba,
+      ColorOutput::IndexedPixels => FrameBufType::IndexedPixels,
+      ColorOutput::Rgb => FrameBufType::Rgb,
+      ColorOutput::Hex => FrameBufType::Hex,
+    };
+
+    self.w.set_color_output(match value {
+      ColorOutput::IndexedPixels => gif::ColorOutput::Indexed,
+      ColorOutput::Rgba | ColorOutput::Rgb | ColorOutput::Hex => gif::ColorOutput::RGBA,
+    });
+  }
+
+  /// Configure a memory limit for decoding.
+  /// @param value - The memory limit in bytes. Negative values are treated as unlimited. (e.g. -1)
+  #[wasm_bindgen(js_name = setMemoryLimit)]
+  pub fn set_memory_limit(&mut self, value: i64) -> Result<(), JsError> {
+    if value <= -1 {
+      self.w.set_memory_limit(gif::MemoryLimit::Unlimited);
+    } else {
+      self.w.set_memory_limit(gif::MemoryLimit::Bytes(
+        NonZero::new(value as u64)
+          .ok_or_else(|| JsError::new("Limit must be a positive non-zero integer"))?,
+      ));
+    }
+    Ok(())
+  }
+
+  /// Configure if frames must be within the screen descriptor. The default is `false`.
+  #[wasm_bindgen(js_name = checkFrameConsistency)]
+  pub fn check_frame_consistency(&mut self, value: bool) {
+    self.w.check_frame_consistency(value);
+  }
+
+  /// Configure whether to skip decoding frames. The default is `false`.
+  /// Frames read this way hold LZW data and can be written back as-is with `<Encoder>.addRawFrame`.
+  #[wasm_bindgen(js_name = skipFrameDecoding)]
+  pub fn skip_frame_decoding(&mut self, value: bool) {
+    self.custom_options.skip_frame_decoding = value;
+    self.w.skip_frame_decoding(value);
+  }
+
+  /// Configure if LZW encoded blocks must end with a marker end code. The default is `false`.
+  #[wasm_bindgen(js_name = checkLzwEndCode)]
+  pub fn check_lzw_end_code(&mut self, value: bool) {
+    self.w.check_lzw_end_code(value);
+  }
+
+  /// Configure if unknown blocks are allowed to be decoded. The default is `false`.
+  #[wasm_bindgen(js_name = allowUnknownBlocks)]
+  pub fn allow_unknown_blocks(&mut self, value: bool) {
+    self.w.allow_unknown_blocks(value);
+  }
+
+  /// Reads the logical screen descriptor including the global color palette
+  /// Returns a Decoder. All decoder configuration has to be done beforehand.
+  /// @param buffer - The GIF buffer to decode.
+  #[wasm_bindgen(js_name = readInfo)]
+  pub fn read_info(&self, buffer: Vec<u8>) -> Result<Decoder, JsError> {
+    Ok(Decoder {
+      w: self
+        .w
+        .clone()
+        .read_info(Cursor::new(buffer))
+        .map_err(decoding_error)?,
+      custom_options: self.custom_options.clone(),
+      compositor: None,
+    })
+  }
+}
@@ -0,0 +1,200 @@
+use super::enums::{FrameBufType, PaletteOrder};
+use super::frame::Frame;
+use crate::progress::Control;
+use crate::{encode, lzw, palette, quantize};
+use std::io::Cursor;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+pub struct Encoder {
+  w: gif::Encoder<Cursor<Vec<u8>>>,
+  palette: Option<Vec<u8>>,
+  lossy: u32,
+  compact_palettes: Option<palette::PaletteOrder>,
+  /// The gif width.
+  #[wasm_bindgen(readonly)]
+  pub width: u16,
+  /// The gif height.
+  #[wasm_bindgen(readonly)]
+  pub height: u16,
+}
+
+#[wasm_bindgen]
+impl Encoder {
+  /// Create a new encoder.
+  #[wasm_bindgen(constructor)]
+  pub fn new(width: u16, height: u16, palette: Option<Vec<u8>>) -> Result<Encoder, JsError> {
+    let w = gif::Encoder::new(
+      Cursor::new(Vec::new()),
+      width,
+      height,
+      palette.as_deref().unwrap_or(&[]),
+    )
+    .map_err(|e| JsError::new(&format!("Failed to create a GIF Encoder: {}", e)))?;
+
+    Ok(Encoder {
+      w,
+      palette,
+      lossy: 0,
+      compact_palettes: None,
+      width,
+      height,
+    })
+  }
+
+  /// Add a frame to the gif.
+  ///
+  /// ### Notes:
+  /// - The frame must fit within the gif's `width` and `height`, taking `left` and `top` into account.
+  /// - Frames holding RGB, RGBA or hex pixels are quantized first, see `<Frame>.convertTo`.
+  #[wasm_bindgen(js_name = addFrame)]
+  pub fn add_frame(&mut self, frame: &Frame) -> Result<(), JsError> {
+    if frame.buf_type == FrameBufType::Lzw {
+      return Err(JsError::new(
+        "Frame holds LZW data, use `<Encoder>.addRawFrame` instead",
+      ));
+    }
+
+    self.check_bounds(frame)?;
+
+    // RGB, RGBA and hex frames, e.g. from a decoder, are quantized first.
+    Ok(encode::write_frame(
+      &mut self.w,
+      &frame.w,
+      frame.buf_type.into(),
+      self.compact_palettes,
+      frame.lossy.unwrap_or(self.lossy),
+      self.palette.as_deref(),
+    )?)
+  }
+
+  /// Add multiple frames from RGBA pixel data to the gif.
+  /// @param buffer - The RGBA pixels of every frame, one after another.
+  ///
+  /// ### Notes:
+  /// - Speed needs to be in the range 1-30. Higher is faster, lower CPU usage but worse quality.
+  /// - The size of `buffer` should be a multiple of the frame size based on `width`, `height`.
+  /// - `delays`, if provided, needs one entry per frame.
+  #[wasm_bindgen(js_name = addRgbaFrames)]
+  pub fn add_rgba_frames(
+    &mut self,
+    mut buffer: Vec<u8>,
+    delays: Option<Vec<u16>>,
+    speed: Option<i32>,
+  ) -> Result<(), JsError> {
+    let size = self.width as usize * self.height as usize * 4;
+    if size == 0 || !buffer.len().is_multiple_of(size) {
+      return Err(JsError::new("Buffer size mismatch"));
+    }
+
+    let count = buffer.len() / size;
+    if let Some(delays) = &delays {
+      if delays.len() != count {
+        return Err(JsError::new(&format!(
+          "Expected {} delays, got {}",
+          count,
+          delays.len()
+        )));
+      }
+    }
+
+    let encoded = quantize::quantize_rgba_batch(
+      self.width,
+      self.height,
+      buffer.chunks_exact_mut(size).collect(),
+      speed.unwrap_or(15),
+      self.palette.as_deref(),
+      self.lossy,
+      None,
+      Control::default(),
+    )?;
+
+    for (i, mut frame) in encoded.into_iter().enumerate() {
+      if let Some(delays) = &delays {
+        frame.delay = delays[i];
+      }
+
+      self
+        .w
+        .write_lzw_pre_encoded_frame(&frame)
+        .map_err(|e| JsError::new(&format!("Failed to write a frame: {}", e)))?;
+    }
+    Ok(())
+  }
+
+  /// Sets the lossy LZW compression level used for frames without their own `lossy` level.
+  ///
+  /// ### Notes:
+  /// - The level needs to be in the range 0-200. `0` disables lossy compression; 20-80 is a reasonable range.
+  #[wasm_bindgen(js_name = setLossy)]
+  pub fn set_lossy(&mut self, value: u32) -> Result<(), JsError> {
+    lzw::check_lossy(value)?;
+    self.lossy = value;
+    Ok(())
+  }
+
+  /// Compacts the palette of every frame with a palette of its own added with `addFrame`, see `<Frame>.compactPalette`.
+  /// Decoded frames often carry 256-color palettes of which only a few entries are used, which costs space in every frame.
+  /// @param order - How to order the colors that are kept. Defaults to `PaletteOrder.Original`.
+  #[wasm_bindgen(js_name = setCompactPalettes)]
+  pub fn set_compact_palettes(&mut self, value: bool, order: Option<PaletteOrder>) {
+    self.compact_palettes = value.then(|| order.map(Into::into).unwrap_or_default());
+  }
+
+  /// Add a frame whose buffer is already LZW-compressed to the gif, without re-encoding it.
+  ///
+  /// ### Notes:
+  /// - The frame is usually read from a decoder with `<DecodeOptions>.skipFrameDecoding` enabled.
+  /// - A frame without a palette of its own keeps decoding against the global palette of the GIF it was read from, which becomes its own palette unless it matches the encoder's.
+  #[wasm_bindgen(js_name = addRawFrame)]
+  pub fn add_raw_frame(&mut self, frame: &Frame) -> Result<(), JsError> {
+    if frame.buf_type != FrameBufType::Lzw {
+      return Err(JsError::new(
+        "Frame does not hold LZW data, use `<Encoder>.addFrame` instead",
+      ));
+    }
+
+    self.check_bounds(frame)?;
+    let prepared = encode::prepare_raw_frame(
+      &frame.w,
+      frame.source_palette.as_deref(),
+      self.palette.as_deref(),
+    )?;
+    self
+      .w
+      .write_lzw_pre_encoded_frame(&prepared)
+      .map_err(|e| JsError::new(&format!("Failed to write a raw frame: {}", e)))
+  }
+
+  /// Sets the repeat count for the gif. If the value is -1, the gif will repeat infinitely; otherwise, the gif will repeat a `value` number of times.
+  #[wasm_bindgen(js_name = setRepeat)]
+  pub fn set_repeat(&mut self, value: i16) {
+    let _ = self.w.set_repeat(if value <= -1 {
+      gif::Repeat::Infinite
+    } else {
+      gif::Repeat::Finite(value as u16)
+    });
+  }
+
+  /// Returns the gif buffer.
+  #[wasm_bindgen(js_name = getBuffer)]
+  pub fn get_buffer(&self) -> Vec<u8> {
+    let mut buf = self.w.get_ref().get_ref().clone();
+    buf.push(0x3B);
+    buf
+  }
+}
+
+impl Encoder {
+  fn check_bounds(&self, frame: &Frame) -> Result<(), JsError> {
+    if frame.w.left as u32 + frame.w.width as u32 > self.width as u32
+      || frame.w.top as u32 + frame.w.height as u32 > self.height as u32
+    {
+      return Err(JsError::new(&format!(
+        "Frame {}x{} at {},{} does not fit within encoder dimensions {}x{}",
+        frame.w.width, frame.w.height, frame.w.left, frame.w.top, self.width, self.height
+      )));
+    }
+    Ok(())
+  }
+}
@@ -0,0 +1,187 @@
+use crate::{color, palette, pixels, PixelFormat};
+use wasm_bindgen::prelude::*;
+
+/// Output mode for the image data.
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq)]
+pub enum ColorOutput {
+  /// RGBA color mode, where each pixel is represented with red, green, blue, and alpha components.
+  Rgba,
+  /// Indexed pixel mode, where each pixel references a color in a palette.
+  IndexedPixels,
+  /// RGB color mode, like `Rgba` without the alpha component.
+  Rgb,
+  /// Hex color mode, where each pixel is written as an ASCII `#RRGGBBAA` string.
+  Hex,
+}
+
+/// The representation of a frame's buffer.
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq)]
+pub enum FrameBufType {
+  /// Four bytes per pixel: red, green, blue and alpha.
+  Rgba,
+  /// Three bytes per pixel: red, green and blue.
+  Rgb,
+  /// One palette index per pixel.
+  IndexedPixels,
+  /// Nine bytes per pixel: an ASCII `#RRGGBBAA` color.
+  Hex,
+  /// LZW-compressed image data, as produced by a decoder that skips frame decoding.
+  Lzw,
+}
+
+impl From<FrameBufType> for PixelFormat {
+  fn from(buf_type: FrameBufType) -> Self {
+    match buf_type {
+      FrameBufType::Rgba => PixelFormat::Rgba,
+      FrameBufType::Rgb => PixelFormat::Rgb,
+      FrameBufType::Hex => PixelFormat::Hex,
+      FrameBufType::IndexedPixels => PixelFormat::Indexed,
+      FrameBufType::Lzw => PixelFormat::Lzw,
+    }
+  }
+}
+
+/// Disposal method, describing how the next frame should be drawn over the current one.
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq)]
+pub enum DisposalMethod {
+  /// Decoder is not required to take any specific action.
+  Any,
+  /// Retain the current frame as it is.
+  Keep,
+  /// Clear the frame and restore the canvas to its background color.
+  Background,
+  /// Restore the canvas to the previous frame's state.
+  Previous,
+}
+
+impl From<gif::DisposalMethod> for DisposalMethod {
+  fn from(value: gif::DisposalMethod) -> Self {
+    match value {
+      gif::DisposalMethod::Any => DisposalMethod::Any,
+      gif::DisposalMethod::Keep => DisposalMethod::Keep,
+      gif::DisposalMethod::Background => DisposalMethod::Background,
+      gif::DisposalMethod::Previous => DisposalMethod::Previous,
+    }
+  }
+}
+
+impl From<DisposalMethod> for gif::DisposalMethod {
+  fn from(value: DisposalMethod) -> Self {
+    match value {
+      DisposalMethod::Any => gif::DisposalMethod::Any,
+      DisposalMethod::Keep => gif::DisposalMethod::Keep,
+      DisposalMethod::Background => gif::DisposalMethod::Background,
+      DisposalMethod::Previous => gif::DisposalMethod::Previous,
+    }
+  }
+}
+
+/// A color space for `rgbToColorSpace` and `colorSpaceToRgb`.
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq)]
+pub enum ColorSpace {
+  /// Hue in degrees (0-360), saturation and lightness (0-1).
+  Hsl,
+  /// Hue in degrees (0-360), saturation and value (0-1).
+  Hsv,
+  /// CIE L*a*b* under the D65 white point. Lightness is 0-100.
+  Lab,
+  /// Cylindrical CIE L*a*b*: lightness (0-100), chroma and hue in degrees (0-360).
+  Lch,
+  /// OKLab. Lightness is 0-1.
+  Oklab,
+}
+
+impl From<ColorSpace> for color::ColorSpace {
+  fn from(value: ColorSpace) -> Self {
+    match value {
+      ColorSpace::Hsl => color::ColorSpace::Hsl,
+      ColorSpace::Hsv => color::ColorSpace::Hsv,
+      ColorSpace::Lab => color::ColorSpace::Lab,
+      ColorSpace::Lch => color::ColorSpace::Lch,
+      ColorSpace::Oklab => color::ColorSpace::Oklab,
+    }
+  }
+}
+
+/// A way to measure how different two colors look.
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq)]
+pub enum DistanceMetric {
+  /// Euclidean distance in CIE L*a*b* (ΔE*76). Fast, but overstates differences between saturated colors.
+  Cie76,
+  /// The CIEDE2000 color difference (ΔE*00). The most accurate, and the slowest.
+  Ciede2000,
+  /// Euclidean distance in OKLab. Close to CIEDE2000 at the cost of CIE76.
+  Oklab,
+}
+
+impl From<DistanceMetric> for color::DistanceMetric {
+  fn from(value: DistanceMetric) -> Self {
+    match value {
+      DistanceMetric::Cie76 => color::DistanceMetric::Cie76,
+      DistanceMetric::Ciede2000 => color::DistanceMetric::Ciede2000,
+      DistanceMetric::Oklab => color::DistanceMetric::Oklab,
+    }
+  }
+}
+
+/// How `compactPalette` orders the colors it keeps.
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq)]
+pub enum PaletteOrder {
+  /// Keep the colors in their original order.
+  Original,
+  /// Darkest first.
+  Luminance,
+  /// Most used first. Runs of low indices tend to compress better.
+  Frequency,
+}
+
+impl From<PaletteOrder> for palette::PaletteOrder {
+  fn from(value: PaletteOrder) -> Self {
+    match value {
+      PaletteOrder::Original => palette::PaletteOrder::Original,
+      PaletteOrder::Luminance => palette::PaletteOrder::Luminance,
+      PaletteOrder::Frequency => palette::PaletteOrder::Frequency,
+    }
+  }
+}
+
+/// The layout of the pixels passed to `Frame.fromPixels`.
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq)]
+pub enum InputFormat {
+  /// Red, green, blue and straight alpha.
+  Rgba = "rgba",
+  /// Blue, green, red and alpha, as produced by most screen capture APIs.
+  Bgra = "bgra",
+  /// Alpha, red, green and blue.
+  Argb = "argb",
+  /// RGBA with the color channels multiplied by alpha, as held by canvas surfaces.
+  RgbaPremultiplied = "rgba_premultiplied",
+  /// One luminance byte per pixel.
+  Gray = "gray",
+  /// Luminance and alpha.
+  GrayAlpha = "gray_alpha",
+}
+
+// String enums accept any string from JavaScript, so unknown formats are only caught here.
+impl TryFrom<InputFormat> for pixels::InputFormat {
+  type Error = crate::Error;
+
+  fn try_from(value: InputFormat) -> crate::Result<Self> {
+    Ok(match value {
+      InputFormat::Rgba => pixels::InputFormat::Rgba,
+      InputFormat::Bgra => pixels::InputFormat::Bgra,
+      InputFormat::Argb => pixels::InputFormat::Argb,
+      InputFormat::RgbaPremultiplied => pixels::InputFormat::RgbaPremultiplied,
+      InputFormat::Gray => pixels::InputFormat::Gray,
+      InputFormat::GrayAlpha => pixels::InputFormat::GrayAlpha,
+      _ => return Err(crate::Error::invalid("Unknown pixel format")),
+    })
+  }
+}
@@ -0,0 +1,436 @@
+use super::enums::{DisposalMethod, FrameBufType, InputFormat, PaletteOrder};
+use super::util::{self, ImageStats, PaletteColor, PaletteOptions};
+use crate::{apng, convert, css, hex, lzw, palette, pixels, quantize, stats};
+use wasm_bindgen::prelude::*;
+
+/// How semi-transparent pixels are handled when quantizing RGBA pixels. GIF pixels are either fully opaque or fully transparent.
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct AlphaOptions {
+  w: quantize::AlphaOptions,
+}
+
+#[wasm_bindgen]
+impl AlphaOptions {
+  /// Create new alpha options. Only fully transparent pixels become transparent until changed.
+  #[wasm_bindgen(constructor)]
+  pub fn new() -> AlphaOptions {
+    Self::default()
+  }
+
+  /// Pixels with an alpha below `value` become transparent, all others opaque.
+  #[wasm_bindgen(js_name = setThreshold)]
+  pub fn set_threshold(&mut self, value: u8) {
+    self.w.threshold = value;
+  }
+
+  /// Replace the threshold with an ordered dither pattern, so that soft edges fade out instead of being cut off.
+  #[wasm_bindgen(js_name = setDither)]
+  pub fn set_dither(&mut self, value: bool) {
+    self.w.dither = value;
+  }
+
+  /// Blend semi-transparent pixels that stay opaque over a hex or other CSS color instead of dropping their alpha.
+  #[wasm_bindgen(js_name = setMatte)]
+  pub fn set_matte(&mut self, value: Option<String>) -> Result<(), JsError> {
+    self.w.matte = match value {
+      Some(c) => {
+        let [r, g, b, _] = css::parse_color(&c)?;
+        Some([r, g, b])
+      }
+      None => None,
+    };
+    Ok(())
+  }
+
+  /// Reserve a palette index for transparent pixels. Opaque pixels never use it, even if the frame has no transparent pixels.
+  #[wasm_bindgen(js_name = setTransparentIndex)]
+  pub fn set_transparent_index(&mut self, value: Option<u8>) {
+    self.w.transparent_index = value;
+  }
+}
+
+/// A GIF frame.
+#[wasm_bindgen]
+pub struct Frame {
+  #[wasm_bindgen(js_name = bufType)]
+  pub buf_type: FrameBufType,
+  pub(crate) w: gif::Frame<'static>,
+  pub(crate) lossy: Option<u32>,
+  /// The global palette of the GIF a frame holding LZW data was read from, if the frame has no palette of its own.
+  pub(crate) source_palette: Option<Vec<u8>>,
+}
+
+#[wasm_bindgen]
+impl Frame {
+  #[wasm_bindgen(getter)]
+  pub fn width(&self) -> u16 {
+    self.w.width
+  }
+  #[wasm_bindgen(getter)]
+  pub fn height(&self) -> u16 {
+    self.w.height
+  }
+
+  #[wasm_bindgen(getter)]
+  pub fn delay(&self) -> u16 {
+    self.w.delay
+  }
+  #[wasm_bindgen(setter)]
+  pub fn set_delay(&mut self, val: u16) {
+    self.w.delay = val;
+  }
+
+  #[wasm_bindgen(getter)]
+  pub fn dispose(&self) -> DisposalMethod {
+    self.w.dispose.into()
+  }
+  #[wasm_bindgen(setter)]
+  pub fn set_dispose(&mut self, val: DisposalMethod) {
+    self.w.dispose = val.into();
+  }
+
+  #[wasm_bindgen(getter, js_name = needsUserInput)]
+  pub fn needs_user_input(&self) -> bool {
+    self.w.needs_user_input
+  }
+  #[wasm_bindgen(setter, js_name = needsUserInput)]
+  pub fn set_needs_user_input(&mut self, val: bool) {
+    self.w.needs_user_input = val;
+  }
+
+  #[wasm_bindgen(getter)]
+  pub fn transparent(&self) -> Option<u8> {
+    self.w.transparent
+  }
+  #[wasm_bindgen(setter)]
+  pub fn set_transparent(&mut self, val: Option<u8>) {
+    self.w.transparent = val;
+  }
+
+  #[wasm_bindgen(getter)]
+  pub fn interlaced(&self) -> bool {
+    self.w.interlaced
+  }
+  #[wasm_bindgen(setter)]
+  pub fn set_interlaced(&mut self, val: bool) {
+    self.w.interlaced = val;
+  }
+
+  #[wasm_bindgen(getter)]
+  pub fn top(&self) -> u16 {
+    self.w.top
+  }
+  #[wasm_bindgen(setter)]
+  pub fn set_top(&mut self, val: u16) {
+    self.w.top = val;
+  }
+
+  #[wasm_bindgen(getter)]
+  pub fn left(&self) -> u16 {
+    self.w.left
+  }
+  #[wasm_bindgen(setter)]
+  pub fn set_left(&mut self, val: u16) {
+    self.w.left = val;
+  }
+
+  /// Lossy LZW compression level for this frame, overriding the encoder's level. See `<Encoder>.setLossy`.
+  #[wasm_bindgen(getter)]
+  pub fn lossy(&self) -> Option<u32> {
+    self.lossy
+  }
+  #[wasm_bindgen(setter)]
+  pub fn set_lossy(&mut self, val: Option<u32>) -> Result<(), JsError> {
+    if let Some(val) = val {
+      lzw::check_lossy(val)?;
+    }
+    self.lossy = val;
+    Ok(())
+  }
+
+  /// The frame's palette.
+  #[wasm_bindgen(getter)]
+  pub fn palette(&self) -> Option<Vec<u8>> {
+    self.w.palette.clone()
+  }
+  #[wasm_bindgen(setter)]
+  pub fn set_palette(&mut self, val: Option<Vec<u8>>) {
+    self.w.palette = val;
+  }
+
+  /// The frame's buffer.
+  #[wasm_bindgen(getter)]
+  pub fn buffer(&self) -> Vec<u8> {
+    self.w.buffer.to_vec()
+  }
+  #[wasm_bindgen(setter)]
+  pub fn set_buffer(&mut self, buf: Vec<u8>) {
+    self.w.buffer = buf.into();
+  }
+
+  /// Encodes the frame as a PNG.
+  /// Indexed frames are written as an indexed PNG, keeping the palette and the transparent index.
+  /// @param globalPalette - The palette to use if the frame has no palette of its own. (e.g. `<Decoder>.globalPalette`)
+  #[wasm_bindgen(js_name = toPng)]
+  pub fn to_png(&self, global_palette: Option<Vec<u8>>) -> Result<Vec<u8>, JsError> {
+    Ok(apng::encode_png(
+      &self.w,
+      self.buf_type.into(),
+      global_palette.as_deref(),
+    )?)
+  }
+
+  /// Finds up to `count` colors that best represent the frame, most common first. See `extractPalette`.
+  /// @param globalPalette - The palette to use if the frame has no palette of its own. (e.g. `<Decoder>.globalPalette`)
+  #[wasm_bindgen(js_name = extractPalette)]
+  pub fn extract_palette(
+    &self,
+    count: u32,
+    options: Option<PaletteOptions>,
+    global_palette: Option<Vec<u8>>,
+  ) -> Result<Vec<PaletteColor>, JsError> {
+    let rgba = apng::frame_to_rgba(&self.w, self.buf_type.into(), global_palette.as_deref())?;
+    util::extract_palette_from([rgba], count, options)
+  }
+
+  /// Computes the frame's pixel statistics: distinct colors, channel means and variances, transparency and the bounds of its visible pixels.
+  /// Indexed frames also count how often each palette index is used.
+  /// @param globalPalette - The palette to use if the frame has no palette of its own. (e.g. `<Decoder>.globalPalette`)
+  #[wasm_bindgen]
+  pub fn stats(&self, global_palette: Option<Vec<u8>>) -> Result<ImageStats, JsError> {
+    Ok(stats::frame_stats(&self.w, self.buf_type.into(), global_palette.as_deref())?.into())
+  }
+
+  /// Returns a copy of the frame with unused palette entries dropped and duplicate colors merged, see `compactPalette`.
+  /// Frames without a palette of their own get the compacted `globalPalette` as their own.
+  ///
+  /// ### Notes:
+  /// - Only frames holding indexed pixels can be compacted.
+  /// @param order - How to order the colors that are kept. Defaults to `PaletteOrder.Original`.
+  /// @param globalPalette - The palette to use if the frame has no palette of its own. (e.g. `<Decoder>.globalPalette`)
+  #[wasm_bindgen(js_name = compactPalette)]
+  pub fn compact_palette(
+    &self,
+    order: Option<PaletteOrder>,
+    global_palette: Option<Vec<u8>>,
+  ) -> Result<Frame, JsError> {
+    if self.buf_type != FrameBufType::IndexedPixels {
+      return Err(JsError::new(
+        "Only frames holding indexed pixels can be compacted",
+      ));
+    }
+
+    let mut w = self.w.clone();
+    palette::compact_frame_palette(
+      &mut w,
+      global_palette.as_deref(),
+      order.map(Into::into).unwrap_or_default(),
+    )?;
+    let mut frame = Frame::from_gif_frame(w, FrameBufType::IndexedPixels);
+    frame.lossy = self.lossy;
+    Ok(frame)
+  }
+
+  /// Returns a copy of the frame with its buffer converted to another representation.
+  /// Indexed pixels are resolved against the frame's palette and transparent index, or `globalPalette` if the frame has none.
+  ///
+  /// ### Notes:
+  /// - Converting to indexed pixels keeps the exact colors of frames with up to 256 colors, and quantizes the others.
+  /// - Speed needs to be in the range 1-30. Higher is faster, lower CPU usage but worse quality.
+  /// - Frames holding LZW data can not be converted.
+  /// @param bufType - The representation to convert to.
+  /// @param globalPalette - The palette to use if the frame has no palette of its own. (e.g. `<Decoder>.globalPalette`)
+  /// @param speed - The quantization speed, used when converting to indexed pixels.
+  #[wasm_bindgen(js_name = convertTo)]
+  pub fn convert_to(
+    &self,
+    buf_type: FrameBufType,
+    global_palette: Option<Vec<u8>>,
+    speed: Option<i32>,
+  ) -> Result<Frame, JsError> {
+    let mut frame = Self::from_gif_frame(
+      convert::convert_frame(
+        &self.w,
+        self.buf_type.into(),
+        buf_type.into(),
+        global_palette.as_deref(),
+        speed.unwrap_or(15),
+      )?,
+      buf_type,
+    );
+    frame.lossy = self.lossy;
+    Ok(frame)
+  }
+
+  /// Creates a frame from RGBA pixel data.
+  ///
+  /// ### Notes:
+  /// - Speed needs to be in the range 1-30. Higher is faster, lower CPU usage but worse quality.
+  /// - The size of `buffer` should match the expected size based on `width`, `height`.
+  /// - Only fully transparent pixels become transparent unless set otherwise with `alpha`.
+  #[wasm_bindgen(js_name = fromRgba)]
+  pub fn from_rgba(
+    width: u16,
+    height: u16,
+    mut buffer: Vec<u8>,
+    speed: Option<i32>,
+    alpha: Option<AlphaOptions>,
+  ) -> Result<Frame, JsError> {
+    let frame = quantize::quantize_rgba_with_alpha(
+      width,
+      height,
+      &mut buffer,
+      speed.unwrap_or(15),
+      &alpha.unwrap_or_default().w,
+    )?;
+    Ok(Self::from_gif_frame(frame, FrameBufType::IndexedPixels))
+  }
+
+  /// Creates a frame from RGB pixel data.
+  ///
+  /// ### Notes:
+  /// - Speed needs to be in the range 1-30. Higher is faster, lower CPU usage but worse quality.
+  /// - The size of `buffer` should match the expected size based on `width`, `height`.
+  #[wasm_bindgen(js_name = fromRgb)]
+  pub fn from_rgb(
+    width: u16,
+    height: u16,
+    buffer: &[u8],
+    speed: Option<i32>,
+  ) -> Result<Frame, JsError> {
+    let frame = quantize::quantize_rgb(width, height, buffer, speed.unwrap_or(15))?;
+    Ok(Self::from_gif_frame(frame, FrameBufType::IndexedPixels))
+  }
+
+  /// Creates a frame from indexed pixel data.
+  ///
+  /// ### Notes:
+  /// - The size of `buffer` should match the expected size based on `width`, `height`.
+  #[wasm_bindgen(js_name = fromIndexedPixels)]
+  pub fn from_indexed_pixels(
+    width: u16,
+    height: u16,
+    pixels: Vec<u8>,
+    palette: Option<Vec<u8>>,
+    transparent: Option<u8>,
+  ) -> Result<Frame, JsError> {
+    if pixels.len() != width as usize * height as usize {
+      return Err(JsError::new("Buffer size mismatch"));
+    }
+
+    let mut frame = gif::Frame::from_indexed_pixels(width, height, pixels, transparent);
+    frame.palette = palette;
+    Ok(Self::from_gif_frame(frame, FrameBufType::IndexedPixels))
+  }
+
+  /// Creates a frame from BGRA, ARGB, premultiplied RGBA or grayscale pixel data.
+  /// @param format - The layout of `buffer`. Defaults to `"rgba"`.
+  /// @param stride - The number of bytes from the start of one row to the next, for buffers with padding after each row.
+  ///
+  /// ### Notes:
+  /// - Speed needs to be in the range 1-30. Higher is faster, lower CPU usage but worse quality.
+  /// - Only fully transparent pixels become transparent unless set otherwise with `alpha`.
+  #[wasm_bindgen(js_name = fromPixels)]
+  pub fn from_pixels(
+    width: u16,
+    height: u16,
+    buffer: &[u8],
+    format: Option<InputFormat>,
+    stride: Option<u32>,
+    speed: Option<i32>,
+    alpha: Option<AlphaOptions>,
+  ) -> Result<Frame, JsError> {
+    let rgba = pixels::to_rgba(
+      buffer,
+      width as usize,
+      height as usize,
+      format.map_or(Ok(pixels::InputFormat::Rgba), TryInto::try_into)?,
+      stride.map(|s| s as usize),
+    )?;
+    Self::from_rgba(width, height, rgba, speed, alpha)
+  }
+
+  /// Creates a frame from hex colors, one per pixel. (e.g. `["#FF0000", "#00FF0080"]`)
+  ///
+  /// ### Notes:
+  /// - Speed needs to be in the range 1-30. Higher is faster, lower CPU usage but worse quality.
+  /// - The length of `buffer` should match the number of pixels based on `width`, `height`.
+  /// - Only fully transparent pixels become transparent unless set otherwise with `alpha`.
+  #[wasm_bindgen(js_name = fromHex)]
+  pub fn from_hex(
+    width: u16,
+    height: u16,
+    buffer: Vec<String>,
+    speed: Option<i32>,
+    alpha: Option<AlphaOptions>,
+  ) -> Result<Frame, JsError> {
+    Self::from_rgba(width, height, hex::hex_to_rgba(&buffer)?, speed, alpha)
+  }
+
+  ///
+  /// ### Notes:
+  /// - Speed needs to be in the range 1-30. Higher is faster, lower CPU usage but worse quality.
+  /// - Only fully transparent pixels become transparent unless set otherwise with `alpha`.
+  /// - For an APNG, only the first frame is used. See `Frame.fromApng`.
+  #[wasm_bindgen(js_name = fromPng)]
+  pub fn from_png(
+    buffer: &[u8],
+    speed: Option<i32>,
+    alpha: Option<AlphaOptions>,
+  ) -> Result<Frame, JsError> {
+    let png = apng::decode_png(buffer)?
+      .into_iter()
+      .next()
+      .ok_or_else(|| JsError::new("PNG has no frames"))?;
+    Self::from_png_frame(png, speed.unwrap_or(15), &alpha.unwrap_or_default().w)
+  }
+
+  /// Creates frames from every frame of an APNG, keeping their offsets, delays and disposal methods.
+  ///
+  /// ### Notes:
+  /// - Speed needs to be in the range 1-30. Higher is faster, lower CPU usage but worse quality.
+  /// - Only fully transparent pixels become transparent unless set otherwise with `alpha`.
+  /// - APNGs whose frames clear pixels with the `source` blend operation are composited into full-size frames, as GIF frames are always drawn over the canvas.
+  #[wasm_bindgen(js_name = fromApng)]
+  pub fn from_apng(
+    buffer: &[u8],
+    speed: Option<i32>,
+    alpha: Option<AlphaOptions>,
+  ) -> Result<Vec<Frame>, JsError> {
+    let speed = speed.unwrap_or(15);
+    let alpha = alpha.unwrap_or_default().w;
+    apng::decode_png(buffer)?
+      .into_iter()
+      .map(|png| Self::from_png_frame(png, speed, &alpha))
+      .collect()
+  }
+}
+
+impl Frame {
+  pub(crate) fn from_gif_frame(w: gif::Frame<'static>, buf_type: FrameBufType) -> Frame {
+    Frame {
+      w,
+      buf_type,
+      lossy: None,
+      source_palette: None,
+    }
+  }
+
+  fn from_png_frame(
+    mut png: apng::PngFrame,
+    speed: i32,
+    alpha: &quantize::AlphaOptions,
+  ) -> Result<Frame, JsError> {
+    // Clearing transparent pixels first keeps them to a single palette entry. Quantizing applies the options again, which leaves them as they are.
+    quantize::apply_alpha(&mut png.rgba, png.width, alpha);
+
+    let mut frame =
+      quantize::quantize_rgba_with_alpha(png.width, png.height, &mut png.rgba, speed, alpha)?;
+    frame.left = png.left;
+    frame.top = png.top;
+    frame.delay = png.delay;
+    frame.dispose = png.dispose;
+    Ok(Self::from_gif_frame(frame, FrameBufType::IndexedPixels))
+  }
+}
@@ -0,0 +1,95 @@
+use crate::hash;
+use wasm_bindgen::prelude::*;
+
+/// The hashes of a composited frame, see `<Decoder>.fingerprint`.
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone)]
+pub struct FrameHash {
+  /// The difference hash as 16 hex digits, see `dhash`.
+  pub dhash: String,
+  /// The perceptual hash as 16 hex digits, see `phash`.
+  pub phash: String,
+  /// How long the frame is shown in units of 10ms.
+  pub delay: u32,
+}
+
+/// The hashes of the composited frames of a GIF along with their timing, see `<Decoder>.fingerprint`.
+#[wasm_bindgen]
+pub struct Fingerprint {
+  w: hash::Fingerprint,
+}
+
+#[wasm_bindgen]
+impl Fingerprint {
+  /// One entry per frame. Consecutive frames with the same hashes are merged into one, adding up their delays.
+  #[wasm_bindgen(getter)]
+  pub fn frames(&self) -> Vec<FrameHash> {
+    self
+      .w
+      .frames
+      .iter()
+      .map(|h| FrameHash {
+        dhash: hash::format_hash(h.dhash),
+        phash: hash::format_hash(h.phash),
+        delay: h.delay,
+      })
+      .collect()
+  }
+
+  /// The summed delays of all frames in units of 10ms.
+  #[wasm_bindgen(getter)]
+  pub fn duration(&self) -> u32 {
+    self.w.duration()
+  }
+
+  /// How alike this GIF is to another, see `fingerprintSimilarity`.
+  pub fn similarity(&self, other: &Fingerprint) -> f64 {
+    self.w.similarity(&other.w)
+  }
+}
+
+impl From<hash::Fingerprint> for Fingerprint {
+  fn from(w: hash::Fingerprint) -> Self {
+    Self { w }
+  }
+}
+
+/// The difference hash of RGBA pixels as 16 hex digits.
+/// Each bit tells whether a cell of a 9 by 8 grayscale grid is brighter than its right neighbour.
+///
+/// ### Notes:
+/// - Fast, and robust to resizing and re-encoding. See `hashDistance` to compare hashes.
+/// - The size of `rgba` should match the expected size based on `width`, `height`.
+#[wasm_bindgen]
+pub fn dhash(rgba: &[u8], width: u16, height: u16) -> Result<String, JsError> {
+  Ok(hash::format_hash(hash::dhash(rgba, width, height)?))
+}
+
+/// The perceptual hash of RGBA pixels as 16 hex digits.
+/// Each bit tells whether one of the 64 lowest frequencies of the DCT of a 32 by 32 grayscale version is above their median.
+///
+/// ### Notes:
+/// - Slower than `dhash`, but also robust to changes in brightness and contrast. See `hashDistance` to compare hashes.
+/// - The size of `rgba` should match the expected size based on `width`, `height`.
+#[wasm_bindgen]
+pub fn phash(rgba: &[u8], width: u16, height: u16) -> Result<String, JsError> {
+  Ok(hash::format_hash(hash::phash(rgba, width, height)?))
+}
+
+/// The number of bits two hashes from `dhash` or `phash` differ in, from 0 for identical images to 64.
+/// Images with a distance of up to about 10 usually look alike.
+#[wasm_bindgen(js_name = hashDistance)]
+pub fn hash_distance(a: &str, b: &str) -> Result<u32, JsError> {
+  Ok(hash::distance(hash::parse_hash(a)?, hash::parse_hash(b)?))
+}
+
+/// How alike two GIFs are based on their fingerprints, in the range 0-1 where 1 means they look the same throughout.
+///
+/// ### Notes:
+/// - Both animations are stretched to the same length and compared over time, so every frame counts as much as it is shown.
+/// - Speeding a GIF up or down keeps it similar, reordering its frames does not.
+/// - Unrelated GIFs score around 0.5; re-encoded or resized copies usually score above 0.9.
+#[wasm_bindgen(js_name = fingerprintSimilarity)]
+pub fn fingerprint_similarity(a: &Fingerprint, b: &Fingerprint) -> f64 {
+  a.similarity(b)
+}
@@ -0,0 +1,12 @@
+//! wasm-bindgen bindings wrapping the core of the crate, mirroring the N-API bindings in [`crate::node`].
+//!
+//! Build with `--no-default-features --features wasm` for the `wasm32-unknown-unknown` target.
+
+pub mod compare;
+pub mod decoder;
+pub mod encoder;
+pub mod enums;
+pub mod frame;
+pub mod hash;
+pub mod neuquant;
+pub mod util;
@@ -0,0 +1,48 @@
+use wasm_bindgen::prelude::*;
+
+/// A NeuQuant instance.
+#[wasm_bindgen]
+pub struct NeuQuant {
+  w: color_quant::NeuQuant,
+}
+
+#[wasm_bindgen]
+impl NeuQuant {
+  /// Create a new NeuQuant instance.
+  #[wasm_bindgen(constructor)]
+  pub fn new(sample: i32, colors: u32, pixels: &[u8]) -> NeuQuant {
+    NeuQuant {
+      w: color_quant::NeuQuant::new(sample, colors as usize, pixels),
+    }
+  }
+
+  /// Maps the rgba-pixel in-place to the best-matching color in the color map.
+  #[wasm_bindgen(js_name = mapPixel)]
+  pub fn map_pixel(&self, pixel: &mut [u8]) {
+    self.w.map_pixel(pixel);
+  }
+
+  /// Finds the best-matching index in the color map.
+  /// `pixel` is assumed to be in RGBA format.
+  #[wasm_bindgen(js_name = indexOf)]
+  pub fn index_of(&self, pixel: &[u8]) -> u32 {
+    self.w.index_of(pixel) as u32
+  }
+
+  /// Lookup pixel values for color at `idx` in the colormap.
+  pub fn lookup(&self, idx: u32) -> Option<Vec<u8>> {
+    self.w.lookup(idx as usize).map(|p| p.to_vec())
+  }
+
+  /// Returns the RGBA color map calculated from the sample.
+  #[wasm_bindgen(js_name = colorMapRgba)]
+  pub fn color_map_rgba(&self) -> Vec<u8> {
+    self.w.color_map_rgba()
+  }
+
+  /// Returns the RGB color map calculated from the sample.
+  #[wasm_bindgen(js_name = colorMapRgb)]
+  pub fn color_map_rgb(&self) -> Vec<u8> {
+    self.w.color_map_rgb()
+  }
+}
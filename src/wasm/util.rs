@@ -0,0 +1,383 @@
+use super::enums::{ColorSpace, DistanceMetric, PaletteOrder};
+use crate::{color, hex, palette, pixels, stats};
+use wasm_bindgen::prelude::*;
+
+fn text_format(
+  always_include_alpha: Option<bool>,
+  allow_short: Option<bool>,
+  lowercase: Option<bool>,
+  css: Option<bool>,
+) -> hex::TextFormat {
+  hex::TextFormat {
+    always_include_alpha: always_include_alpha.unwrap_or(false),
+    allow_short: allow_short.unwrap_or(false),
+    lowercase: lowercase.unwrap_or(false),
+    css: css.unwrap_or(false),
+  }
+}
+
+/// Converts RGBA pixels to one color string per pixel.
+/// @param lowercase - Write hex digits in lowercase.
+/// @param css - Write CSS `rgb()`/`rgba()` functions instead of hex colors, with alpha in the range 0-1.
+#[wasm_bindgen(js_name = rgbaToHex)]
+pub fn rgba_to_hex(
+  rgba: &[u8],
+  always_include_alpha: Option<bool>,
+  allow_short: Option<bool>,
+  lowercase: Option<bool>,
+  css: Option<bool>,
+) -> Result<Vec<String>, JsError> {
+  Ok(hex::rgba_to_strings(
+    rgba,
+    text_format(always_include_alpha, allow_short, lowercase, css),
+  )?)
+}
+
+/// Converts RGB pixels to one color string per pixel. See `rgbaToHex`.
+#[wasm_bindgen(js_name = rgbToHex)]
+pub fn rgb_to_hex(
+  rgb: &[u8],
+  allow_short: Option<bool>,
+  lowercase: Option<bool>,
+  css: Option<bool>,
+) -> Result<Vec<String>, JsError> {
+  Ok(hex::rgb_to_strings(
+    rgb,
+    text_format(None, allow_short, lowercase, css),
+  )?)
+}
+
+#[wasm_bindgen(js_name = hexToRgba)]
+pub fn hex_to_rgba(hexes: Vec<String>) -> Result<Vec<u8>, JsError> {
+  Ok(hex::hex_to_rgba(&hexes)?)
+}
+
+#[wasm_bindgen(js_name = hexToRgb)]
+pub fn hex_to_rgb(hexes: Vec<String>) -> Result<Vec<u8>, JsError> {
+  Ok(hex::hex_to_rgb(&hexes)?)
+}
+
+#[wasm_bindgen(js_name = indexedToRgba)]
+pub fn indexed_to_rgba(pixels: &[u8], palette: &[u8], transparent_index: Option<u8>) -> Vec<u8> {
+  hex::indexed_to_rgba(pixels, palette, transparent_index)
+}
+
+/// Resolves indexed pixels against an RGB palette as one color string per pixel. See `rgbaToHex`.
+#[wasm_bindgen(js_name = indexedToHex)]
+pub fn indexed_to_hex(
+  pixels: &[u8],
+  palette: &[u8],
+  transparent_index: Option<u8>,
+  always_include_alpha: Option<bool>,
+  allow_short: Option<bool>,
+  lowercase: Option<bool>,
+  css: Option<bool>,
+) -> Vec<String> {
+  hex::indexed_to_strings(
+    pixels,
+    palette,
+    transparent_index,
+    text_format(always_include_alpha, allow_short, lowercase, css),
+  )
+}
+
+/// Converts RGB or RGBA pixels to another color space, three floats per pixel.
+/// @param alpha - Whether the pixels are RGBA. The alpha channel is kept as a fourth float in the range 0-1.
+#[wasm_bindgen(js_name = rgbToColorSpace)]
+pub fn rgb_to_color_space(
+  pixels: &[u8],
+  space: ColorSpace,
+  alpha: Option<bool>,
+) -> Result<Vec<f32>, JsError> {
+  Ok(color::pixels_to_space(
+    pixels,
+    space.into(),
+    alpha.unwrap_or(false),
+  )?)
+}
+
+/// Converts colors from another color space back to RGB or RGBA pixels, clamping colors outside the sRGB gamut.
+/// @param alpha - Whether the colors have a fourth alpha float in the range 0-1, producing RGBA pixels.
+#[wasm_bindgen(js_name = colorSpaceToRgb)]
+pub fn color_space_to_rgb(
+  values: &[f32],
+  space: ColorSpace,
+  alpha: Option<bool>,
+) -> Result<Vec<u8>, JsError> {
+  Ok(color::space_to_pixels(
+    values,
+    space.into(),
+    alpha.unwrap_or(false),
+  )?)
+}
+
+/// Measures how different each pair of pixels in two RGB or RGBA buffers of the same length looks.
+/// @param alpha - Whether the pixels are RGBA. The alpha channel is ignored.
+/// @returns One distance per pixel.
+#[wasm_bindgen(js_name = colorDistance)]
+pub fn color_distance(
+  a: &[u8],
+  b: &[u8],
+  metric: DistanceMetric,
+  alpha: Option<bool>,
+) -> Result<Vec<f32>, JsError> {
+  Ok(color::distances(
+    a,
+    b,
+    metric.into(),
+    alpha.unwrap_or(false),
+  )?)
+}
+
+/// Returns the index of the palette color that looks closest to an RGB color, or `undefined` for an empty palette.
+#[wasm_bindgen(js_name = nearestColor)]
+pub fn nearest_color(
+  palette: &[u8],
+  color: &[u8],
+  metric: DistanceMetric,
+) -> Result<Option<u32>, JsError> {
+  let [r, g, b, ..] = *color else {
+    return Err(JsError::new("Color must have at least 3 components"));
+  };
+  Ok(color::nearest(palette, [r, g, b], metric.into()).map(|i| i as u32))
+}
+
+/// Drops the alpha channel of RGBA pixels.
+#[wasm_bindgen(js_name = rgbaToRgb)]
+pub fn rgba_to_rgb(rgba: &[u8]) -> Vec<u8> {
+  pixels::rgba_to_rgb(rgba)
+}
+
+/// Adds an opaque alpha channel to RGB pixels.
+#[wasm_bindgen(js_name = rgbToRgba)]
+pub fn rgb_to_rgba(rgb: &[u8]) -> Vec<u8> {
+  pixels::rgb_to_rgba(rgb)
+}
+
+/// Swaps the red and blue channels of BGRA pixels. The same call converts RGBA pixels to BGRA.
+#[wasm_bindgen(js_name = bgraToRgba)]
+pub fn bgra_to_rgba(bgra: &[u8]) -> Vec<u8> {
+  let mut rgba = bgra.to_vec();
+  pixels::swap_red_blue(&mut rgba);
+  rgba
+}
+
+/// Multiplies the color channels of RGBA pixels by their alpha, as expected by canvas and GPU APIs.
+#[wasm_bindgen(js_name = premultiplyAlpha)]
+pub fn premultiply_alpha(rgba: &[u8]) -> Vec<u8> {
+  let mut premultiplied = rgba.to_vec();
+  pixels::premultiply(&mut premultiplied);
+  premultiplied
+}
+
+/// Reverses `premultiplyAlpha`. Fully transparent pixels are left unchanged.
+#[wasm_bindgen(js_name = unpremultiplyAlpha)]
+pub fn unpremultiply_alpha(rgba: &[u8]) -> Vec<u8> {
+  let mut straight = rgba.to_vec();
+  pixels::unpremultiply(&mut straight);
+  straight
+}
+
+/// Which pixels `extractPalette` leaves out.
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct PaletteOptions {
+  w: palette::ExtractOptions,
+}
+
+#[wasm_bindgen]
+impl PaletteOptions {
+  /// Create new palette options. Transparent pixels are skipped until changed.
+  #[wasm_bindgen(constructor)]
+  pub fn new() -> PaletteOptions {
+    Self::default()
+  }
+
+  /// Skip pixels with an alpha below 128.
+  #[wasm_bindgen(js_name = setIgnoreTransparent)]
+  pub fn set_ignore_transparent(&mut self, value: bool) {
+    self.w.ignore_transparent = value;
+  }
+
+  /// Skip near-white pixels.
+  #[wasm_bindgen(js_name = setIgnoreWhite)]
+  pub fn set_ignore_white(&mut self, value: bool) {
+    self.w.ignore_white = value;
+  }
+
+  /// Skip near-black pixels.
+  #[wasm_bindgen(js_name = setIgnoreBlack)]
+  pub fn set_ignore_black(&mut self, value: bool) {
+    self.w.ignore_black = value;
+  }
+
+  /// How close to white or black every channel of a pixel needs to be to be skipped. The default is `16`.
+  #[wasm_bindgen(js_name = setTolerance)]
+  pub fn set_tolerance(&mut self, value: u8) {
+    self.w.tolerance = value;
+  }
+
+  /// The quantization speed in the range 1-30, used when there are more distinct colors than requested. The default is `10`.
+  #[wasm_bindgen(js_name = setSpeed)]
+  pub fn set_speed(&mut self, value: i32) {
+    self.w.speed = value;
+  }
+}
+
+/// A color found by `extractPalette`.
+#[wasm_bindgen(getter_with_clone)]
+pub struct PaletteColor {
+  /// The color as `#RRGGBB`.
+  pub color: String,
+  /// The color as red, green and blue.
+  pub rgb: Vec<u8>,
+  /// The number of pixels closest to this color.
+  pub count: u32,
+  /// The share of the counted pixels closest to this color, in the range 0-100.
+  pub percentage: f64,
+}
+
+pub(crate) fn extract_palette_from(
+  buffers: impl IntoIterator<Item = impl AsRef<[u8]>>,
+  count: u32,
+  options: Option<PaletteOptions>,
+) -> Result<Vec<PaletteColor>, JsError> {
+  let mut builder = palette_builder(count, options)?;
+  for buffer in buffers {
+    builder.add_rgba(buffer.as_ref())?;
+  }
+  Ok(palette_colors(builder))
+}
+
+pub(crate) fn palette_builder(
+  count: u32,
+  options: Option<PaletteOptions>,
+) -> Result<palette::PaletteBuilder, JsError> {
+  Ok(palette::PaletteBuilder::new(
+    count as usize,
+    &options.unwrap_or_default().w,
+  )?)
+}
+
+pub(crate) fn palette_colors(builder: palette::PaletteBuilder) -> Vec<PaletteColor> {
+  builder
+    .finish()
+    .into_iter()
+    .map(|c| {
+      let [r, g, b] = c.rgb;
+      PaletteColor {
+        color: hex::TextFormat::default().format(r, g, b, 255),
+        rgb: c.rgb.to_vec(),
+        count: c.count as u32,
+        percentage: c.percentage,
+      }
+    })
+    .collect()
+}
+
+/// A rectangle on the GIF's canvas.
+#[wasm_bindgen]
+#[derive(Clone, Copy)]
+pub struct Bounds {
+  pub left: u32,
+  pub top: u32,
+  pub width: u32,
+  pub height: u32,
+}
+
+/// Pixel statistics of a frame or a whole GIF, see `<Frame>.stats` and `<Decoder>.stats`.
+#[wasm_bindgen(getter_with_clone)]
+pub struct ImageStats {
+  /// The number of pixels counted.
+  pub pixels: u32,
+  /// The number of distinct RGBA colors.
+  #[wasm_bindgen(js_name = distinctColors)]
+  pub distinct_colors: u32,
+  /// The mean of the red, green, blue and alpha channels, in the range 0-255.
+  pub mean: Vec<f64>,
+  /// The variance of the red, green, blue and alpha channels.
+  pub variance: Vec<f64>,
+  /// The share of fully transparent pixels, in the range 0-1.
+  #[wasm_bindgen(js_name = transparentFraction)]
+  pub transparent_fraction: f64,
+  /// The smallest rectangle on the canvas holding every pixel that is not fully transparent. Missing if all pixels are transparent.
+  pub bounds: Option<Bounds>,
+  /// How many pixels use each of the 256 palette indices. Only set for indexed frames.
+  #[wasm_bindgen(js_name = paletteUsage)]
+  pub palette_usage: Option<Vec<u32>>,
+}
+
+impl From<stats::Stats> for ImageStats {
+  fn from(s: stats::Stats) -> Self {
+    Self {
+      pixels: s.pixels as u32,
+      distinct_colors: s.distinct_colors as u32,
+      mean: s.mean.to_vec(),
+      variance: s.variance.to_vec(),
+      transparent_fraction: s.transparent_fraction,
+      bounds: s.bounds.map(|b| Bounds {
+        left: b.left,
+        top: b.top,
+        width: b.width,
+        height: b.height,
+      }),
+      palette_usage: s
+        .palette_usage
+        .map(|u| u.into_iter().map(|n| n as u32).collect()),
+    }
+  }
+}
+
+/// Finds up to `count` colors that best represent RGBA pixels, most common first.
+/// See `<Frame>.extractPalette` and `<Decoder>.extractPalette` to use frames or a whole GIF.
+///
+/// ### Notes:
+/// - Images with at most `count` distinct colors are counted exactly, others are quantized with NeuQuant.
+/// - `count` needs to be in the range 1-256.
+#[wasm_bindgen(js_name = extractPalette)]
+pub fn extract_palette(
+  rgba: &[u8],
+  count: u32,
+  options: Option<PaletteOptions>,
+) -> Result<Vec<PaletteColor>, JsError> {
+  extract_palette_from([rgba], count, options)
+}
+
+/// Indexed pixels with a palette rewritten by `compactPalette`.
+#[wasm_bindgen(getter_with_clone)]
+pub struct CompactedPalette {
+  /// The pixels, pointing into the new palette.
+  pub pixels: Vec<u8>,
+  /// The new palette, padded to a power of two.
+  pub palette: Vec<u8>,
+  /// The new transparent index. Missing if no pixel is transparent.
+  pub transparent: Option<u8>,
+}
+
+/// Drops unused palette entries and merges duplicate colors of indexed pixels, remapping the pixels and the transparent index.
+/// See `<Frame>.compactPalette` to compact a frame's palette.
+///
+/// ### Notes:
+/// - The palette is padded with black to the smallest power of two that GIF palettes can hold.
+/// - The transparent entry is kept apart from opaque entries of the same color, and dropped if no pixel uses it.
+/// @param order - How to order the colors that are kept. Defaults to `PaletteOrder.Original`.
+#[wasm_bindgen(js_name = compactPalette)]
+pub fn compact_palette(
+  pixels: &[u8],
+  palette: &[u8],
+  transparent: Option<u8>,
+  order: Option<PaletteOrder>,
+) -> Result<CompactedPalette, JsError> {
+  let mut pixels = pixels.to_vec();
+  let (palette, transparent) = palette::compact_palette(
+    &mut pixels,
+    palette,
+    transparent,
+    order.map(Into::into).unwrap_or_default(),
+  )?;
+  Ok(CompactedPalette {
+    pixels,
+    palette,
+    transparent,
+  })
+}
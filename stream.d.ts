@@ -0,0 +1,44 @@
+// `index.d.ts` is generated by `napi build`, so the declarations of the helpers in `stream.js` live here.
+
+import type { Duplex, Transform } from 'node:stream'
+import type { DecodeOptions, Frame } from './index'
+
+export * from './index'
+
+declare module './index' {
+  interface Decoder {
+    /** `for await...of` reads the remaining frames off the main thread, see `<Decoder>.readNextFrameAsync`. */
+    [Symbol.asyncIterator](): AsyncIterator<Frame, void, void>
+  }
+}
+
+export interface DecodeStreamOptions {
+  /** The options to open the decoder with. */
+  decodeOptions?: DecodeOptions
+  /** Whether to emit the composited canvas, like `<Decoder>.readNextCompositedFrame`. */
+  composited?: boolean
+  /** The number of frames to buffer before applying backpressure. */
+  highWaterMark?: number
+}
+export interface EncodeStreamOptions {
+  /** The global color palette. */
+  palette?: Uint8Array
+  /** The repeat count, see `<Encoder>.setRepeat`. */
+  repeat?: number
+  /** The lossy LZW compression level, see `<Encoder>.setLossy`. */
+  lossy?: number
+}
+/**
+ * Creates a stream that takes GIF bytes and emits the decoded `Frame`s in object mode.
+ * Each frame is decoded off the main thread as soon as its bytes have arrived, and writes wait while the consumer is behind.
+ * @param options - The decoder options and whether to emit composited frames.
+ */
+export declare function createDecodeStream(options?: DecodeStreamOptions): Duplex
+/**
+ * Creates a stream that takes `Frame`s in object mode and emits the GIF bytes as each frame is encoded.
+ * Frames holding LZW data are added with `<Encoder>.addRawFrame`.
+ * @param width - The gif width.
+ * @param height - The gif height.
+ * @param options - The palette, repeat count and lossy level of the gif.
+ */
+export declare function createEncodeStream(width: number, height: number, options?: EncodeStreamOptions): Transform
@@ -0,0 +1,108 @@
+// `index.js` and `index.d.ts` are generated by `napi build`, so the helpers built on top of the native bindings
+// live here. This is the package entry point, re-exporting the bindings along with the helpers.
+const { Duplex, Transform } = require('stream')
+
+const binding = require('./index.js')
+const { Decoder, DecoderFeed, Encoder, FrameBufType } = binding
+
+// N-API classes can't declare async generators, so `for await...of` is built on `readNextFrameAsync`.
+Decoder.prototype[Symbol.asyncIterator] = async function* () {
+  let frame
+  while ((frame = await this.readNextFrameAsync())) yield frame
+}
+
+function createDecodeStream(options = {}) {
+  const feed = new DecoderFeed(options.decodeOptions)
+  let decoder = null
+  let read = 0
+  let ended = false
+  let done = false
+  let reading = false
+  let wanted = false
+  let pendingWrite = null
+
+  const releaseWrite = () => {
+    const callback = pendingWrite
+    pendingWrite = null
+    if (callback) callback()
+  }
+
+  // Reads the frames that have completely arrived while the readable side wants more. The pending write is
+  // only released once they have all been pushed, so that writes wait for slow consumers.
+  const pump = () => {
+    if (reading || done) return
+    if (!decoder || (!ended && read >= feed.frames)) return releaseWrite()
+    if (!wanted) return
+    reading = true
+    decoder.readNextFrameAsync(options.composited).then(
+      (frame) => {
+        reading = false
+        read++
+        if (frame === null) {
+          done = true
+          stream.push(null)
+          return releaseWrite()
+        }
+        wanted = stream.push(frame)
+        pump()
+      },
+      (err) => stream.destroy(err),
+    )
+  }
+
+  const stream = new Duplex({
+    readableObjectMode: true,
+    readableHighWaterMark: options.highWaterMark,
+    write(chunk, _encoding, callback) {
+      try {
+        feed.push(chunk)
+        if (!decoder) decoder = feed.decoder()
+      } catch (err) {
+        return callback(err)
+      }
+      pendingWrite = callback
+      pump()
+    },
+    final(callback) {
+      ended = true
+      try {
+        feed.end()
+        if (!decoder) decoder = feed.decoder()
+      } catch (err) {
+        return callback(err)
+      }
+      callback()
+      pump()
+    },
+    read() {
+      wanted = true
+      pump()
+    },
+  })
+
+  return stream
+}
+
+function createEncodeStream(width, height, options = {}) {
+  const encoder = new Encoder(width, height, options.palette)
+  if (options.repeat != null) encoder.setRepeat(options.repeat)
+  if (options.lossy != null) encoder.setLossy(options.lossy)
+
+  return new Transform({
+    writableObjectMode: true,
+    transform(frame, _encoding, callback) {
+      try {
+        if (frame.bufType === FrameBufType.Lzw) encoder.addRawFrame(frame)
+        else encoder.addFrame(frame)
+      } catch (err) {
+        return callback(err)
+      }
+      callback(null, encoder.takeBuffer())
+    },
+    flush(callback) {
+      callback(null, encoder.finish())
+    },
+  })
+}
+
+module.exports = { ...binding, createDecodeStream, createEncodeStream }